@@ -0,0 +1,273 @@
+use std::{
+    any::TypeId,
+    path::PathBuf,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use iced::{
+    futures::{channel::mpsc::Sender, executor::block_on, SinkExt},
+    subscription, Subscription,
+};
+use sysinfo::Pid;
+
+use crate::{
+    recording::{Recorder, RecordingHeader, RECORDING_VERSION},
+    system_source::{SysinfoSource, SystemSource},
+    types::{process_columns::ProcessColumn, snapshot::Snapshot},
+};
+
+/// What the UI currently needs sampled beyond the basics, so the collector only reads what is shown
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectorRequest {
+    /// Time between refreshes
+    pub interval: Duration,
+    /// Process table columns whose values are read from /proc on top of the regular refresh
+    pub process_columns: Vec<ProcessColumn>,
+    /// Whether to read the cgroup of each process, which applications are matched by
+    pub cgroups: bool,
+    /// The process to collect details for
+    pub details_pid: Option<Pid>,
+    /// Where to record every snapshot to, if anywhere
+    pub recording: Option<PathBuf>,
+}
+
+impl CollectorRequest {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            process_columns: Vec::new(),
+            cgroups: false,
+            details_pid: None,
+            recording: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum CollectorEvent {
+    /// The collector started, and takes new requests through the sender
+    Ready(mpsc::Sender<CollectorRequest>),
+    Snapshot(Arc<Snapshot>),
+    /// Recording stopped because the file couldn't be written
+    RecordingFailed(String),
+}
+
+/// Samples the system on a background thread, sending a snapshot every interval.
+/// A new request wakes the collector so the UI gets what it asked for right away
+pub fn collect(request: CollectorRequest) -> Subscription<CollectorEvent> {
+    subscription::channel(
+        TypeId::of::<Collector>(),
+        1,
+        |mut output: Sender<CollectorEvent>| async move {
+            let (request_sender, request_receiver) = mpsc::channel();
+
+            let _ = output.send(CollectorEvent::Ready(request_sender)).await;
+
+            thread::spawn(move || Collector::new(request).run(request_receiver, output));
+
+            // The thread does the work, this only keeps the subscription alive
+            std::future::pending().await
+        },
+    )
+}
+
+/// Samples a system source into snapshots. Lives on the collector thread, or the main one when headless
+pub struct Collector {
+    request: CollectorRequest,
+    source: Box<dyn SystemSource>,
+    /// When the source was last refreshed
+    last_refresh: Instant,
+    /// The recording last requested, even if it couldn't be started
+    recording: Option<PathBuf>,
+    recorder: Option<Recorder>,
+}
+
+impl Collector {
+    /// Samples the system we're running on
+    pub fn new(request: CollectorRequest) -> Self {
+        Self::with_source(request, Box::new(SysinfoSource::new()))
+    }
+
+    pub fn with_source(request: CollectorRequest, source: Box<dyn SystemSource>) -> Self {
+        Self {
+            request,
+            source,
+            last_refresh: Instant::now(),
+            recording: None,
+            recorder: None,
+        }
+    }
+
+    fn run(mut self, requests: Receiver<CollectorRequest>, mut output: Sender<CollectorEvent>) {
+        loop {
+            match requests.recv_timeout(self.request.interval) {
+                Ok(request) => {
+                    self.request = request;
+
+                    // Only the latest request matters
+                    while let Ok(request) = requests.try_recv() {
+                        self.request = request;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                // The app is gone, or replaying a recording instead
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.stop_recording(&mut output);
+                    return;
+                }
+            }
+
+            self.sync_recording(&mut output);
+
+            let snapshot = Arc::new(self.sample());
+
+            if let Some(recorder) = &mut self.recorder {
+                if let Err(error) = recorder.record(&snapshot) {
+                    self.recorder = None;
+                    let _ = block_on(output.send(CollectorEvent::RecordingFailed(error)));
+                }
+            }
+
+            if block_on(output.send(CollectorEvent::Snapshot(snapshot))).is_err() {
+                self.stop_recording(&mut output);
+                return;
+            }
+        }
+    }
+
+    /// Refreshes and returns the new snapshot, waiting first if the last refresh was too recent
+    pub fn sample(&mut self) -> Snapshot {
+        let since_refresh = self.last_refresh.elapsed();
+        let minimum_interval = self.source.minimum_refresh_interval();
+
+        if since_refresh < minimum_interval {
+            thread::sleep(minimum_interval - since_refresh);
+        }
+
+        self.collect()
+    }
+
+    /// Starts or stops recording if the request asks for a different recording than before
+    fn sync_recording(&mut self, output: &mut Sender<CollectorEvent>) {
+        if self.recording == self.request.recording {
+            return;
+        }
+
+        self.stop_recording(output);
+        self.recording = self.request.recording.clone();
+
+        let Some(path) = &self.recording else {
+            return;
+        };
+
+        let header = RecordingHeader {
+            version: RECORDING_VERSION,
+            logical_core_count: self.source.cpus().len() as u32,
+            physical_core_count: self.source.physical_core_count(),
+            cpu_brand: self.source.cpu_brand(),
+            cpu_info: self.source.cpu_info(),
+        };
+
+        match Recorder::create(path, &header) {
+            Ok(recorder) => self.recorder = Some(recorder),
+            Err(error) => {
+                let _ = block_on(output.send(CollectorEvent::RecordingFailed(error)));
+            }
+        }
+    }
+
+    fn stop_recording(&mut self, output: &mut Sender<CollectorEvent>) {
+        let Some(recorder) = self.recorder.take() else {
+            return;
+        };
+
+        if let Err(error) = recorder.finish() {
+            let _ = block_on(output.send(CollectorEvent::RecordingFailed(error)));
+        }
+    }
+
+    fn collect(&mut self) -> Snapshot {
+        self.source.refresh();
+
+        let now = Instant::now();
+        let refresh_interval = now - self.last_refresh;
+        self.last_refresh = now;
+
+        let process_details = self
+            .request
+            .details_pid
+            .map(|pid| self.source.process_details(pid));
+
+        Snapshot {
+            timestamp: SystemTime::now(),
+            refresh_interval,
+            cpus: self.source.cpus(),
+            cpu_times: self.source.cpu_times(),
+            cpu_temperatures: self.source.cpu_temperatures(),
+            memory: self.source.memory(),
+            pressure: self.source.pressure(),
+            disks: self.source.disks(),
+            batteries: self.source.batteries(),
+            processes: self.source.processes(&self.request),
+            process_details,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use sysinfo::Pid;
+
+    use crate::system_source::FixtureSource;
+
+    use super::{Collector, CollectorRequest};
+
+    fn collector() -> Collector {
+        Collector::with_source(
+            CollectorRequest::new(Duration::from_secs(1)),
+            Box::new(FixtureSource::load("desktop")),
+        )
+    }
+
+    #[test]
+    fn samples_each_frame_then_repeats_the_last() {
+        let mut collector = collector();
+
+        let first = collector.sample();
+        assert_eq!(first.cpus.len(), 4);
+        assert_eq!(first.disks.len(), 1);
+        assert_eq!(first.processes.len(), 4);
+        assert!(first.process_details.is_none());
+
+        let second = collector.sample();
+        assert_eq!(second.cpus[0].usage, 100.);
+        assert!(second.disks.is_empty());
+        assert_eq!(second.processes.len(), 1);
+        assert!(second.timestamp >= first.timestamp);
+
+        let third = collector.sample();
+        assert_eq!(third.memory.swap_used, second.memory.swap_used);
+    }
+
+    #[test]
+    fn collects_details_for_the_requested_process() {
+        let mut collector = collector();
+
+        let mut request = CollectorRequest::new(Duration::from_secs(1));
+        request.details_pid = Some(Pid::from_u32(200));
+        collector.request = request;
+
+        let snapshot = collector.sample();
+
+        let details = snapshot.process_details.expect("details were requested");
+        assert_eq!(details.pid, Pid::from_u32(200));
+        assert!(!details.exists);
+    }
+}
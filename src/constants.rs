@@ -23,6 +23,8 @@ pub mod font_sizes {
 pub mod sizings {
     pub const MAX_MAIN_CONTENT_CHILDREN_WIDTH: u16 = 800;
     pub const DEFAULT_CHART_HEIGHT: f32 = 300.;
+    pub const SPARKLINE_WIDTH: f32 = 60.;
+    pub const SPARKLINE_HEIGHT: f32 = 20.;
 }
 
 #[derive(Debug, Default, Copy, Clone)]
@@ -33,7 +35,7 @@ pub enum DisplayState {
 }
 
 pub const BYTE_UNITS_DECIMAL: [&str; 9] =
-    ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB", "ZiB", "YiB"];
+    ["B", "KB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"];
 pub const BYTE_UNITS_BINARY: [&str; 9] =
     ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB", "ZiB", "YiB"];
 pub const FREQUENCY_UNITS: [&str; 3] = ["MHz", "GHz", "THz"];
@@ -42,10 +44,6 @@ pub static ICON: &[u8] = include_bytes!("icon.png");
 /// How many decimals to show for percentage values.
 /// TODO: Make this user configurable
 pub const PERCENT_PRECISION: u8 = 1;
-/// How many ticks to keep contigious history for
-// TODO: Make this user configurable
-pub const HISTORY_TICKS: u32 = 30;
-
 pub mod custom_theme {
     use iced::Color;
 
@@ -104,4 +102,57 @@ pub mod custom_theme {
         b: 0.7,
         a: 1.,
     };
+
+    pub const DANGER: Color = Color {
+        r: 0.96,
+        g: 0.11,
+        b: 0.14,
+        a: 1.,
+    };
+
+    /// The layers of stacked charts, from the bottom up
+    pub const CHART_LAYERS: [Color; 7] = [
+        Color {
+            r: 0.21,
+            g: 0.52,
+            b: 0.89,
+            a: 1.,
+        },
+        Color {
+            r: 0.55,
+            g: 0.75,
+            b: 0.98,
+            a: 1.,
+        },
+        Color {
+            r: 0.94,
+            g: 0.55,
+            b: 0.16,
+            a: 1.,
+        },
+        Color {
+            r: 0.96,
+            g: 0.11,
+            b: 0.14,
+            a: 1.,
+        },
+        Color {
+            r: 0.62,
+            g: 0.36,
+            b: 0.82,
+            a: 1.,
+        },
+        Color {
+            r: 0.8,
+            g: 0.58,
+            b: 0.92,
+            a: 1.,
+        },
+        Color {
+            r: 0.98,
+            g: 0.82,
+            b: 0.18,
+            a: 1.,
+        },
+    ];
 }
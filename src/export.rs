@@ -0,0 +1,383 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{
+    types::{
+        process_columns::ProcessColumn,
+        resource_data::{ApplicationData, ProcessData},
+        time_series::TimeSeries,
+    },
+    utils::csv_field,
+};
+
+/// A value as it's exported. Numbers are in the base unit they're exported with, not formatted
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+    /// Not known, like a value that couldn't be read
+    None,
+}
+
+impl Value {
+    fn to_csv(&self) -> String {
+        match self {
+            Value::Number(number) => number.to_string(),
+            Value::Text(text) => csv_field(text),
+            Value::None => String::new(),
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(number: f64) -> Self {
+        Value::Number(number)
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Self {
+        Value::Text(text)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::None, Into::into)
+    }
+}
+
+/// One of the current values shown on a page
+#[derive(Debug, Clone, Serialize)]
+pub struct Detail {
+    pub name: String,
+    pub value: Value,
+    /// Empty for values without one
+    pub unit: &'static str,
+}
+
+impl Detail {
+    pub fn number(name: impl Into<String>, value: f64, unit: &'static str) -> Self {
+        Self {
+            name: name.into(),
+            value: Value::Number(value),
+            unit,
+        }
+    }
+
+    pub fn text(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: Value::Text(value.into()),
+            unit: "",
+        }
+    }
+
+    pub fn optional(name: impl Into<String>, value: impl Into<Value>, unit: &'static str) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            unit,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportSample {
+    /// Seconds since the unix epoch
+    pub timestamp: f64,
+    pub value: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportSeries {
+    pub name: String,
+    pub unit: &'static str,
+    pub samples: Vec<ExportSample>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportColumn {
+    pub name: &'static str,
+    pub unit: &'static str,
+}
+
+/// A table like the process list. The first column identifies the row
+#[derive(Debug, Serialize)]
+pub struct ExportTable {
+    pub columns: Vec<ExportColumn>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+/// What a page shows: its history, the current values and its table if it has one
+#[derive(Debug, Serialize)]
+pub struct Export {
+    /// Which page it's from, like "disk /dev/sda"
+    pub page: String,
+    /// Seconds since the unix epoch
+    pub exported_at: f64,
+    pub history: Vec<ExportSeries>,
+    pub details: Vec<Detail>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<ExportTable>,
+}
+
+impl Export {
+    pub fn new(page: impl Into<String>) -> Self {
+        Self {
+            page: page.into(),
+            exported_at: unix_seconds(SystemTime::now()),
+            history: Vec::new(),
+            details: Vec::new(),
+            table: None,
+        }
+    }
+
+    pub fn add_series(&mut self, name: impl Into<String>, series: &TimeSeries) {
+        self.history.push(ExportSeries {
+            name: name.into(),
+            unit: series.unit().name(),
+            samples: series
+                .samples()
+                .iter()
+                .map(|sample| ExportSample {
+                    timestamp: unix_seconds(sample.timestamp),
+                    value: sample.value,
+                })
+                .collect(),
+        });
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|error| error.to_string())
+    }
+
+    /// One value per row, so history, details and table cells all fit the same columns
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("timestamp,section,row,name,value,unit\n");
+
+        for series in &self.history {
+            for sample in &series.samples {
+                csv.push_str(&format!(
+                    "{},history,,{},{},{}\n",
+                    sample.timestamp,
+                    csv_field(&series.name),
+                    sample.value,
+                    series.unit
+                ));
+            }
+        }
+
+        for detail in &self.details {
+            csv.push_str(&format!(
+                "{},detail,,{},{},{}\n",
+                self.exported_at,
+                csv_field(&detail.name),
+                detail.value.to_csv(),
+                detail.unit
+            ));
+        }
+
+        if let Some(table) = &self.table {
+            for row in &table.rows {
+                let key = row.first().map(Value::to_csv).unwrap_or_default();
+
+                for (column, value) in table.columns.iter().zip(row) {
+                    csv.push_str(&format!(
+                        "{},table,{},{},{},{}\n",
+                        self.exported_at,
+                        key,
+                        csv_field(column.name),
+                        value.to_csv(),
+                        column.unit
+                    ));
+                }
+            }
+        }
+
+        csv
+    }
+
+    /// Asks where to save, as JSON or CSV depending on the extension picked. None if cancelled
+    pub async fn save(self) -> Option<Result<PathBuf, String>> {
+        let file_name = format!(
+            "c-tasks-{}.json",
+            self.page
+                .replace(|character: char| !character.is_alphanumeric(), "-")
+        );
+
+        let file = rfd::AsyncFileDialog::new()
+            .set_title("Export")
+            .set_file_name(file_name)
+            .add_filter("JSON", &["json"])
+            .add_filter("CSV", &["csv"])
+            .save_file()
+            .await?;
+
+        let path = file.path().to_path_buf();
+
+        let is_csv = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+
+        let contents = if is_csv {
+            Ok(self.to_csv())
+        } else {
+            self.to_json()
+        };
+
+        Some(
+            contents
+                .and_then(|contents| fs::write(&path, contents).map_err(|error| error.to_string()))
+                .map(|_| path),
+        )
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// The processes table with its shown columns, and the PID first to tell rows apart
+pub fn processes_table(columns: &[ProcessColumn], processes: &[ProcessData]) -> ExportTable {
+    let mut export_columns = vec![ExportColumn {
+        name: "PID",
+        unit: "",
+    }];
+
+    export_columns.extend(columns.iter().map(|column| ExportColumn {
+        name: column.title(),
+        unit: column.unit(),
+    }));
+
+    ExportTable {
+        columns: export_columns,
+        rows: processes
+            .iter()
+            .map(|process| {
+                let mut row = vec![Value::Number(process.pid.as_u32() as f64)];
+                row.extend(columns.iter().map(|column| column.value(process)));
+                row
+            })
+            .collect(),
+    }
+}
+
+pub fn applications_table(applications: &[ApplicationData]) -> ExportTable {
+    let columns = vec![
+        ExportColumn {
+            name: "ID",
+            unit: "",
+        },
+        ExportColumn {
+            name: "Name",
+            unit: "",
+        },
+        ExportColumn {
+            name: "Processes",
+            unit: "",
+        },
+        ExportColumn {
+            name: "CPU",
+            unit: "percent",
+        },
+        ExportColumn {
+            name: "Memory",
+            unit: "bytes",
+        },
+        ExportColumn {
+            name: "Disk Read",
+            unit: "bytes/s",
+        },
+        ExportColumn {
+            name: "Disk Written",
+            unit: "bytes/s",
+        },
+    ];
+
+    ExportTable {
+        columns,
+        rows: applications
+            .iter()
+            .map(|application| {
+                vec![
+                    Value::Text(application.id.clone()),
+                    Value::Text(application.name.clone()),
+                    Value::Number(application.processes.len() as f64),
+                    Value::Number(application.cpu_usage as f64),
+                    Value::Number(application.memory_usage as f64),
+                    Value::Number(application.disk_read_rate),
+                    Value::Number(application.disk_written_rate),
+                ]
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::types::time_series::{TimeSeries, Unit};
+
+    use super::{Detail, Export, ExportColumn, ExportTable, Value};
+
+    #[test]
+    fn csv_lines() {
+        let mut series = TimeSeries::new(Unit::Percent, Duration::from_secs(60));
+        series.push(UNIX_EPOCH + Duration::from_secs(10), 5.);
+        series.push(UNIX_EPOCH + Duration::from_millis(11_500), 7.25);
+
+        let mut export = Export::new("cpu");
+        export.exported_at = 100.;
+        export.add_series("cpu", &series);
+        export.details = vec![
+            Detail::optional("Swap", None::<f64>, "bytes"),
+            Detail::text("Brand", "Acme, Inc."),
+        ];
+        export.table = Some(ExportTable {
+            columns: vec![
+                ExportColumn {
+                    name: "Name",
+                    unit: "",
+                },
+                ExportColumn {
+                    name: "Memory",
+                    unit: "bytes",
+                },
+            ],
+            rows: vec![
+                vec![
+                    Value::Text(String::from("say \"hi\"")),
+                    Value::Number(2048.),
+                ],
+                vec![Value::Text(String::from("sh")), Value::None],
+            ],
+        });
+
+        let csv = export.to_csv();
+
+        assert_eq!(
+            csv.lines().collect::<Vec<&str>>(),
+            vec![
+                "timestamp,section,row,name,value,unit",
+                "10,history,,cpu,5,percent",
+                "11.5,history,,cpu,7.25,percent",
+                "100,detail,,Swap,,bytes",
+                "100,detail,,Brand,\"Acme, Inc.\",",
+                "100,table,\"say \"\"hi\"\"\",Name,\"say \"\"hi\"\"\",",
+                "100,table,\"say \"\"hi\"\"\",Memory,2048,bytes",
+                "100,table,sh,Name,sh,",
+                "100,table,sh,Memory,,bytes",
+            ]
+        );
+    }
+}
@@ -0,0 +1,517 @@
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, RwLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    collector::{Collector, CollectorRequest},
+    headless::{BatteryRecord, DiskRecord, HeadlessOptions, HeadlessRecord, ProcessRecord},
+    types::{cpu_times::CpuState, snapshot::Snapshot},
+};
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Every state a battery can report, as it's displayed
+const BATTERY_STATES: [&str; 5] = ["unknown", "charging", "discharging", "empty", "full"];
+
+/// How long a client gets to send its whole request, and to take the response, before it's dropped
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// More than any scraper sends, so a client can't make us buffer without end
+const MAX_REQUEST_BYTES: u64 = 16 * 1024;
+
+/// Samples in the background and serves the latest snapshot at /metrics in the OpenMetrics text format
+pub fn serve(options: &HeadlessOptions, address: SocketAddr) -> Result<(), String> {
+    let listener = TcpListener::bind(address)
+        .map_err(|error| format!("Couldn't listen on {}: {}", address, error))?;
+
+    let latest: Arc<RwLock<Option<Arc<Snapshot>>>> = Arc::new(RwLock::new(None));
+    let interval = options.interval;
+
+    {
+        let latest = latest.clone();
+
+        thread::spawn(move || {
+            let mut collector = Collector::new(CollectorRequest::new(interval));
+
+            loop {
+                // The first refresh has nothing to measure usage against
+                thread::sleep(interval);
+
+                let snapshot = Arc::new(collector.sample());
+                *latest.write().expect("snapshot lock poisoned") = Some(snapshot);
+            }
+        });
+    }
+
+    eprintln!("Serving metrics at http://{}/metrics", address);
+
+    let top = options.top.unwrap_or(0);
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+
+        let latest = latest.clone();
+
+        // So a slow client doesn't hold up the scrapes behind it
+        thread::spawn(move || {
+            let snapshot = latest.read().expect("snapshot lock poisoned").clone();
+
+            if let Err(error) = respond(stream, snapshot.as_deref(), top) {
+                eprintln!("Couldn't answer a scrape: {}", error);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Reads from a stream until a deadline. A read timeout alone only covers one read, so a client sending a byte at a
+/// time could keep a request going forever
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let remaining = self
+            .deadline
+            .checked_duration_since(Instant::now())
+            .filter(|remaining| !remaining.is_zero())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "request took too long"))?;
+
+        self.stream.set_read_timeout(Some(remaining))?;
+
+        let mut stream = self.stream;
+        stream.read(buffer)
+    }
+}
+
+fn respond(mut stream: TcpStream, snapshot: Option<&Snapshot>, top: usize) -> io::Result<()> {
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut reader = BufReader::new(
+        DeadlineReader {
+            stream: &stream,
+            deadline: Instant::now() + REQUEST_TIMEOUT,
+        }
+        .take(MAX_REQUEST_BYTES),
+    );
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // The headers don't change the response, but have to be read before it
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") => match snapshot {
+            Some(snapshot) => (
+                "200 OK",
+                CONTENT_TYPE,
+                render(&HeadlessRecord::new(snapshot, top)),
+            ),
+            None => (
+                "503 Service Unavailable",
+                "text/plain",
+                String::from("No snapshot has been collected yet\n"),
+            ),
+        },
+        ("GET", "/") => (
+            "200 OK",
+            "text/plain",
+            String::from("cTasks metrics are at /metrics\n"),
+        ),
+        ("GET", _) => ("404 Not Found", "text/plain", String::from("Not found\n")),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            String::from("Only GET is supported\n"),
+        ),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+
+    stream.flush()
+}
+
+/// A metric family's name and help, and how to get its value from a disk, battery or process
+type Gauge<T> = (&'static str, &'static str, fn(&T) -> f64);
+
+/// Writes the samples of one gauge metric family
+struct Family<'a> {
+    output: &'a mut String,
+    name: &'static str,
+}
+
+impl<'a> Family<'a> {
+    fn new(output: &'a mut String, name: &'static str, help: &str) -> Self {
+        let _ = writeln!(output, "# TYPE {} gauge", name);
+        let _ = writeln!(output, "# HELP {} {}", name, help);
+
+        Self { output, name }
+    }
+
+    fn sample(&mut self, labels: &[(&str, &str)], value: f64) {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(name, value)| format!("{}=\"{}\"", name, escape_label(value)))
+            .collect();
+
+        if labels.is_empty() {
+            let _ = writeln!(self.output, "{} {}", self.name, value);
+        } else {
+            let _ = writeln!(
+                self.output,
+                "{}{{{}}} {}",
+                self.name,
+                labels.join(","),
+                value
+            );
+        }
+    }
+}
+
+/// Uses the base units Prometheus expects, so ratios instead of percents and hertz instead of megahertz
+fn render(record: &HeadlessRecord) -> String {
+    let mut output = String::new();
+
+    let cpu = &record.cpu;
+
+    Family::new(&mut output, "ctasks_cpu_usage_ratio", "Usage of all cores")
+        .sample(&[], cpu.usage_percent as f64 / 100.);
+    Family::new(
+        &mut output,
+        "ctasks_cpu_frequency_hertz",
+        "Average frequency of all cores",
+    )
+    .sample(&[], cpu.frequency_mhz as f64 * 1_000_000.);
+
+    let mut time = Family::new(
+        &mut output,
+        "ctasks_cpu_time_ratio",
+        "Share of the time since the last refresh all cores spent in a state",
+    );
+    if let Some(times) = &cpu.times {
+        for state in CpuState::ALL {
+            time.sample(
+                &[("state", state.name())],
+                times.percent(state) as f64 / 100.,
+            );
+        }
+    }
+
+    let mut core_time = Family::new(
+        &mut output,
+        "ctasks_cpu_core_time_ratio",
+        "Share of the time since the last refresh a logical core spent in a state",
+    );
+    for (index, core) in cpu.cores.iter().enumerate() {
+        let Some(times) = &core.times else {
+            continue;
+        };
+
+        for state in CpuState::ALL {
+            core_time.sample(
+                &[("core", &index.to_string()), ("state", state.name())],
+                times.percent(state) as f64 / 100.,
+            );
+        }
+    }
+
+    let mut usage = Family::new(
+        &mut output,
+        "ctasks_cpu_core_usage_ratio",
+        "Usage of a logical core",
+    );
+    for (index, core) in cpu.cores.iter().enumerate() {
+        usage.sample(
+            &[("core", &index.to_string())],
+            core.usage_percent as f64 / 100.,
+        );
+    }
+
+    let mut frequency = Family::new(
+        &mut output,
+        "ctasks_cpu_core_frequency_hertz",
+        "Frequency of a logical core",
+    );
+    for (index, core) in cpu.cores.iter().enumerate() {
+        frequency.sample(
+            &[("core", &index.to_string())],
+            core.frequency_mhz as f64 * 1_000_000.,
+        );
+    }
+
+    let memory = &record.memory;
+
+    let mut used = Family::new(&mut output, "ctasks_memory_used_bytes", "Memory in use");
+    used.sample(&[("kind", "ram")], memory.ram_used_bytes as f64);
+    used.sample(&[("kind", "swap")], memory.swap_used_bytes as f64);
+
+    let mut total = Family::new(&mut output, "ctasks_memory_total_bytes", "Memory available");
+    total.sample(&[("kind", "ram")], memory.ram_total_bytes as f64);
+    total.sample(&[("kind", "swap")], memory.swap_total_bytes as f64);
+
+    let space_families: [Gauge<DiskRecord>; 2] = [
+        ("ctasks_disk_used_bytes", "Space used on a disk", |disk| {
+            disk.space_used_bytes as f64
+        }),
+        ("ctasks_disk_total_bytes", "Size of a disk", |disk| {
+            disk.space_total_bytes as f64
+        }),
+    ];
+
+    for (name, help, value) in space_families {
+        let mut family = Family::new(&mut output, name, help);
+
+        for disk in &record.disks {
+            family.sample(
+                &[("disk", &disk.name), ("mount_point", &disk.mount_point)],
+                value(disk),
+            );
+        }
+    }
+
+    // A device mounted more than once is listed for every mount, but its I/O is only counted once
+    let mut devices: Vec<&DiskRecord> = Vec::new();
+    for disk in &record.disks {
+        if !devices.iter().any(|device| device.name == disk.name) {
+            devices.push(disk);
+        }
+    }
+
+    let io_families: [Gauge<DiskRecord>; 2] = [
+        (
+            "ctasks_disk_read_bytes_per_second",
+            "Rate a disk is read at",
+            |disk| disk.read_bytes_per_second,
+        ),
+        (
+            "ctasks_disk_written_bytes_per_second",
+            "Rate a disk is written at",
+            |disk| disk.written_bytes_per_second,
+        ),
+    ];
+
+    for (name, help, value) in io_families {
+        let mut family = Family::new(&mut output, name, help);
+
+        for disk in &devices {
+            family.sample(&[("disk", &disk.name)], value(disk));
+        }
+    }
+
+    let battery_families: [Gauge<BatteryRecord>; 3] = [
+        (
+            "ctasks_battery_charge_ratio",
+            "State of charge of a battery",
+            |battery| battery.state_of_charge_percent as f64 / 100.,
+        ),
+        (
+            "ctasks_battery_health_ratio",
+            "Capacity of a battery compared to its design",
+            |battery| battery.state_of_health_percent as f64 / 100.,
+        ),
+        (
+            "ctasks_battery_energy_rate_watts",
+            "Rate a battery is charging or discharging at",
+            |battery| battery.energy_rate_watts as f64,
+        ),
+    ];
+
+    for (name, help, value) in battery_families {
+        let mut family = Family::new(&mut output, name, help);
+
+        for (index, battery) in record.batteries.iter().enumerate() {
+            family.sample(&[("battery", &index.to_string())], value(battery));
+        }
+    }
+
+    // One series per state rather than a state label on the other families, so they don't restart when it changes
+    let mut state = Family::new(
+        &mut output,
+        "ctasks_battery_state",
+        "Whether a battery is in a state, 1 for the one it's in",
+    );
+    for (index, battery) in record.batteries.iter().enumerate() {
+        for name in BATTERY_STATES {
+            state.sample(
+                &[("battery", &index.to_string()), ("state", name)],
+                if battery.state == name { 1. } else { 0. },
+            );
+        }
+    }
+
+    // Only with --top, since every process is its own series
+    if !record.processes.is_empty() {
+        let process_families: [Gauge<ProcessRecord>; 4] = [
+            (
+                "ctasks_process_cpu_usage_ratio",
+                "CPU usage of a process, which can be more than 1 across cores",
+                |process| process.cpu_usage_percent as f64 / 100.,
+            ),
+            (
+                "ctasks_process_memory_bytes",
+                "Memory used by a process",
+                |process| process.memory_bytes as f64,
+            ),
+            (
+                "ctasks_process_disk_read_bytes_per_second",
+                "Rate a process reads from disk",
+                |process| process.disk_read_bytes_per_second,
+            ),
+            (
+                "ctasks_process_disk_written_bytes_per_second",
+                "Rate a process writes to disk",
+                |process| process.disk_written_bytes_per_second,
+            ),
+        ];
+
+        for (name, help, value) in process_families {
+            let mut family = Family::new(&mut output, name, help);
+
+            for process in &record.processes {
+                family.sample(
+                    &[("pid", &process.pid.to_string()), ("name", &process.name)],
+                    value(process),
+                );
+            }
+        }
+    }
+
+    output.push_str("# EOF\n");
+    output
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::headless::{
+        BatteryRecord, CoreRecord, CpuRecord, DiskRecord, HeadlessRecord, MemoryRecord,
+        ProcessRecord,
+    };
+
+    use super::render;
+
+    fn record() -> HeadlessRecord {
+        let disk = |mount_point: &str, space_used_bytes| DiskRecord {
+            name: String::from("/dev/nvme0n1p3"),
+            mount_point: String::from(mount_point),
+            space_used_bytes,
+            space_total_bytes: 1000,
+            read_bytes_per_second: 2048.,
+            written_bytes_per_second: 0.,
+        };
+
+        HeadlessRecord {
+            timestamp: 1_700_000_000.,
+            cpu: CpuRecord {
+                usage_percent: 25.,
+                frequency_mhz: 3200,
+                times: None,
+                cores: vec![CoreRecord {
+                    usage_percent: 50.,
+                    frequency_mhz: 1800,
+                    times: None,
+                }],
+            },
+            memory: MemoryRecord {
+                ram_used_bytes: 512,
+                ram_total_bytes: 1024,
+                ram_usage_percent: 50.,
+                swap_used_bytes: 0,
+                swap_total_bytes: 0,
+                swap_usage_percent: 0.,
+            },
+            disks: vec![disk("/", 400), disk("/home", 400)],
+            batteries: vec![BatteryRecord {
+                state: String::from("charging"),
+                state_of_charge_percent: 80.,
+                state_of_health_percent: 90.,
+                energy_rate_watts: 12.5,
+            }],
+            processes: vec![ProcessRecord {
+                pid: 42,
+                name: String::from("say \"hi\"\\now\n"),
+                cpu_usage_percent: 150.,
+                memory_bytes: 4096,
+                disk_read_bytes_per_second: 0.,
+                disk_written_bytes_per_second: 0.,
+            }],
+        }
+    }
+
+    #[test]
+    fn openmetrics_text() {
+        let output = render(&record());
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert!(lines.contains(&"# TYPE ctasks_cpu_usage_ratio gauge"));
+        assert!(lines.contains(&"# HELP ctasks_cpu_usage_ratio Usage of all cores"));
+
+        // Percents become ratios and megahertz become hertz
+        assert!(lines.contains(&"ctasks_cpu_usage_ratio 0.25"));
+        assert!(lines.contains(&"ctasks_cpu_frequency_hertz 3200000000"));
+        assert!(lines.contains(&"ctasks_cpu_core_usage_ratio{core=\"0\"} 0.5"));
+        assert!(lines.contains(
+            &"ctasks_process_cpu_usage_ratio{pid=\"42\",name=\"say \\\"hi\\\"\\\\now\\n\"} 1.5"
+        ));
+
+        // Every mount has its own space, but the device's I/O is only counted once
+        assert!(lines.contains(
+            &"ctasks_disk_used_bytes{disk=\"/dev/nvme0n1p3\",mount_point=\"/home\"} 400"
+        ));
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|line| line.starts_with("ctasks_disk_read_bytes_per_second{"))
+                .count(),
+            1
+        );
+
+        assert!(lines.contains(&"ctasks_battery_charge_ratio{battery=\"0\"} 0.8"));
+        assert!(lines.contains(&"ctasks_battery_state{battery=\"0\",state=\"charging\"} 1"));
+        assert!(lines.contains(&"ctasks_battery_state{battery=\"0\",state=\"discharging\"} 0"));
+
+        // Every family has its metadata once
+        let types = lines
+            .iter()
+            .filter(|line| line.starts_with("# TYPE "))
+            .count();
+        let helps = lines
+            .iter()
+            .filter(|line| line.starts_with("# HELP "))
+            .count();
+        assert_eq!(types, helps);
+
+        assert_eq!(lines.last(), Some(&"# EOF"));
+        assert!(output.ends_with("# EOF\n"));
+    }
+}
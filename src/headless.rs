@@ -0,0 +1,669 @@
+use std::{
+    io::{self, Write},
+    net::SocketAddr,
+    thread,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{
+    collector::{Collector, CollectorRequest},
+    exporter,
+    types::{
+        cpu_times::{CpuState, CpuTimes},
+        resource_data::{CpuData, DiskData, MemoryData},
+        snapshot::Snapshot,
+    },
+    utils::csv_field,
+};
+
+pub const USAGE: &str = "Usage: c-tasks [--headless [options]]
+
+Without --headless the window opens as usual.
+
+Options:
+    --headless           Print snapshots to stdout instead of opening a window
+    --format json|csv    JSON lines, or CSV rows of timestamp,resource,name,metric,value,unit (default json)
+    --interval DURATION  Time between snapshots, like 500ms, 1s or 2m (default 1s)
+    --count N            Stop after N snapshots (default: never)
+    --top N              How many processes to include, by CPU usage (default 10, or none with --listen)
+    --listen ADDRESS     Serve OpenMetrics at http://ADDRESS/metrics instead of printing, like 127.0.0.1:9184";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone)]
+pub struct HeadlessOptions {
+    pub format: OutputFormat,
+    pub interval: Duration,
+    /// None to keep going until stopped
+    pub count: Option<u64>,
+    /// Processes to include, those using the most CPU first. None for the default
+    pub top: Option<usize>,
+    /// Where to serve metrics, instead of printing snapshots
+    pub listen: Option<SocketAddr>,
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        Self {
+            format: OutputFormat::Json,
+            interval: Duration::from_secs(1),
+            count: None,
+            top: None,
+            listen: None,
+        }
+    }
+}
+
+impl HeadlessOptions {
+    /// Parses the command line arguments, without the program name. None if the window should open instead
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut args = args.into_iter().peekable();
+
+        if args.peek().is_none() {
+            return Ok(None);
+        }
+
+        let mut headless = false;
+        let mut options = Self::default();
+
+        while let Some(arg) = args.next() {
+            // Both --interval 1s and --interval=1s
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{} needs a value", flag))
+            };
+
+            match flag.as_str() {
+                "--headless" => headless = true,
+                "--format" => {
+                    options.format = match value()?.as_str() {
+                        "json" => OutputFormat::Json,
+                        "csv" => OutputFormat::Csv,
+                        format => return Err(format!("Unknown format {}", format)),
+                    }
+                }
+                "--interval" => options.interval = parse_duration(&value()?)?,
+                "--count" => {
+                    let count = value()?;
+                    options.count = Some(
+                        count
+                            .parse()
+                            .map_err(|_| format!("Invalid count {}", count))?,
+                    );
+                }
+                "--top" => {
+                    let top = value()?;
+                    options.top = Some(
+                        top.parse()
+                            .map_err(|_| format!("Invalid process count {}", top))?,
+                    );
+                }
+                "--listen" => {
+                    let address = value()?;
+                    options.listen = Some(
+                        address
+                            .parse()
+                            .map_err(|_| format!("Invalid address {}", address))?,
+                    );
+                }
+                _ => return Err(format!("Unknown argument {}", flag)),
+            }
+        }
+
+        if !headless {
+            return Err(String::from("Options are only used with --headless"));
+        }
+
+        Ok(Some(options))
+    }
+}
+
+/// A number with an optional unit of ms, s, m or h. Seconds without one
+fn parse_duration(duration: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid duration {}", duration);
+
+    let split = duration
+        .find(|character: char| character.is_ascii_alphabetic())
+        .unwrap_or(duration.len());
+    let (number, unit) = duration.split_at(split);
+
+    let number: f64 = number.trim().parse().map_err(|_| invalid())?;
+
+    let seconds = match unit {
+        "ms" => number / 1000.,
+        "" | "s" => number,
+        "m" => number * 60.,
+        "h" => number * 60. * 60.,
+        _ => return Err(invalid()),
+    };
+
+    if !seconds.is_finite() || seconds <= 0. {
+        return Err(invalid());
+    }
+
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// Collects snapshots without a window and writes each to stdout, until the count is reached or stdout closes
+pub fn run(options: &HeadlessOptions) -> Result<(), String> {
+    if let Some(address) = options.listen {
+        return exporter::serve(options, address);
+    }
+
+    let top = options.top.unwrap_or(10);
+    let mut collector = Collector::new(CollectorRequest::new(options.interval));
+    let mut stdout = io::stdout().lock();
+    let mut written = 0;
+
+    if options.format == OutputFormat::Csv
+        && writeln!(stdout, "timestamp,resource,name,metric,value,unit").is_err()
+    {
+        return Ok(());
+    }
+
+    while options.count.is_none_or(|count| written < count) {
+        // The first refresh has nothing to measure usage against
+        thread::sleep(options.interval);
+
+        let record = HeadlessRecord::new(&collector.sample(), top);
+
+        let result = match options.format {
+            OutputFormat::Json => serde_json::to_writer(&mut stdout, &record)
+                .map_err(io::Error::from)
+                .and_then(|_| writeln!(stdout)),
+            OutputFormat::Csv => write_csv(&mut stdout, &record),
+        };
+
+        // Like when piped into head
+        if result.and_then(|_| stdout.flush()).is_err() {
+            return Ok(());
+        }
+
+        written += 1;
+    }
+
+    Ok(())
+}
+
+/// What's written for each snapshot. Units are in the field names
+#[derive(Debug, Serialize)]
+pub struct HeadlessRecord {
+    /// Seconds since the unix epoch
+    pub timestamp: f64,
+    pub cpu: CpuRecord,
+    pub memory: MemoryRecord,
+    pub disks: Vec<DiskRecord>,
+    pub batteries: Vec<BatteryRecord>,
+    pub processes: Vec<ProcessRecord>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CpuRecord {
+    pub usage_percent: f32,
+    pub frequency_mhz: u64,
+    /// None where /proc/stat can't be read
+    pub times: Option<CpuTimesRecord>,
+    pub cores: Vec<CoreRecord>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CoreRecord {
+    pub usage_percent: f32,
+    pub frequency_mhz: u64,
+    pub times: Option<CpuTimesRecord>,
+}
+
+/// Percent of the time since the previous snapshot spent in each state
+#[derive(Debug, Serialize)]
+pub struct CpuTimesRecord {
+    pub user_percent: f32,
+    pub nice_percent: f32,
+    pub system_percent: f32,
+    pub idle_percent: f32,
+    pub iowait_percent: f32,
+    pub irq_percent: f32,
+    pub softirq_percent: f32,
+    pub steal_percent: f32,
+}
+
+impl CpuTimesRecord {
+    /// None if no time was counted
+    fn new(times: &CpuTimes) -> Option<Self> {
+        if times.total() == 0 {
+            return None;
+        }
+
+        Some(Self {
+            user_percent: times.percent(CpuState::User),
+            nice_percent: times.percent(CpuState::Nice),
+            system_percent: times.percent(CpuState::System),
+            idle_percent: times.percent(CpuState::Idle),
+            iowait_percent: times.percent(CpuState::Iowait),
+            irq_percent: times.percent(CpuState::Irq),
+            softirq_percent: times.percent(CpuState::Softirq),
+            steal_percent: times.percent(CpuState::Steal),
+        })
+    }
+
+    pub fn percent(&self, state: CpuState) -> f32 {
+        match state {
+            CpuState::User => self.user_percent,
+            CpuState::Nice => self.nice_percent,
+            CpuState::System => self.system_percent,
+            CpuState::Idle => self.idle_percent,
+            CpuState::Iowait => self.iowait_percent,
+            CpuState::Irq => self.irq_percent,
+            CpuState::Softirq => self.softirq_percent,
+            CpuState::Steal => self.steal_percent,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemoryRecord {
+    pub ram_used_bytes: u64,
+    pub ram_total_bytes: u64,
+    pub ram_usage_percent: f32,
+    pub swap_used_bytes: u64,
+    pub swap_total_bytes: u64,
+    pub swap_usage_percent: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiskRecord {
+    pub name: String,
+    pub mount_point: String,
+    pub space_used_bytes: u64,
+    pub space_total_bytes: u64,
+    pub read_bytes_per_second: f64,
+    pub written_bytes_per_second: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatteryRecord {
+    pub state: String,
+    pub state_of_charge_percent: f32,
+    pub state_of_health_percent: f32,
+    pub energy_rate_watts: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProcessRecord {
+    pub pid: u32,
+    pub name: String,
+    pub cpu_usage_percent: f32,
+    pub memory_bytes: u64,
+    pub disk_read_bytes_per_second: f64,
+    pub disk_written_bytes_per_second: f64,
+}
+
+impl HeadlessRecord {
+    /// Goes through the same resource data as the pages, so the numbers match what the window shows
+    pub fn new(snapshot: &Snapshot, top: usize) -> Self {
+        let mut cpu = CpuData::new();
+        cpu.update(&snapshot.cpus, snapshot.cpus.len().max(1) as u32);
+
+        let mut memory = MemoryData::new();
+        memory.update(&snapshot.memory);
+
+        let mut processes: Vec<ProcessRecord> = snapshot
+            .processes
+            .iter()
+            .map(|(pid, process)| ProcessRecord {
+                pid: pid.as_u32(),
+                name: process.name.clone(),
+                cpu_usage_percent: process.cpu_usage,
+                memory_bytes: process.memory,
+                disk_read_bytes_per_second: snapshot.rate(process.disk_read),
+                disk_written_bytes_per_second: snapshot.rate(process.disk_written),
+            })
+            .collect();
+
+        processes.sort_by(|a, b| b.cpu_usage_percent.total_cmp(&a.cpu_usage_percent));
+        processes.truncate(top);
+
+        Self {
+            timestamp: snapshot
+                .timestamp
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64(),
+            cpu: CpuRecord {
+                usage_percent: cpu.cpu_usage_percent,
+                frequency_mhz: cpu.frequency,
+                times: CpuTimesRecord::new(&snapshot.cpu_times.total),
+                cores: cpu
+                    .logical_cores_usage_percents
+                    .iter()
+                    .zip(&cpu.logical_cores_frequencies)
+                    .enumerate()
+                    .map(|(index, (usage, frequency))| CoreRecord {
+                        usage_percent: *usage,
+                        frequency_mhz: *frequency,
+                        times: snapshot
+                            .cpu_times
+                            .logical_cores
+                            .get(index)
+                            .and_then(CpuTimesRecord::new),
+                    })
+                    .collect(),
+            },
+            memory: MemoryRecord {
+                ram_used_bytes: memory.ram_usage,
+                ram_total_bytes: memory.ram_total,
+                ram_usage_percent: memory.ram_usage_percent,
+                swap_used_bytes: memory.swap_usage,
+                swap_total_bytes: memory.swap_total,
+                // Without swap it's 0 of 0
+                swap_usage_percent: if memory.swap_total == 0 {
+                    0.
+                } else {
+                    memory.swap_usage_percent
+                },
+            },
+            disks: snapshot
+                .disks
+                .iter()
+                .map(|sample| {
+                    let mut disk = DiskData::new();
+                    disk.update(sample, snapshot.refresh_interval);
+
+                    DiskRecord {
+                        name: disk.name,
+                        mount_point: sample.mount_point.to_string_lossy().to_string(),
+                        space_used_bytes: disk.space_used,
+                        space_total_bytes: disk.space_total,
+                        read_bytes_per_second: disk.read,
+                        written_bytes_per_second: disk.written,
+                    }
+                })
+                .collect(),
+            batteries: snapshot
+                .batteries
+                .iter()
+                .map(|battery| BatteryRecord {
+                    state: battery.state.to_string(),
+                    state_of_charge_percent: battery.state_of_charge.value * 100.,
+                    state_of_health_percent: battery.state_of_health.value * 100.,
+                    energy_rate_watts: battery.energy_rate.value,
+                })
+                .collect(),
+            processes,
+        }
+    }
+}
+
+/// One row per value, so rows look the same however many cores, disks and processes there are
+fn write_csv(writer: &mut impl Write, record: &HeadlessRecord) -> io::Result<()> {
+    let mut row = |resource: &str, name: &str, metric: &str, value: f64, unit: &str| {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{}",
+            record.timestamp,
+            resource,
+            csv_field(name),
+            metric,
+            value,
+            unit
+        )
+    };
+
+    let cpu = &record.cpu;
+    row("cpu", "", "usage", cpu.usage_percent as f64, "percent")?;
+    row("cpu", "", "frequency", cpu.frequency_mhz as f64, "MHz")?;
+
+    if let Some(times) = &cpu.times {
+        for state in CpuState::ALL {
+            let metric = format!("time_{}", state.name());
+            row("cpu", "", &metric, times.percent(state) as f64, "percent")?;
+        }
+    }
+
+    for (index, core) in cpu.cores.iter().enumerate() {
+        let name = index.to_string();
+        row("core", &name, "usage", core.usage_percent as f64, "percent")?;
+        row("core", &name, "frequency", core.frequency_mhz as f64, "MHz")?;
+
+        if let Some(times) = &core.times {
+            for state in CpuState::ALL {
+                let metric = format!("time_{}", state.name());
+                row(
+                    "core",
+                    &name,
+                    &metric,
+                    times.percent(state) as f64,
+                    "percent",
+                )?;
+            }
+        }
+    }
+
+    let memory = &record.memory;
+    row(
+        "memory",
+        "ram",
+        "used",
+        memory.ram_used_bytes as f64,
+        "bytes",
+    )?;
+    row(
+        "memory",
+        "ram",
+        "total",
+        memory.ram_total_bytes as f64,
+        "bytes",
+    )?;
+    row(
+        "memory",
+        "ram",
+        "usage",
+        memory.ram_usage_percent as f64,
+        "percent",
+    )?;
+    row(
+        "memory",
+        "swap",
+        "used",
+        memory.swap_used_bytes as f64,
+        "bytes",
+    )?;
+    row(
+        "memory",
+        "swap",
+        "total",
+        memory.swap_total_bytes as f64,
+        "bytes",
+    )?;
+    row(
+        "memory",
+        "swap",
+        "usage",
+        memory.swap_usage_percent as f64,
+        "percent",
+    )?;
+
+    for disk in &record.disks {
+        row(
+            "disk",
+            &disk.name,
+            "used",
+            disk.space_used_bytes as f64,
+            "bytes",
+        )?;
+        row(
+            "disk",
+            &disk.name,
+            "total",
+            disk.space_total_bytes as f64,
+            "bytes",
+        )?;
+        row(
+            "disk",
+            &disk.name,
+            "read",
+            disk.read_bytes_per_second,
+            "bytes/s",
+        )?;
+        row(
+            "disk",
+            &disk.name,
+            "written",
+            disk.written_bytes_per_second,
+            "bytes/s",
+        )?;
+    }
+
+    for (index, battery) in record.batteries.iter().enumerate() {
+        let name = index.to_string();
+        row(
+            "battery",
+            &name,
+            "charge",
+            battery.state_of_charge_percent as f64,
+            "percent",
+        )?;
+        row(
+            "battery",
+            &name,
+            "health",
+            battery.state_of_health_percent as f64,
+            "percent",
+        )?;
+        row(
+            "battery",
+            &name,
+            "energy_rate",
+            battery.energy_rate_watts as f64,
+            "W",
+        )?;
+    }
+
+    for process in &record.processes {
+        let name = format!("{} {}", process.pid, process.name);
+        row(
+            "process",
+            &name,
+            "cpu",
+            process.cpu_usage_percent as f64,
+            "percent",
+        )?;
+        row(
+            "process",
+            &name,
+            "memory",
+            process.memory_bytes as f64,
+            "bytes",
+        )?;
+        row(
+            "process",
+            &name,
+            "disk_read",
+            process.disk_read_bytes_per_second,
+            "bytes/s",
+        )?;
+        row(
+            "process",
+            &name,
+            "disk_written",
+            process.disk_written_bytes_per_second,
+            "bytes/s",
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{parse_duration, HeadlessOptions, OutputFormat};
+
+    fn parse(args: &[&str]) -> Result<Option<HeadlessOptions>, String> {
+        HeadlessOptions::from_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+
+        for invalid in ["", "ms", "0s", "-1s", "5d", "inf", "1 s s"] {
+            assert!(parse_duration(invalid).is_err(), "{} was accepted", invalid);
+        }
+    }
+
+    #[test]
+    fn without_arguments_the_window_opens() {
+        assert!(matches!(parse(&[]), Ok(None)));
+    }
+
+    #[test]
+    fn options_with_separate_and_inline_values() {
+        let options = parse(&[
+            "--headless",
+            "--format=csv",
+            "--interval",
+            "250ms",
+            "--count=3",
+            "--top",
+            "5",
+        ])
+        .expect("arguments not parsed")
+        .expect("headless not chosen");
+
+        assert_eq!(options.format, OutputFormat::Csv);
+        assert_eq!(options.interval, Duration::from_millis(250));
+        assert_eq!(options.count, Some(3));
+        assert_eq!(options.top, Some(5));
+        assert_eq!(options.listen, None);
+
+        let options = parse(&["--listen=127.0.0.1:9184", "--headless"])
+            .expect("arguments not parsed")
+            .expect("headless not chosen");
+
+        assert_eq!(
+            options.listen.map(|address| address.to_string()),
+            Some(String::from("127.0.0.1:9184"))
+        );
+        assert_eq!(options.format, OutputFormat::Json);
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        assert_eq!(
+            parse(&["--headless", "--count"]).err(),
+            Some(String::from("--count needs a value"))
+        );
+        assert_eq!(
+            parse(&["--interval", "1s"]).err(),
+            Some(String::from("Options are only used with --headless"))
+        );
+        assert_eq!(
+            parse(&["--headless", "--format", "xml"]).err(),
+            Some(String::from("Unknown format xml"))
+        );
+        assert_eq!(
+            parse(&["--headless", "--verbose"]).err(),
+            Some(String::from("Unknown argument --verbose"))
+        );
+        assert!(parse(&["--headless", "--listen", "localhost"]).is_err());
+        assert!(parse(&["--headless", "--top=-1"]).is_err());
+    }
+}
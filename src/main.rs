@@ -1,7 +1,9 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::HashMap,
     env,
     ffi::OsString,
+    path::PathBuf,
+    sync::{mpsc, Arc},
     time::Duration,
 };
 
@@ -9,7 +11,8 @@ use battery::{
     units::{ElectricPotential, Energy, Power, ThermodynamicTemperature},
     Batteries, Battery,
 };
-use constants::{padding, DisplayState, ICON, PERCENT_PRECISION};
+use constants::{custom_theme, padding, DisplayState, ICON, PERCENT_PRECISION};
+use general_widgets::icons::bootstrap_icon;
 use iced::{
     advanced::{
         graphics::{
@@ -39,47 +42,78 @@ use iced_aw::{
     native::Split,
     split, BootstrapIcon, FloatingElement, NerdIcon, Spinner, NERD_FONT,
 };
+use export::{Detail, Export};
+use metrics_store::MetricsStore;
 use preferences::Preferences;
+use recording::Recording;
+use replay::{Replay, ReplayMessage};
+use resources::ResourceRegistry;
 use resource_pages::{
     applications_page::{self, ApplicationsPage, ApplicationsPageMessage},
-    battery_page::{BatteryPage, BatteryPageMessage},
-    cpu_page::{CpuPage, CpuPageMessage},
+    battery_page::BatteryPageMessage,
+    cpu_page::CpuPageMessage,
     disk_page::{DiskPage, DiskPageMessage},
-    memory_page::{MemoryPage, MemoryPageMessage},
+    memory_page::MemoryPageMessage,
+    process_details_page::{ProcessDetailsPage, ProcessDetailsPageMessage},
     processes_page::{ProcessesPage, ProcessesPageMessage},
     resource_details::{ResourceDetails, ResourceDetailsMessage},
 };
 
 use resource_previews::{
     applications_preview::ApplicationsPreview,
-    battery_preview::BatteryPreview,
-    cpu_preview::{self, CpuPreview},
-    disk_preview::DiskPreview,
-    memory_preview::MemoryPreview,
     processes_preview::ProcessesPreview,
     resource_preview::ResourcePreviewMessage,
 };
 use sidebar::sidebar_item::{SidebarItemParent, SidebarItemParentMessage};
 use styles::container::{main_content, sidebar};
-use sysinfo::{
-    Cpu, CpuRefreshKind, Disk, DiskKind, Disks, MemoryRefreshKind, Networks, Pid,
-    ProcessRefreshKind, RefreshKind, System, UpdateKind,
+use sysinfo::Pid;
+use system_source::{SysinfoSource, SystemSource};
+use types::{
+    desktop_entry::DesktopEntries,
+    resource_data::{DiskData, ResourceData},
+    snapshot::Snapshot,
+    time_series::{TimeSeries, Unit},
 };
-use types::resource_data::{DiskData, ResourceData};
 
-use crate::constants::HISTORY_TICKS;
+use crate::collector::{CollectorEvent, CollectorRequest};
 
+mod collector;
 mod constants;
+mod export;
+mod exporter;
 mod general_widgets;
+mod headless;
+mod metrics_store;
 mod preferences;
+mod process_control;
+mod recording;
+mod replay;
 mod resource_pages;
 mod resource_previews;
+mod resources;
 mod sidebar;
 mod styles;
+mod system_source;
 mod types;
 mod utils;
 
 pub fn main() -> iced::Result {
+    match headless::HeadlessOptions::from_args(env::args().skip(1)) {
+        Ok(Some(options)) => {
+            if let Err(error) = headless::run(&options) {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+
+            return Ok(());
+        }
+        Ok(None) => {}
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, headless::USAGE);
+            std::process::exit(2);
+        }
+    }
+
     // let image = Image::load_from_memory(ICON).unwrap();
     // let icon = iced::window::icon::from_rgba(ICON.as_bytes().to_vec(), ICON_HEIGHT, ICON_WIDTH).unwrap();
     //let icon = iced::window::icon::from_rgba(rgba, width, height)
@@ -111,58 +145,58 @@ impl CustomThemeChoice {
     }
 }
 
+/// History that isn't kept by a registered resource
 #[derive(Debug, Default)]
 pub struct ResourceHistory {
-    // use the difference between the current tick and the last tick
-    pub last_tick: i32,
-    pub cpu: VecDeque<(i32, i32)>,
-    pub logical_cores: Vec<VecDeque<(i32, i32)>>,
-    pub ram: VecDeque<(i32, i32)>,
-    pub swap: VecDeque<(i32, i32)>,
-    pub disk_write: HashMap<String, VecDeque<(i32, i32)>>,
-    pub disk_read: HashMap<String, VecDeque<(i32, i32)>>,
-    pub gpu: VecDeque<(i32, i32)>,
-    pub vram: VecDeque<(i32, i32)>,
-    pub wifi: VecDeque<(i32, i32)>,
-    pub ethernet: VecDeque<(i32, i32)>,
-    pub battery_charge: VecDeque<(i32, i32)>,
+    pub processes: HashMap<Pid, ProcessHistory>,
 }
 
 impl ResourceHistory {
-    fn new(logical_cores_count: u32) -> Self {
-        let mut logical_cores = vec![];
+    /// The history of a process, starting it if there isn't any yet
+    pub fn process(&mut self, pid: Pid, start_time: u64, retention: Duration) -> &ProcessHistory {
+        self.processes
+            .entry(pid)
+            .or_insert_with(|| ProcessHistory::new(start_time, retention))
+    }
+}
 
-        // initialize history queue for each logical core
-        for _ in 0..logical_cores_count {
-            logical_cores.push(VecDeque::new());
-        }
+#[derive(Debug)]
+pub struct ProcessHistory {
+    /// Of the process the history is for, to tell it apart from a later one given the same PID
+    pub start_time: u64,
+    pub cpu: TimeSeries,
+    pub memory: TimeSeries,
+    pub disk_read: TimeSeries,
+    pub disk_written: TimeSeries,
+}
 
+impl ProcessHistory {
+    pub fn new(start_time: u64, retention: Duration) -> Self {
         Self {
-            logical_cores,
-            ..Default::default()
+            start_time,
+            cpu: TimeSeries::new(Unit::Percent, retention),
+            memory: TimeSeries::new(Unit::Bytes, retention),
+            disk_read: TimeSeries::new(Unit::BytesPerSecond, retention),
+            disk_written: TimeSeries::new(Unit::BytesPerSecond, retention),
         }
     }
 }
 
+/// Previews of pages that aren't registered resources
 #[derive(Debug, Default)]
 pub struct ResourcePreviews {
     pub applications: ApplicationsPreview,
     pub processes: ProcessesPreview,
-    pub cpu: CpuPreview,
-    pub memory: MemoryPreview,
-    pub disks: HashMap<String, DiskPreview>,
-    pub battery: BatteryPreview,
 }
 
 #[derive(Debug)]
 
 pub enum ResourcePage {
-    Cpu(CpuPage),
-    Disk(DiskPage),
-    Memory(MemoryPage),
+    /// The page of a registered resource, which keeps the page's state itself
+    Resource(ActivePreview),
     Applications(ApplicationsPage),
     Processes(ProcessesPage),
-    Battery(BatteryPage),
+    ProcessDetails(Box<ProcessDetailsPage>),
 }
 
 #[derive(Debug)]
@@ -187,6 +221,7 @@ pub enum ResourcePageMessage {
     MemoryPageMessage(MemoryPageMessage),
     ApplicationsPageMessage(ApplicationsPageMessage),
     ProcessesPageMessage(ProcessesPageMessage),
+    ProcessDetailsPageMessage(ProcessDetailsPageMessage),
     BatteryPageMessage(BatteryPageMessage),
 }
 
@@ -198,7 +233,19 @@ enum AppMessage {
     ResourcePageMessage(ResourcePageMessage),
     SetResourceDetails(ResourceType),
     ResourcePreviewMessage(ResourcePreviewMessage),
-    Tick,
+    Collector(CollectorEvent),
+    /// Starts recording snapshots, asking where to first, or stops
+    ToggleRecording,
+    RecordingPathPicked(Option<PathBuf>),
+    OpenRecording,
+    /// None if no recording was picked
+    RecordingOpened(Option<Result<Arc<Recording>, String>>),
+    Replay(ReplayMessage),
+    /// Saves the current page's history and values
+    Export,
+    /// None if no file was picked
+    Exported(Option<Result<PathBuf, String>>),
+    DesktopEntriesLoaded(Arc<DesktopEntries>),
 }
 
 #[derive(Debug, Default)]
@@ -208,7 +255,7 @@ enum AppState {
     Loaded,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ActivePreview {
     pub resource: ResourceType,
     // A identifying name if there are multiple devices of the resource type
@@ -220,85 +267,86 @@ struct App {
     sidebar_items: Vec<SidebarItemParent>,
     preferences: Preferences,
     resource_page: ResourcePage,
-    system_info: System,
-    physical_core_count: u32,
     logical_core_count: u32,
-    cpu_brand: String,
-    disk_info: Disks,
-    network_info: Networks,
     state: AppState,
     tick: i32,
+    /// The latest snapshot from the collector
+    snapshot: Arc<Snapshot>,
+    /// Sends requests to the collector, once it has started
+    collector: Option<mpsc::Sender<CollectorRequest>>,
+    /// The last request sent to the collector
+    collector_request: CollectorRequest,
     resource_history: ResourceHistory,
     resource_data: ResourceData,
+    /// The CPU, memory, disks and other resources with their own preview and page
+    resources: ResourceRegistry,
     previews: ResourcePreviews,
     active_preview: ActivePreview,
-    battery_manager: battery::Manager,
+    /// Where collected snapshots are being recorded to
+    recording: Option<PathBuf>,
+    /// Why recording, or opening a recording, last failed
+    recording_error: Option<String>,
+    /// Shown instead of live data while a recording is open
+    replay: Option<Replay>,
+    /// Where the last export was saved, or why it couldn't be
+    export_status: Option<Result<PathBuf, String>>,
 }
 
 async fn load() -> Result<(), String> {
     Ok(())
 }
 
-impl Application for App {
-    type Message = AppMessage;
-    type Theme = Theme;
-    type Executor = executor::Default;
-    type Flags = ();
+impl App {
+    /// Shows a snapshot, either just collected or from a recording
+    fn apply_snapshot(&mut self, snapshot: Arc<Snapshot>) {
+        self.snapshot = snapshot;
 
-    fn new(_flags: ()) -> (Self, Command<AppMessage>) {
-        let system_info = System::new_all();
-        let physical_core_count = system_info.physical_core_count().unwrap_or(1) as u32/* system_info.cpus().len() as u32 */;
-        let logical_core_count = system_info.cpus().len() as u32;
+        self.tick += 1;
 
-        let cpu_brand = system_info.global_cpu_info().brand().to_string();
+        // clean old custom resource data that won't necessarily be replaced
 
-        let mut logical_cores_usage_percent = Vec::new();
-        let mut logical_cores_frequencies = Vec::new();
+        self.resource_data.processes.clean_in_depth();
+        self.resource_data.applications.clean_in_depth();
 
-        for _ in 0..logical_core_count {
-            logical_cores_usage_percent.push(0.);
-            logical_cores_frequencies.push(0);
-        }
+        self.resources.collect(&self.snapshot);
 
-        let preferences = Preferences::new();
+        // process history
 
-        let new_self = Self {
-            state: AppState::Loading,
-            preferences,
-            system_info,
-            physical_core_count,
-            logical_core_count,
-            cpu_brand,
-            tick: 0,
-            resource_history: ResourceHistory::new(logical_core_count),
-            sidebar_items: Vec::new(),
-            resource_page: ResourcePage::Cpu(CpuPage::new(&preferences, logical_core_count)),
-            active_preview: ActivePreview {
-                resource: ResourceType::Cpu,
-                name: None,
-            },
-            resource_data: ResourceData::new(),
-            previews: ResourcePreviews::default(),
-            disk_info: Disks::new(),
-            network_info: Networks::new(),
-            battery_manager: battery::Manager::new().unwrap(),
-        };
+        let timestamp = self.snapshot.timestamp;
 
-        let command = Command::batch(vec![
-            // font::load(iced_aw::NERD_FONT_BYTES).map(Message::FontLoaded),
-            // Command::perform(load(), Message::Loaded),
-            font::load(iced_aw::BOOTSTRAP_FONT_BYTES).map(AppMessage::FontLoaded),
-            Command::perform(load(), AppMessage::Loaded),
-        ]);
+        for (pid, process) in &self.snapshot.processes {
+            let history = self.resource_history.processes.entry(*pid).or_insert_with(|| {
+                ProcessHistory::new(process.start_time, self.preferences.history_retention)
+            });
 
-        (new_self, command)
-    }
+            // A reused PID belongs to a different process, whose history starts over
+            if history.start_time != process.start_time {
+                *history =
+                    ProcessHistory::new(process.start_time, self.preferences.history_retention);
+            }
 
-    fn title(&self) -> String {
-        String::from("C Tasks")
+            history.cpu.push(timestamp, process.cpu_usage as f64);
+            history.memory.push(timestamp, process.memory as f64);
+            history
+                .disk_read
+                .push(timestamp, self.snapshot.rate(process.disk_read));
+            history
+                .disk_written
+                .push(timestamp, self.snapshot.rate(process.disk_written));
+        }
+
+        // Forget the history of processes that have exited
+        self.resource_history
+            .processes
+            .retain(|pid, _| self.snapshot.processes.contains_key(pid));
+
+        // resource page
+
+        try_update_in_depth(self);
     }
 
-    fn update(&mut self, message: AppMessage) -> Command<AppMessage> {
+    /// Handles every message but the collector starting
+    fn update_state(&mut self, message: AppMessage) -> Command<AppMessage> {
         match self.state {
             AppState::Loading => match message {
                 AppMessage::Loaded(Ok(state)) => {
@@ -325,206 +373,57 @@ impl Application for App {
                 // ];
 
                 match message {
-                    AppMessage::Tick => {
-                        self.resource_history.last_tick = self.tick;
-                        self.tick += 1;
-
-                        // clean old custom resource data that won't necessarily be replaced
-
-                        self.resource_data.processes.clean_in_depth();
-                        self.resource_data.applications.clean_in_depth();
-
-                        for disk_data in &mut self.resource_data.disks.values_mut() {
-                            disk_data.clean_in_depth();
-                        }
-
-                        // Change this to call specific to be more optimal
-
-                        self.system_info.refresh_cpu_specifics(
-                            CpuRefreshKind::new().with_cpu_usage().with_frequency(),
-                        );
-                        self.system_info.refresh_processes_specifics(
-                            ProcessRefreshKind::new().with_user(UpdateKind::Always),
+                    // One can still arrive after a recording was opened
+                    AppMessage::Collector(CollectorEvent::Snapshot(snapshot))
+                        if self.replay.is_none() =>
+                    {
+                        self.apply_snapshot(snapshot);
+                    }
+                    AppMessage::Collector(CollectorEvent::RecordingFailed(error)) => {
+                        self.recording = None;
+                        self.recording_error = Some(error);
+                    }
+                    AppMessage::ToggleRecording if self.recording.is_some() => {
+                        self.recording = None;
+                    }
+                    AppMessage::ToggleRecording => {
+                        return Command::perform(
+                            recording::pick_recording_path(),
+                            AppMessage::RecordingPathPicked,
                         );
-                        self.system_info
-                            .refresh_memory_specifics(MemoryRefreshKind::new().with_ram());
-                        self.disk_info = Disks::new_with_refreshed_list();
-                        self.network_info = Networks::new_with_refreshed_list();
-
-                        // Update and construct disk data
-
-                        for disk in &self.disk_info {
-                            let disk_name = disk.name().to_str().unwrap_or("default").to_string();
-
-                            if let Some(disk_data) = self.resource_data.disks.get_mut(&disk_name) {
-                                disk_data.update(&disk_name, disk);
-                                continue;
-                            };
-
-                            let mut new_disk_data = DiskData::new();
-
-                            new_disk_data.update(&disk_name, disk);
-
-                            self.resource_data.disks.insert(disk_name, new_disk_data);
-                        }
-
-                        // Update and construct disk previews and details
-
-                        for (_, disk_data) in &self.resource_data.disks {
-                            if self.previews.disks.get_mut(&disk_data.name).is_none() {
-                                let new_preview = DiskPreview::new();
-
-                                self.previews
-                                    .disks
-                                    .insert(disk_data.name.clone(), new_preview);
-                            }
-                        }
-
-                        // cpu
-                        self.resource_data
-                            .cpu
-                            .update(self.system_info.cpus(), self.logical_core_count);
-
-                        // ram
-                        self.resource_data.memory.update(&self.system_info);
-
-                        // battery
-                        // TODO: should probably store batteries similar to disk_info
-
-                        for battery in self.battery_manager.batteries().unwrap() {
-                            let Ok(battery) = battery else {
-                                continue;
-                            };
-
-                            self.resource_data.battery.update(&battery);
-                        }
-
-                        // cpu history
-
-                        let tick_delta = self.tick - self.resource_history.last_tick;
-
-                        for history_tick in &mut self.resource_history.cpu {
-                            history_tick.0 -= tick_delta;
-                        }
-
-                        self.resource_history
-                            .cpu
-                            .retain(|history_tick| history_tick.0 >= 0);
-
-                        self.resource_history.cpu.push_back((
-                            HISTORY_TICKS as i32,
-                            self.resource_data.cpu.cpu_usage_percent as i32,
-                        ));
-
-                        // logical cores
-
-                        // logical cores history
-
-                        for (index, history) in
-                            self.resource_history.logical_cores.iter_mut().enumerate()
-                        {
-                            for history_tick in history.iter_mut() {
-                                history_tick.0 -= tick_delta;
-                            }
-
-                            history.retain(|history_tick| history_tick.0 >= 0);
-
-                            history.push_back((
-                                HISTORY_TICKS as i32,
-                                self.resource_data.cpu.logical_cores_usage_percents[index] as i32,
-                            ));
-                        }
-
-                        // ram history
-
-                        for history_tick in &mut self.resource_history.ram {
-                            history_tick.0 -= tick_delta;
-                        }
-
-                        self.resource_history
-                            .ram
-                            .retain(|history_tick| history_tick.0 >= 0);
-
-                        self.resource_history.ram.push_back((
-                            HISTORY_TICKS as i32,
-                            self.resource_data.memory.ram_usage_percent as i32,
-                        ));
-
-                        // swap history
-
-                        for history_tick in &mut self.resource_history.swap {
-                            history_tick.0 -= tick_delta;
-                        }
-
-                        self.resource_history
-                            .swap
-                            .retain(|history_tick| history_tick.0 >= 0);
-
-                        self.resource_history.swap.push_back((
-                            HISTORY_TICKS as i32,
-                            self.resource_data.memory.swap_usage_percent as i32,
-                        ));
-
-                        // disk history
-
-                        for (disk_name, disk_data) in &self.resource_data.disks {
-                            // written
-
-                            let written_history = self
-                                .resource_history
-                                .disk_write
-                                .entry(disk_name.clone())
-                                .or_insert(VecDeque::new());
-
-                            for history_tick in &mut *written_history {
-                                history_tick.0 -= tick_delta;
-                            }
-
-                            written_history.retain(|history_tick| history_tick.0 >= 0);
-
-                            written_history
-                                .push_back((HISTORY_TICKS as i32, disk_data.written as i32));
-
-                            // read
-
-                            let read_history = self
-                                .resource_history
-                                .disk_read
-                                .entry(disk_name.clone())
-                                .or_insert(VecDeque::new());
-
-                            for history_tick in &mut *read_history {
-                                history_tick.0 -= tick_delta;
-                            }
-
-                            read_history.retain(|history_tick| history_tick.0 >= 0);
-
-                            read_history.push_back((HISTORY_TICKS as i32, disk_data.read as i32));
-                        }
-
-                        // battery history
-
-                        for history_tick in &mut self.resource_history.battery_charge {
-                            history_tick.0 -= tick_delta;
+                    }
+                    AppMessage::RecordingPathPicked(path) => {
+                        if path.is_some() {
+                            self.recording_error = None;
                         }
 
-                        self.resource_history
-                            .battery_charge
-                            .retain(|history_tick| history_tick.0 >= 0);
-
-                        self.resource_history.battery_charge.push_back((
-                            HISTORY_TICKS as i32,
-                            (self.resource_data.battery.state_of_charge.value * 100.) as i32,
-                        ));
-
-                        // resource page
-
+                        self.recording = path;
+                    }
+                    AppMessage::OpenRecording => {
+                        return Command::perform(
+                            recording::pick_recording(),
+                            AppMessage::RecordingOpened,
+                        );
+                    }
+                    AppMessage::RecordingOpened(Some(Ok(recording))) => {
+                        self.recording_error = None;
+                        start_replay(self, recording);
+                    }
+                    AppMessage::RecordingOpened(Some(Err(error))) => {
+                        self.recording_error = Some(error);
+                    }
+                    AppMessage::Replay(message) => {
+                        update_replay(self, message);
+                    }
+                    AppMessage::DesktopEntriesLoaded(desktop_entries) => {
+                        self.resource_data.applications.desktop_entries = Some(desktop_entries);
                         try_update_in_depth(self);
-                        update_resource_page(self);
-
-                        //
-
-                        println!("tick: {}", self.tick);
+                    }
+                    AppMessage::Export => {
+                        return Command::perform(page_export(self).save(), AppMessage::Exported);
+                    }
+                    AppMessage::Exported(Some(status)) => {
+                        self.export_status = Some(status);
                     }
                     AppMessage::ResourcePageMessage(resource_page_message) => {
                         // maybe this is good reason to split each page into its own message, since they each may have unique properties
@@ -533,33 +432,82 @@ impl Application for App {
                         // match page, then match page message
 
                         match resource_page_message {
-                            ResourcePageMessage::DiskPageMessage(disk_page_message) => {}
-                            ResourcePageMessage::CpuPageMessage(cpu_page_message) => {
-                                match &mut self.resource_page {
-                                    ResourcePage::Cpu(cpu_page) => {
-                                        cpu_page
-                                            .update(cpu_page_message, &mut self.resource_data.cpu);
-                                    }
-                                    _ => {}
-                                }
-                            }
-                            ResourcePageMessage::MemoryPageMessage(memory_page_message) => {}
                             ResourcePageMessage::ApplicationsPageMessage(
                                 applications_page_message,
-                            ) => {}
+                            ) => {
+                                if let ResourcePage::Applications(applications_page) =
+                                    &mut self.resource_page
+                                {
+                                    return applications_page
+                                        .update(
+                                            applications_page_message,
+                                            &self.resource_data.applications,
+                                        )
+                                        .map(|message| {
+                                            AppMessage::ResourcePageMessage(
+                                                ResourcePageMessage::ApplicationsPageMessage(
+                                                    message,
+                                                ),
+                                            )
+                                        });
+                                }
+                            }
+                            ResourcePageMessage::ProcessesPageMessage(
+                                ProcessesPageMessage::OpenDetails(pid),
+                            ) => {
+                                let start_time = self
+                                    .snapshot
+                                    .processes
+                                    .get(&pid)
+                                    .map(|process| process.start_time)
+                                    .unwrap_or_default();
+                                let history = self.resource_history.process(
+                                    pid,
+                                    start_time,
+                                    self.preferences.history_retention,
+                                );
+
+                                self.resource_page = ResourcePage::ProcessDetails(Box::new(
+                                    ProcessDetailsPage::new(&self.preferences, pid, history),
+                                ));
+
+                                try_update_in_depth(self);
+                            }
+                            ResourcePageMessage::ProcessDetailsPageMessage(
+                                ProcessDetailsPageMessage::Back,
+                            ) => {
+                                self.resource_page = ResourcePage::Processes(ProcessesPage::new(
+                                    &self.preferences,
+                                    self.logical_core_count,
+                                ));
+                                self.resource_data.process_details = None;
+
+                                try_update_in_depth(self);
+                            }
                             ResourcePageMessage::ProcessesPageMessage(processes_page_message) => {
                                 match &mut self.resource_page {
                                     ResourcePage::Processes(processes_page) => {
                                         processes_page.update(
                                             processes_page_message,
                                             &mut self.resource_data.processes,
-                                            &self.system_info,
+                                            &self.snapshot,
                                         );
                                     }
                                     _ => {}
                                 }
                             }
-                            _ => {}
+                            // Everything else belongs to the page of a registered resource
+                            resource_page_message => {
+                                let ResourcePage::Resource(key) = &self.resource_page else {
+                                    return Command::none();
+                                };
+
+                                if let Some(resource) = self.resources.get_mut(key) {
+                                    return resource
+                                        .update_page(resource_page_message)
+                                        .map(AppMessage::ResourcePageMessage);
+                                }
+                            }
                         }
                     }
                     AppMessage::ResourcePreviewMessage(preview_message) => {
@@ -580,7 +528,6 @@ impl Application for App {
                                 self.active_preview = active_preview;
 
                                 try_update_in_depth(self);
-                                update_resource_page(self);
 
                                 // change resource page to match preview
 
@@ -596,12 +543,106 @@ impl Application for App {
 
         Command::none()
     }
+}
+
+impl Application for App {
+    type Message = AppMessage;
+    type Theme = Theme;
+    type Executor = executor::Default;
+    type Flags = ();
+
+    fn new(_flags: ()) -> (Self, Command<AppMessage>) {
+        let preferences = Preferences::new();
+
+        let (mut resources, logical_core_count) = live_resources(&preferences);
+
+        let active_preview = ActivePreview {
+            resource: ResourceType::Cpu,
+            name: None,
+        };
+
+        resources.open_page(&active_preview, &preferences);
+
+        let new_self = Self {
+            state: AppState::Loading,
+            preferences,
+            logical_core_count,
+            tick: 0,
+            snapshot: Arc::new(Snapshot::default()),
+            collector: None,
+            collector_request: CollectorRequest::new(Duration::from_millis(
+                preferences.tick_interval,
+            )),
+            resource_history: ResourceHistory::default(),
+            sidebar_items: Vec::new(),
+            resource_page: ResourcePage::Resource(active_preview.clone()),
+            active_preview,
+            resource_data: ResourceData::new(),
+            resources,
+            previews: ResourcePreviews::default(),
+            recording: None,
+            recording_error: None,
+            replay: None,
+            export_status: None,
+        };
+
+        let command = Command::batch(vec![
+            // font::load(iced_aw::NERD_FONT_BYTES).map(Message::FontLoaded),
+            // Command::perform(load(), Message::Loaded),
+            font::load(iced_aw::BOOTSTRAP_FONT_BYTES).map(AppMessage::FontLoaded),
+            Command::perform(load(), AppMessage::Loaded),
+        ]);
+
+        (new_self, command)
+    }
+
+    fn title(&self) -> String {
+        String::from("C Tasks")
+    }
+
+    fn update(&mut self, message: AppMessage) -> Command<AppMessage> {
+        // The collector can start before loading finishes, and never starts again if its sender is dropped
+        if let AppMessage::Collector(CollectorEvent::Ready(collector)) = message {
+            self.collector = Some(collector);
+            sync_collector_request(self);
+
+            return Command::none();
+        }
+
+        let command = self.update_state(message);
+
+        sync_collector_request(self);
+
+        Command::batch([command, load_desktop_entries(self)])
+    }
 
     fn subscription(&self) -> Subscription<AppMessage> {
-        iced::time::every(std::time::Duration::from_millis(
-            self.preferences.tick_interval,
-        ))
-        .map(|_| AppMessage::Tick)
+        // A replay takes the place of the collector, which stops when it's no longer subscribed to
+        let source = match &self.replay {
+            None => collector::collect(CollectorRequest::new(Duration::from_millis(
+                self.preferences.tick_interval,
+            )))
+            .map(AppMessage::Collector),
+            Some(replay) => match replay.tick_interval() {
+                Some(interval) => iced::time::every(interval)
+                    .map(|_| AppMessage::Replay(ReplayMessage::Tick)),
+                None => Subscription::none(),
+            },
+        };
+
+        // The processes page needs to know when shift is held for range selection
+        let modifiers = iced::event::listen_with(|event, _status| match event {
+            iced::Event::Keyboard(iced::keyboard::Event::ModifiersChanged(modifiers)) => {
+                Some(AppMessage::ResourcePageMessage(
+                    ResourcePageMessage::ProcessesPageMessage(
+                        ProcessesPageMessage::ModifiersChanged(modifiers),
+                    ),
+                ))
+            }
+            _ => None,
+        });
+
+        Subscription::batch(vec![source, modifiers])
     }
 
     fn view(&self) -> Element<AppMessage> {
@@ -675,52 +716,60 @@ impl Application for App {
                             .processes
                             .view(&self.preferences, &self.active_preview)
                             .map(AppMessage::ResourcePreviewMessage),
-                        self.previews
-                            .cpu
-                            .view(
-                                &self.preferences,
-                                &self.active_preview,
-                                &self.resource_data.cpu,
-                            )
-                            .map(AppMessage::ResourcePreviewMessage),
-                        self.previews
-                            .memory
-                            .view(
-                                &self.preferences,
-                                &self.active_preview,
-                                &self.resource_data.memory,
-                            )
-                            .map(AppMessage::ResourcePreviewMessage),
                     ];
 
-                    for (disk_name, disk_preview) in &self.previews.disks {
-                        let disk_data = self.resource_data.disks.get(disk_name).unwrap();
-
+                    for resource in self.resources.iter() {
                         children.push(
-                            disk_preview
-                                .view(&self.preferences, &self.active_preview, disk_data)
+                            resource
+                                .preview(&self.preferences, &self.active_preview)
                                 .map(AppMessage::ResourcePreviewMessage),
                         );
                     }
 
-                    children.push(
-                        self.previews
-                            .battery
-                            .view(
-                                &self.preferences,
-                                &self.active_preview,
-                                &self.resource_data.battery,
-                            )
-                            .map(AppMessage::ResourcePreviewMessage),
-                    );
-
                     children
                 })
                 .spacing(padding::PORTION);
 
+                let recording_controls = {
+                    let mut controls = Column::new().spacing(padding::PORTION);
+
+                    // Replaying stops collection, so there's nothing to record
+                    if self.replay.is_none() {
+                        let (icon, label) = match self.recording {
+                            Some(_) => (BootstrapIcon::StopFill, "Stop recording"),
+                            None => (BootstrapIcon::RecordCircle, "Record"),
+                        };
+
+                        controls = controls.push(
+                            button(row![bootstrap_icon(icon), text(label)].spacing(padding::PORTION))
+                                .on_press(AppMessage::ToggleRecording)
+                                .style(theme::Button::Text)
+                                .width(Length::Fill),
+                        );
+                    }
+
+                    controls = controls.push(
+                        button(
+                            row![bootstrap_icon(BootstrapIcon::FolderFill), text("Open recording")]
+                                .spacing(padding::PORTION),
+                        )
+                        .on_press(AppMessage::OpenRecording)
+                        .style(theme::Button::Text)
+                        .width(Length::Fill),
+                    );
+
+                    if let Some(error) = &self.recording_error {
+                        controls = controls.push(
+                            text(error).style(theme::Text::Color(custom_theme::DANGER)),
+                        );
+                    }
+
+                    controls
+                };
+
                 let sidebar = container(
                     iced::widget::scrollable(
-                        column![sidebar_header, sidebar_content_new, sidebar_content]
+                        column![sidebar_header, sidebar_content_new, sidebar_content, recording_controls]
                             .spacing(20)
                             .padding(padding::MAIN),
                     )
@@ -771,59 +820,40 @@ impl Application for App {
                                 )
                             }),
                         ResourcePage::Processes(processes_page) => processes_page
-                            .view(&self.preferences, &self.resource_data.processes)
-                            .map(move |message| {
-                                AppMessage::ResourcePageMessage(
-                                    ResourcePageMessage::ProcessesPageMessage(message),
-                                )
-                            }),
-                        ResourcePage::Cpu(cpu_page) => cpu_page
                             .view(
                                 &self.preferences,
-                                &self.resource_data.cpu,
-                                self.physical_core_count,
-                                self.logical_core_count,
-                                self.cpu_brand.clone(),
+                                &self.resource_data.processes,
+                                &self.resource_history,
                             )
                             .map(move |message| {
                                 AppMessage::ResourcePageMessage(
-                                    ResourcePageMessage::CpuPageMessage(message),
-                                )
-                            }),
-                        ResourcePage::Memory(memory_page) => memory_page
-                            .view(&self.preferences, &self.resource_data.memory)
-                            .map(move |message| {
-                                AppMessage::ResourcePageMessage(
-                                    ResourcePageMessage::MemoryPageMessage(message),
+                                    ResourcePageMessage::ProcessesPageMessage(message),
                                 )
                             }),
-                        ResourcePage::Disk(disk_page) => {
-                            let active_preview_name = &self.active_preview.name.as_ref().unwrap();
+                        ResourcePage::ProcessDetails(process_details_page) => {
+                            let Some(data) = &self.resource_data.process_details else {
+                                return text(String::from("Error: failed to access process data"))
+                                    .into();
+                            };
 
-                            let Some(data) =
-                                self.resource_data.disks.get(active_preview_name.as_str())
-                            else {
-                                return text(format!(
-                                    "Error: failed to access data for disk {}",
-                                    active_preview_name.as_str()
-                                ))
-                                .into();
+                            process_details_page
+                                .view(&self.preferences, data)
+                                .map(move |message| {
+                                    AppMessage::ResourcePageMessage(
+                                        ResourcePageMessage::ProcessDetailsPageMessage(message),
+                                    )
+                                })
+                        }
+                        ResourcePage::Resource(key) => {
+                            let Some(resource) = self.resources.get(key) else {
+                                return text(String::from("Error: failed to match resource"))
+                                    .into();
                             };
 
-                            disk_page.view(&self.preferences, data).map(move |message| {
-                                AppMessage::ResourcePageMessage(
-                                    ResourcePageMessage::DiskPageMessage(message),
-                                )
-                            })
+                            resource
+                                .view_page(&self.preferences)
+                                .map(AppMessage::ResourcePageMessage)
                         }
-                        ResourcePage::Battery(battery_page) => battery_page
-                            .view(&self.preferences, &self.resource_data.battery)
-                            .map(move |message| {
-                                AppMessage::ResourcePageMessage(
-                                    ResourcePageMessage::BatteryPageMessage(message),
-                                )
-                            }),
-                        _ => text(String::from("Error: failed to match resource")).into(),
                     };
 
                     /* match self.active_preview.resource {
@@ -851,9 +881,40 @@ impl Application for App {
                 })
                 .style(main_content());
 
+                let export_status: Element<_> = match &self.export_status {
+                    Some(Ok(path)) => text(format!("Exported to {}", path.display()))
+                        .style(theme::Text::Color(custom_theme::GREY_TEXT))
+                        .into(),
+                    Some(Err(error)) => text(format!("Couldn't export: {}", error))
+                        .style(theme::Text::Color(custom_theme::DANGER))
+                        .into(),
+                    None => Space::new(Length::Shrink, Length::Shrink).into(),
+                };
+
+                let toolbar = container(
+                    row![
+                        horizontal_space(),
+                        export_status,
+                        button(
+                            row![bootstrap_icon(BootstrapIcon::Download), text("Export")]
+                                .spacing(padding::PORTION),
+                        )
+                        .on_press(AppMessage::Export)
+                        .style(theme::Button::Text),
+                    ]
+                    .spacing(padding::MAIN)
+                    .align_items(Alignment::Center),
+                )
+                .width(Length::Fill)
+                .padding([0, padding::MAIN]);
+
                 let left = sidebar;
-                let right =
-                    column![/* header, */ main_new /* footer */].width(Length::FillPortion(3));
+                let mut right =
+                    column![/* header, */ toolbar, main_new /* footer */].width(Length::FillPortion(3));
+
+                if let Some(replay) = &self.replay {
+                    right = right.push(replay.view().map(AppMessage::Replay));
+                }
 
                 let container = container(
                     FloatingElement::new(row![left, right], floating_content)
@@ -896,7 +957,7 @@ impl Application for App {
     }
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Copy)]
+#[derive(Debug, Default, Clone, PartialEq, PartialOrd, Copy)]
 pub enum ResourceType {
     #[default]
     Applications,
@@ -911,71 +972,397 @@ pub enum ResourceType {
 }
 
 fn change_resource_page(app: &mut App, active_preview: &ActivePreview) {
+    if let ResourcePage::Resource(key) = &app.resource_page {
+        if let Some(resource) = app.resources.get_mut(key) {
+            resource.close_page();
+        }
+    }
+
+    if app.resources.open_page(active_preview, &app.preferences) {
+        app.resource_page = ResourcePage::Resource(active_preview.clone());
+
+        return;
+    }
+
     match active_preview.resource {
         ResourceType::Applications => {
             app.resource_page = ResourcePage::Applications(ApplicationsPage::new(&app.preferences));
         }
         ResourceType::Processes => {
-            app.resource_page = ResourcePage::Processes(ProcessesPage::new(&app.preferences));
+            app.resource_page = ResourcePage::Processes(ProcessesPage::new(
+                &app.preferences,
+                app.logical_core_count,
+            ));
+        }
+        _ => {}
+    }
+}
+
+/// The resources of this system, and its logical core count.
+/// Sampling happens in the collector, this is only to know the CPU up front
+fn live_resources(preferences: &Preferences) -> (ResourceRegistry, u32) {
+    let source = SysinfoSource::new();
+    let logical_core_count = source.cpus().len() as u32;
+
+    let resources = ResourceRegistry::new(
+        logical_core_count,
+        source.physical_core_count(),
+        source.cpu_brand(),
+        source.cpu_info(),
+        preferences.history_retention,
+        MetricsStore::open(),
+    );
+
+    (resources, logical_core_count)
+}
+
+/// The resources of the system a recording was made on. Its history isn't saved with ours
+fn recorded_resources(recording: &Recording, preferences: &Preferences) -> ResourceRegistry {
+    ResourceRegistry::new(
+        recording.header.logical_core_count,
+        recording.header.physical_core_count,
+        recording.header.cpu_brand.clone(),
+        recording.header.cpu_info.clone(),
+        preferences.history_retention,
+        MetricsStore::in_memory(),
+    )
+}
+
+/// Starts over with new resources and no history
+fn replace_resources(app: &mut App, resources: ResourceRegistry, logical_core_count: u32) {
+    app.resources = resources;
+    app.logical_core_count = logical_core_count;
+    app.resource_history = ResourceHistory::default();
+    app.snapshot = Arc::new(Snapshot::default());
+}
+
+/// Switching between live data and a recording can mean a different system, with other processes and cores
+fn reset_process_pages(app: &mut App) {
+    if let ResourcePage::Processes(_) | ResourcePage::ProcessDetails(_) = &app.resource_page {
+        app.resource_page = ResourcePage::Processes(ProcessesPage::new(
+            &app.preferences,
+            app.logical_core_count,
+        ));
+        app.resource_data.process_details = None;
+    }
+}
+
+/// Opens the current page again once the resources and history it shows were replaced
+fn reopen_resource_page(app: &mut App) {
+    match &app.resource_page {
+        ResourcePage::Resource(key) => {
+            let key = key.clone();
+
+            if app.resources.open_page(&key, &app.preferences) {
+                return;
+            }
+
+            // Like a disk that the other system doesn't have
+            let active_preview = ActivePreview {
+                resource: ResourceType::Cpu,
+                name: None,
+            };
+
+            change_resource_page(app, &active_preview);
+            app.active_preview = active_preview;
         }
-        ResourceType::Cpu => {
-            app.resource_page =
-                ResourcePage::Cpu(CpuPage::new(&app.preferences, app.logical_core_count));
+        ResourcePage::ProcessDetails(process_details_page) => {
+            let pid = process_details_page.pid;
+            let start_time = app
+                .snapshot
+                .processes
+                .get(&pid)
+                .map(|process| process.start_time)
+                .unwrap_or_default();
+            let history =
+                app.resource_history
+                    .process(pid, start_time, app.preferences.history_retention);
+
+            app.resource_page = ResourcePage::ProcessDetails(Box::new(ProcessDetailsPage::new(
+                &app.preferences,
+                pid,
+                history,
+            )));
         }
-        ResourceType::Disk => {
-            app.resource_page = ResourcePage::Disk(DiskPage::new(&app.preferences));
+        _ => {}
+    }
+}
+
+/// Stops collecting, and recording, to show the recording instead
+fn start_replay(app: &mut App, recording: Arc<Recording>) {
+    app.collector = None;
+    app.collector_request =
+        CollectorRequest::new(Duration::from_millis(app.preferences.tick_interval));
+    app.recording = None;
+
+    let resources = recorded_resources(&recording, &app.preferences);
+    replace_resources(app, resources, recording.header.logical_core_count);
+    reset_process_pages(app);
+
+    app.replay = Some(Replay::new(recording));
+    show_replay_position(app);
+}
+
+fn update_replay(app: &mut App, message: ReplayMessage) {
+    let Some(replay) = &mut app.replay else {
+        return;
+    };
+
+    match message {
+        ReplayMessage::TogglePlaying => {
+            replay.playing = !replay.playing;
+
+            // Playing from the end starts over
+            if replay.playing && replay.is_at_end() {
+                replay.position = 0;
+                show_replay_position(app);
+            }
         }
-        ResourceType::Memory => {
-            app.resource_page = ResourcePage::Memory(MemoryPage::new(&app.preferences));
+        ReplayMessage::Seek(position) => {
+            replay.position = (position as usize).min(replay.recording.snapshots.len() - 1);
+            show_replay_position(app);
+        }
+        ReplayMessage::Tick => {
+            if replay.is_at_end() {
+                replay.playing = false;
+                return;
+            }
+
+            replay.position += 1;
+
+            if replay.is_at_end() {
+                replay.playing = false;
+            }
+
+            let snapshot = replay.snapshot().clone();
+            app.apply_snapshot(snapshot);
         }
-        ResourceType::Battery => {
-            app.resource_page = ResourcePage::Battery(BatteryPage::new(&app.preferences));
+        ReplayMessage::Close => {
+            app.replay = None;
+
+            // The collector starts again now that the replay isn't in its place
+            let (resources, logical_core_count) = live_resources(&app.preferences);
+            replace_resources(app, resources, logical_core_count);
+            reset_process_pages(app);
+            reopen_resource_page(app);
         }
-        _ => {}
     }
 }
 
+/// Shows the snapshot at the replay's position, with the history leading up to it
+fn show_replay_position(app: &mut App) {
+    let Some(replay) = &app.replay else {
+        return;
+    };
+
+    let recording = replay.recording.clone();
+    let position = replay.position;
+    let history_start = replay.history_start(app.preferences.history_retention);
+
+    let resources = recorded_resources(&recording, &app.preferences);
+    replace_resources(app, resources, recording.header.logical_core_count);
+
+    for snapshot in &recording.snapshots[history_start..position] {
+        app.apply_snapshot(snapshot.clone());
+    }
+
+    // The page has to be open for the last snapshot to fill in what only it shows
+    let snapshot = recording.snapshots[position].clone();
+    app.resources.discover(&snapshot);
+    reopen_resource_page(app);
+
+    app.apply_snapshot(snapshot);
+}
+
 fn try_update_in_depth(app: &mut App) {
     match &mut app.resource_page {
         ResourcePage::Applications(_) => {
-            app.resource_data
-                .applications
-                .update_in_depth(&mut app.system_info);
+            app.resource_data.applications.update_in_depth(&app.snapshot);
         }
         ResourcePage::Processes(_) => {
-            app.resource_data
-                .processes
-                .update_in_depth(&mut app.system_info);
+            app.resource_data.processes.update_in_depth(&app.snapshot);
+        }
+        ResourcePage::ProcessDetails(process_details_page) => {
+            let pid = process_details_page.pid;
+
+            // Until the collector sends details for a newly opened process there are none to show
+            app.resource_data.process_details = app
+                .snapshot
+                .process_details
+                .as_ref()
+                .filter(|process_details| process_details.pid == pid)
+                .cloned();
         }
-        ResourcePage::Disk(_) => {
-            for disk in &app.disk_info {
-                // to optimize: check if disk name matches data we want to update
+        _ => {}
+    }
+}
+
+/// The current page's history, values and table, for the export action
+fn page_export(app: &App) -> Export {
+    match &app.resource_page {
+        ResourcePage::Resource(key) => {
+            let page = match &key.name {
+                Some(name) => format!("{:?} {}", key.resource, name),
+                None => format!("{:?}", key.resource),
+            };
 
-                let disk_name = disk.name().to_str().unwrap_or("default").to_string();
+            let mut export = Export::new(page.to_lowercase());
 
-                let disk_data = app.resource_data.disks.get_mut(&disk_name).unwrap();
+            if let Some(resource) = app.resources.get(key) {
+                for (name, series) in resource.history() {
+                    export.add_series(name, series);
+                }
+
+                // Disks share the I/O pressure, so it isn't part of their own history
+                if key.resource == ResourceType::Disk {
+                    for (name, series) in app.resources.io_pressure.history() {
+                        export.add_series(name, series);
+                    }
+                }
 
-                disk_data.update_in_depth(&disk_name, disk);
+                export.details = resource.details();
             }
+
+            export
+        }
+        ResourcePage::Applications(_) => {
+            let applications = &app.resource_data.applications.in_depth.applications;
+
+            let mut export = Export::new("applications");
+            export.details = vec![Detail::number(
+                "Applications",
+                applications.len() as f64,
+                "",
+            )];
+            export.table = Some(export::applications_table(applications));
+
+            export
+        }
+        ResourcePage::Processes(_) => {
+            let processes = &app.resource_data.processes;
+
+            let mut export = Export::new("processes");
+            export.details = vec![
+                Detail::number("Processes", processes.in_depth.processes.len() as f64, ""),
+                Detail::text("Filter", processes.filter_query.clone()),
+            ];
+            export.table = Some(export::processes_table(
+                &processes.layout.columns,
+                &processes.in_depth.processes,
+            ));
+
+            export
+        }
+        ResourcePage::ProcessDetails(process_details_page) => {
+            let pid = process_details_page.pid;
+            let mut export = Export::new(format!("process {}", pid));
+
+            if let Some(history) = app.resource_history.processes.get(&pid) {
+                export.add_series("cpu", &history.cpu);
+                export.add_series("memory", &history.memory);
+                export.add_series("disk_read", &history.disk_read);
+                export.add_series("disk_written", &history.disk_written);
+            }
+
+            if let Some(data) = &app.resource_data.process_details {
+                export.details = process_details(data);
+            }
+
+            export
         }
-        _ => {}
     }
 }
 
-fn update_resource_page(app: &mut App) {
-    match &mut app.resource_page {
-        ResourcePage::Cpu(cpu_page) => {
-            cpu_page.update_history(&app.resource_history);
-        }
-        ResourcePage::Memory(memory_page) => {
-            memory_page.update_history(&app.resource_history);
+fn process_details(data: &types::resource_data::ProcessDetailsData) -> Vec<Detail> {
+    let path = |path: &Option<PathBuf>| path.as_ref().map(|path| path.display().to_string());
+
+    vec![
+        Detail::number("PID", data.pid.as_u32() as f64, ""),
+        Detail::text("Name", data.name.clone()),
+        Detail::text("Command", data.command.join(" ")),
+        Detail::optional("Executable", path(&data.exe), ""),
+        Detail::optional("Working directory", path(&data.cwd), ""),
+        Detail::optional("User", data.user.clone(), ""),
+        Detail::optional("Group", data.group.clone(), ""),
+        Detail::text("State", data.state.clone()),
+        Detail::number("Started", data.start_time as f64, "unix seconds"),
+        Detail::number("Running for", data.run_time as f64, "seconds"),
+        Detail::optional("Threads", data.thread_count.map(f64::from), ""),
+        Detail::number("CPU", data.cpu_usage as f64, "percent"),
+        Detail::number("Memory", data.memory_usage as f64, "bytes"),
+        Detail::number("Virtual memory", data.virtual_memory as f64, "bytes"),
+        Detail::optional(
+            "Total read",
+            data.io.map(|io| io.read_bytes as f64),
+            "bytes",
+        ),
+        Detail::optional(
+            "Total written",
+            data.io.map(|io| io.write_bytes as f64),
+            "bytes",
+        ),
+        Detail::optional(
+            "Open files",
+            data.open_files.as_ref().map(|files| files.len() as f64),
+            "",
+        ),
+        Detail::optional("Cgroup", data.cgroup.clone(), ""),
+    ]
+}
+
+/// What the current page needs the collector to sample on top of the basics
+fn collector_request(app: &App) -> CollectorRequest {
+    let mut request = CollectorRequest::new(Duration::from_millis(app.preferences.tick_interval));
+    request.recording = app.recording.clone();
+
+    match &app.resource_page {
+        ResourcePage::Applications(_) => {
+            request.cgroups = true;
         }
-        ResourcePage::Disk(disk_page) => {
-            disk_page.update_history(&app.active_preview, &app.resource_history)
+        ResourcePage::Processes(_) => {
+            request.process_columns = app.resource_data.processes.layout.columns.clone();
         }
-        ResourcePage::Battery(battery_page) => {
-            battery_page.update_history(&app.resource_history);
+        ResourcePage::ProcessDetails(process_details_page) => {
+            request.details_pid = Some(process_details_page.pid);
         }
         _ => {}
     }
+
+    request
+}
+
+/// Starts loading the desktop entries the first time the applications page is open
+fn load_desktop_entries(app: &mut App) -> Command<AppMessage> {
+    let applications = &mut app.resource_data.applications;
+
+    if !matches!(app.resource_page, ResourcePage::Applications(_))
+        || applications.desktop_entries.is_some()
+        || applications.loading_desktop_entries
+    {
+        return Command::none();
+    }
+
+    applications.loading_desktop_entries = true;
+
+    Command::perform(
+        DesktopEntries::load_in_background(),
+        AppMessage::DesktopEntriesLoaded,
+    )
+}
+
+/// Tells the collector what to sample if that changed, which makes it send a new snapshot right away
+fn sync_collector_request(app: &mut App) {
+    let request = collector_request(app);
+
+    if request == app.collector_request {
+        return;
+    }
+
+    let Some(collector) = &app.collector else {
+        return;
+    };
+
+    if collector.send(request.clone()).is_ok() {
+        app.collector_request = request;
+    }
 }
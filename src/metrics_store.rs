@@ -0,0 +1,729 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, Sender},
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    types::time_series::{Sample, TimeSeries, Unit},
+    utils::data_dir,
+};
+
+/// A resolution history is downsampled to, and how long it's kept at it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tier {
+    /// Also the directory the tier is stored in
+    pub name: &'static str,
+    pub resolution: Duration,
+    pub retention: Duration,
+}
+
+impl Tier {
+    /// The most buckets that fit in the retention
+    fn capacity(&self) -> usize {
+        (self.retention.as_secs() / self.resolution.as_secs()) as usize
+    }
+}
+
+/// From finest to coarsest
+pub const TIERS: [Tier; 3] = [
+    Tier {
+        name: "10m",
+        resolution: Duration::from_secs(1),
+        retention: Duration::from_secs(10 * 60),
+    },
+    Tier {
+        name: "24h",
+        resolution: Duration::from_secs(60),
+        retention: Duration::from_secs(24 * 60 * 60),
+    },
+    Tier {
+        name: "30d",
+        resolution: Duration::from_secs(15 * 60),
+        retention: Duration::from_secs(30 * 24 * 60 * 60),
+    },
+];
+
+/// The min, average and max of every bucket in a tier, timestamped by when the bucket started.
+/// Clones share their samples, like the time series they're made of
+#[derive(Debug, Clone)]
+pub struct Rollups {
+    pub tier: Tier,
+    pub min: TimeSeries,
+    pub avg: TimeSeries,
+    pub max: TimeSeries,
+}
+
+impl Rollups {
+    fn new(tier: Tier, unit: Unit) -> Self {
+        Self {
+            tier,
+            min: TimeSeries::new(unit, tier.retention),
+            avg: TimeSeries::new(unit, tier.retention),
+            max: TimeSeries::new(unit, tier.retention),
+        }
+    }
+
+    fn push(&mut self, bucket: &Bucket) {
+        self.min.push(bucket.start, bucket.min);
+        self.avg
+            .push(bucket.start, bucket.sum / bucket.count as f64);
+        self.max.push(bucket.start, bucket.max);
+    }
+}
+
+/// The samples of a tier's resolution that have been recorded so far
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    start: SystemTime,
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: u32,
+}
+
+impl Bucket {
+    fn new(start: SystemTime, value: f64) -> Self {
+        Self {
+            start,
+            min: value,
+            max: value,
+            sum: value,
+            count: 1,
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+        self.count += 1;
+    }
+
+    /// Adds the samples of another part of the same bucket, like one saved before a restart
+    fn merge(&mut self, other: &Bucket) {
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.sum += other.sum;
+        self.count += other.count;
+    }
+}
+
+/// The start of the bucket a time falls in, so buckets line up across restarts
+fn bucket_start(timestamp: SystemTime, resolution: Duration) -> SystemTime {
+    let since_epoch = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let resolution = resolution.as_secs().max(1);
+
+    UNIX_EPOCH + Duration::from_secs(since_epoch.as_secs() / resolution * resolution)
+}
+
+/// How much bucket time the finest tier collects before its lines are written, so files aren't opened every second
+const WRITE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// One tier of a series and the file it's appended to. Lives on the store's writer thread
+#[derive(Debug)]
+struct TierHistory {
+    /// Shared with the store, which hands out clones of them
+    rollups: Rollups,
+    /// Only has the samples recorded since the series was loaded
+    bucket: Option<Bucket>,
+    /// The part of the newest bucket that was saved before the series was loaded, while that bucket may still be
+    /// recorded to. Kept out of the rollups so the bucket isn't in them twice
+    saved_part: Option<Bucket>,
+    /// Complete buckets that haven't been written yet
+    unwritten: Vec<Bucket>,
+    /// None if there's nowhere to save to or saving failed
+    path: Option<PathBuf>,
+    /// Lines in the file, which can be more than the rollups until it's compacted
+    lines: usize,
+}
+
+impl TierHistory {
+    /// Loads the buckets still in the retention into the rollups, compacting the file if some weren't
+    fn load(rollups: Rollups, path: Option<PathBuf>) -> Self {
+        let tier = rollups.tier;
+
+        let mut history = Self {
+            rollups,
+            bucket: None,
+            saved_part: None,
+            unwritten: Vec::new(),
+            path,
+            lines: 0,
+        };
+
+        let Some(file) = history.path.as_ref().and_then(|path| File::open(path).ok()) else {
+            return history;
+        };
+
+        let oldest = SystemTime::now()
+            .checked_sub(tier.retention)
+            .unwrap_or(UNIX_EPOCH);
+
+        let mut buckets: Vec<Bucket> = Vec::new();
+
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            history.lines += 1;
+
+            let Some(bucket) = parse_line(&line) else {
+                continue;
+            };
+
+            if bucket.start < oldest {
+                continue;
+            }
+
+            // A bucket that was in progress when the app last exited is saved in parts
+            match buckets.last_mut() {
+                Some(last) if last.start == bucket.start => last.merge(&bucket),
+                _ => buckets.push(bucket),
+            }
+        }
+
+        let current = bucket_start(SystemTime::now(), tier.resolution);
+
+        if buckets.last().is_some_and(|last| last.start == current) {
+            history.saved_part = buckets.pop();
+        }
+
+        for bucket in &buckets {
+            history.rollups.push(bucket);
+        }
+
+        if history.lines > buckets.len() + history.saved_part.iter().count() {
+            history.compact();
+        }
+
+        history
+    }
+
+    fn record(&mut self, sample: Sample) {
+        let start = bucket_start(sample.timestamp, self.rollups.tier.resolution);
+
+        match &mut self.bucket {
+            Some(bucket) if bucket.start == start => bucket.add(sample.value),
+            _ => {
+                if let Some(bucket) = self.bucket.take() {
+                    self.save(bucket);
+                }
+
+                // The saved part is complete if recording moved on from its bucket
+                if let Some(saved_part) = self.saved_part.filter(|part| part.start != start) {
+                    self.rollups.push(&saved_part);
+                    self.saved_part = None;
+                }
+
+                self.bucket = Some(Bucket::new(start, sample.value));
+            }
+        }
+    }
+
+    /// Adds a complete bucket to the rollups and writes it to the file once enough have been collected
+    fn save(&mut self, bucket: Bucket) {
+        let mut complete = bucket;
+
+        if let Some(saved_part) = self.saved_part.filter(|part| part.start == bucket.start) {
+            complete.merge(&saved_part);
+            self.saved_part = None;
+        }
+
+        self.rollups.push(&complete);
+        self.unwritten.push(bucket);
+
+        let collected = self
+            .unwritten
+            .first()
+            .and_then(|first| bucket.start.duration_since(first.start).ok())
+            .unwrap_or_default();
+
+        if collected + self.rollups.tier.resolution >= WRITE_INTERVAL {
+            self.write();
+        }
+    }
+
+    /// Appends the unwritten buckets to the file. The file is only open while writing, since a handle per tier of
+    /// every series would add up to hundreds on machines with many cores
+    fn write(&mut self) {
+        if self.unwritten.is_empty() {
+            return;
+        }
+
+        let Some(path) = &self.path else {
+            self.unwritten.clear();
+            return;
+        };
+
+        let contents: String = self.unwritten.iter().map(format_line).collect();
+
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| OpenOptions::new().create(true).append(true).open(path))
+            .and_then(|mut file| file.write_all(contents.as_bytes()));
+
+        if let Err(error) = result {
+            eprintln!("Couldn't save history to {}: {}", path.display(), error);
+            self.path = None;
+            self.unwritten.clear();
+            return;
+        }
+
+        self.lines += self.unwritten.len();
+        self.unwritten.clear();
+
+        // Old buckets stay in the file until it's rewritten, so don't let it grow much past the retention
+        if self.lines > self.rollups.tier.capacity() * 2 {
+            self.compact();
+        }
+    }
+
+    /// Writes everything recorded so far, including the bucket in progress. Its samples are merged with those
+    /// recorded after the next start, if that's still within the bucket
+    fn flush(&mut self) {
+        if let Some(bucket) = self.bucket.take() {
+            self.unwritten.push(bucket);
+        }
+
+        self.write();
+    }
+
+    /// Rewrites the file with only the buckets still in the retention
+    fn compact(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let min = self.rollups.min.samples();
+        let avg = self.rollups.avg.samples();
+        let max = self.rollups.max.samples();
+
+        let mut contents: String = min
+            .iter()
+            .zip(avg.iter())
+            .zip(max.iter())
+            .map(|((min, avg), max)| {
+                format_line(&Bucket {
+                    start: avg.timestamp,
+                    min: min.value,
+                    max: max.value,
+                    sum: avg.value,
+                    count: 1,
+                })
+            })
+            .collect();
+
+        if let Some(saved_part) = &self.saved_part {
+            contents.push_str(&format_line(saved_part));
+        }
+
+        let temporary_path = path.with_extension("tmp");
+
+        let result =
+            fs::write(&temporary_path, contents).and_then(|_| fs::rename(&temporary_path, path));
+
+        if let Err(error) = result {
+            eprintln!("Couldn't compact history in {}: {}", path.display(), error);
+            return;
+        }
+
+        self.lines = avg.len() + self.saved_part.iter().count();
+    }
+}
+
+impl Drop for TierHistory {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// milliseconds since the unix epoch, min, avg, max, sample count
+fn format_line(bucket: &Bucket) -> String {
+    let start = bucket
+        .start
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+
+    format!(
+        "{},{},{},{},{}\n",
+        start,
+        bucket.min,
+        bucket.sum / bucket.count as f64,
+        bucket.max,
+        bucket.count
+    )
+}
+
+/// The sample count is only needed to merge the parts of a bucket, and older files don't have it
+fn parse_line(line: &str) -> Option<Bucket> {
+    let mut fields = line.split(',');
+
+    let start = UNIX_EPOCH + Duration::from_millis(fields.next()?.trim().parse().ok()?);
+    let min = fields.next()?.trim().parse().ok()?;
+    let avg: f64 = fields.next()?.trim().parse().ok()?;
+    let max = fields.next()?.trim().parse().ok()?;
+    let count: u32 = match fields.next() {
+        Some(count) => count.trim().parse().ok()?,
+        None => 1,
+    };
+
+    Some(Bucket {
+        start,
+        min,
+        max,
+        sum: avg * count as f64,
+        count,
+    })
+}
+
+/// What the store sends its writer thread
+enum WriterMessage {
+    /// Loads what was saved of a series into its rollups, before anything is recorded to it
+    Open(String, Vec<Rollups>),
+    Record(String, Sample),
+}
+
+/// Long term history of every series, downsampled into tiers and kept on disk so it survives restarts.
+/// Each tier of a series is a file of one bucket per line, under the data dir.
+/// Files are only read and written on a writer thread, so the store only hands out rollups that are kept in memory
+#[derive(Debug)]
+pub struct MetricsStore {
+    /// The rollups of every series used so far, which the writer thread loads and records into
+    series: HashMap<String, Vec<Rollups>>,
+    /// None once the store is dropped, which stops the writer thread
+    writer: Option<Sender<WriterMessage>>,
+    writer_thread: Option<JoinHandle<()>>,
+}
+
+impl MetricsStore {
+    /// Saves history under the data dir, or only keeps it while running if there's none
+    pub fn open() -> Self {
+        Self::with_directory(data_dir().map(|data_dir| data_dir.join("history")))
+    }
+
+    /// Only keeps history while running, like for a recording of another system
+    pub fn in_memory() -> Self {
+        Self::with_directory(None)
+    }
+
+    fn with_directory(directory: Option<PathBuf>) -> Self {
+        let (writer, messages) = mpsc::channel();
+
+        Self {
+            series: HashMap::new(),
+            writer: Some(writer),
+            writer_thread: Some(thread::spawn(move || write_history(directory, messages))),
+        }
+    }
+
+    /// Adds a sample to every tier of the series
+    pub fn record(&mut self, key: &str, unit: Unit, sample: Sample) {
+        self.series(key, unit);
+        self.send(WriterMessage::Record(key.to_string(), sample));
+    }
+
+    /// The rollups of every tier of the series, from finest to coarsest. Saved history is loaded into them in the
+    /// background the first time the series is used
+    pub fn rollups(&mut self, key: &str, unit: Unit) -> Vec<Rollups> {
+        self.series(key, unit).clone()
+    }
+
+    /// Has the writer thread load the series the first time it's used
+    fn series(&mut self, key: &str, unit: Unit) -> &Vec<Rollups> {
+        if !self.series.contains_key(key) {
+            let rollups: Vec<Rollups> =
+                TIERS.iter().map(|tier| Rollups::new(*tier, unit)).collect();
+
+            self.send(WriterMessage::Open(key.to_string(), rollups.clone()));
+            self.series.insert(key.to_string(), rollups);
+        }
+
+        &self.series[key]
+    }
+
+    fn send(&self, message: WriterMessage) {
+        if let Some(writer) = &self.writer {
+            // Only fails if the writer thread panicked, after which nothing is recorded
+            let _ = writer.send(message);
+        }
+    }
+}
+
+impl Drop for MetricsStore {
+    /// Waits for the writer thread to save everything recorded
+    fn drop(&mut self) {
+        self.writer = None;
+
+        if let Some(writer_thread) = self.writer_thread.take() {
+            let _ = writer_thread.join();
+        }
+    }
+}
+
+/// Runs the writer thread until the store is dropped, then saves the buckets in progress
+fn write_history(directory: Option<PathBuf>, messages: Receiver<WriterMessage>) {
+    let mut series: HashMap<String, Vec<TierHistory>> = HashMap::new();
+
+    for message in messages {
+        match message {
+            WriterMessage::Open(key, rollups) => {
+                let tiers = rollups
+                    .into_iter()
+                    .map(|rollups| {
+                        let path = directory.as_ref().map(|directory| {
+                            directory
+                                .join(rollups.tier.name)
+                                .join(format!("{}.csv", file_name(&key)))
+                        });
+
+                        TierHistory::load(rollups, path)
+                    })
+                    .collect();
+
+                series.insert(key, tiers);
+            }
+            WriterMessage::Record(key, sample) => {
+                for tier in series.get_mut(&key).into_iter().flatten() {
+                    tier.record(sample);
+                }
+            }
+        }
+    }
+}
+
+/// Keys can contain things like the slashes in a disk's name
+fn file_name(key: &str) -> String {
+    key.chars()
+        .map(|character| match character {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '_' | '-' => character,
+            _ => '_',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        path::PathBuf,
+        process,
+        time::{Duration, SystemTime},
+    };
+
+    use crate::types::time_series::{Sample, Unit};
+
+    use super::{
+        bucket_start, format_line, parse_line, Bucket, MetricsStore, Rollups, Tier, TierHistory,
+        UNIX_EPOCH,
+    };
+
+    /// Coarse enough that a test doesn't cross into the next bucket
+    const DAILY: Tier = Tier {
+        name: "daily",
+        resolution: Duration::from_secs(24 * 60 * 60),
+        retention: Duration::from_secs(30 * 24 * 60 * 60),
+    };
+
+    fn history_path(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("ctasks-metrics-{}-{}.csv", process::id(), name));
+        let _ = fs::remove_file(&path);
+
+        path
+    }
+
+    fn averages(history: &TierHistory) -> Vec<f64> {
+        history
+            .rollups
+            .avg
+            .samples()
+            .iter()
+            .map(|sample| sample.value)
+            .collect()
+    }
+
+    #[test]
+    fn buckets_line_up_with_the_epoch() {
+        let minute = Duration::from_secs(60);
+
+        assert_eq!(
+            bucket_start(UNIX_EPOCH + Duration::from_millis(125_500), minute),
+            UNIX_EPOCH + Duration::from_secs(120)
+        );
+        assert_eq!(
+            bucket_start(UNIX_EPOCH + Duration::from_secs(180), minute),
+            UNIX_EPOCH + Duration::from_secs(180)
+        );
+    }
+
+    #[test]
+    fn lines_round_trip() {
+        let bucket = Bucket {
+            start: UNIX_EPOCH + Duration::from_millis(1_700_000_000_000),
+            min: 1.5,
+            max: 9.,
+            sum: 12.,
+            count: 3,
+        };
+
+        let line = format_line(&bucket);
+        assert_eq!(line, "1700000000000,1.5,4,9,3\n");
+
+        let parsed = parse_line(line.trim_end()).expect("line not parsed");
+        assert_eq!(parsed.start, bucket.start);
+        assert_eq!((parsed.min, parsed.max), (1.5, 9.));
+        assert_eq!((parsed.sum, parsed.count), (12., 3));
+
+        // Written before the sample count was saved
+        let old = parse_line("1700000000000,1,2,3").expect("old line not parsed");
+        assert_eq!((old.sum, old.count), (2., 1));
+
+        assert!(parse_line("1700000000000,1,2").is_none());
+        assert!(parse_line("not a line").is_none());
+    }
+
+    #[test]
+    fn expired_buckets_are_dropped_and_compacted_away() {
+        let path = history_path("retention");
+        let now = SystemTime::now();
+
+        let line = |age: Duration, value: f64| {
+            format_line(&Bucket {
+                start: now - age,
+                min: value,
+                max: value,
+                sum: value,
+                count: 1,
+            })
+        };
+
+        fs::write(
+            &path,
+            [
+                line(DAILY.retention * 2, 1.),
+                line(DAILY.retention + DAILY.resolution, 2.),
+                String::from("garbage\n"),
+                line(DAILY.resolution * 3, 3.),
+                line(DAILY.resolution * 2, 4.),
+            ]
+            .concat(),
+        )
+        .expect("history not written");
+
+        let history = TierHistory::load(Rollups::new(DAILY, Unit::Percent), Some(path.clone()));
+
+        assert_eq!(averages(&history), vec![3., 4.]);
+        assert_eq!(history.lines, 2);
+        assert_eq!(
+            fs::read_to_string(&path)
+                .expect("history not read")
+                .lines()
+                .count(),
+            2
+        );
+
+        drop(history);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn growing_files_are_compacted() {
+        let path = history_path("growth");
+        let tier = Tier {
+            name: "test",
+            resolution: Duration::from_secs(60),
+            retention: Duration::from_secs(2 * 60),
+        };
+
+        let mut history = TierHistory::load(Rollups::new(tier, Unit::Percent), Some(path.clone()));
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        for minute in 0..8 {
+            history.record(Sample {
+                timestamp: start + tier.resolution * minute,
+                value: minute as f64,
+            });
+        }
+
+        let lines = fs::read_to_string(&path)
+            .expect("history not written")
+            .lines()
+            .count();
+
+        assert!(lines <= tier.capacity() * 2);
+        assert_eq!(averages(&history), vec![4., 5., 6.]);
+
+        drop(history);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn bucket_in_progress_survives_a_restart() {
+        let path = history_path("restart");
+        let now = SystemTime::now();
+        let sample = |timestamp, value| Sample { timestamp, value };
+
+        {
+            let mut history =
+                TierHistory::load(Rollups::new(DAILY, Unit::Percent), Some(path.clone()));
+            history.record(sample(now, 10.));
+            history.record(sample(now, 20.));
+        }
+
+        let mut history = TierHistory::load(Rollups::new(DAILY, Unit::Percent), Some(path.clone()));
+
+        // Still in progress, so not in the rollups yet
+        assert!(averages(&history).is_empty());
+
+        history.record(sample(now, 60.));
+        history.record(sample(now + DAILY.resolution, 5.));
+
+        assert_eq!(averages(&history), vec![30.]);
+        assert_eq!(history.rollups.min.samples()[0].value, 10.);
+        assert_eq!(history.rollups.max.samples()[0].value, 60.);
+
+        drop(history);
+
+        let history = TierHistory::load(Rollups::new(DAILY, Unit::Percent), Some(path.clone()));
+        assert_eq!(averages(&history), vec![30., 5.]);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn store_saves_and_loads_on_its_writer_thread() {
+        let directory = env::temp_dir().join(format!("ctasks-metrics-{}-store", process::id()));
+        let _ = fs::remove_dir_all(&directory);
+
+        let now = SystemTime::now();
+        let sample = |age: u64, value| Sample {
+            timestamp: now - Duration::from_secs(age),
+            value,
+        };
+
+        {
+            let mut store = MetricsStore::with_directory(Some(directory.clone()));
+            store.record("cpu.cpu", Unit::Percent, sample(5, 10.));
+            store.record("cpu.cpu", Unit::Percent, sample(3, 20.));
+        }
+
+        let mut store = MetricsStore::with_directory(Some(directory.clone()));
+        let rollups = store.rollups("cpu.cpu", Unit::Percent);
+
+        // Dropping the store waits for the writer thread, which loads the series before it stops
+        drop(store);
+
+        let averages: Vec<f64> = rollups[0]
+            .avg
+            .samples()
+            .iter()
+            .map(|sample| sample.value)
+            .collect();
+        assert_eq!(averages, vec![10., 20.]);
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
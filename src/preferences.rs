@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::{constants::DisplayState, resource_pages::resource_details::SortDirection};
 
 #[derive(Debug, Copy, Clone)]
@@ -10,7 +12,8 @@ pub enum ByteBase {
 pub struct Preferences {
     display_state: DisplayState,
     percent_precision: u8,
-    history_ticks: i32,
+    /// How far back history is kept and charted
+    pub history_retention: Duration,
     pub chart_y_axis_labels: bool,
     pub chart_y_axis_major_grid_lines: usize,
     pub byte_base: ByteBase,
@@ -22,7 +25,7 @@ impl Default for Preferences {
         Self {
             tick_interval: 1000,
             percent_precision: 1,
-            history_ticks: 30,
+            history_retention: Duration::from_secs(30),
             display_state: DisplayState::Shown,
             chart_y_axis_labels: false,
             chart_y_axis_major_grid_lines: 6,
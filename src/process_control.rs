@@ -0,0 +1,380 @@
+use std::{fs, io};
+
+use sysinfo::{Pid, System};
+
+/// Signals that can be sent to a process from the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSignal {
+    Term,
+    Kill,
+    Stop,
+    Cont,
+    Hup,
+    Int,
+    /// Any other signal, by number
+    Custom(i32),
+}
+
+impl ProcessSignal {
+    /// The named signals, in the order they are shown to the user
+    pub const NAMED: [ProcessSignal; 6] = [
+        ProcessSignal::Term,
+        ProcessSignal::Kill,
+        ProcessSignal::Stop,
+        ProcessSignal::Cont,
+        ProcessSignal::Hup,
+        ProcessSignal::Int,
+    ];
+
+    pub fn number(&self) -> i32 {
+        match self {
+            ProcessSignal::Term => libc::SIGTERM,
+            ProcessSignal::Kill => libc::SIGKILL,
+            ProcessSignal::Stop => libc::SIGSTOP,
+            ProcessSignal::Cont => libc::SIGCONT,
+            ProcessSignal::Hup => libc::SIGHUP,
+            ProcessSignal::Int => libc::SIGINT,
+            ProcessSignal::Custom(number) => *number,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            ProcessSignal::Term => String::from("SIGTERM"),
+            ProcessSignal::Kill => String::from("SIGKILL"),
+            ProcessSignal::Stop => String::from("SIGSTOP"),
+            ProcessSignal::Cont => String::from("SIGCONT"),
+            ProcessSignal::Hup => String::from("SIGHUP"),
+            ProcessSignal::Int => String::from("SIGINT"),
+            ProcessSignal::Custom(number) => format!("signal {}", number),
+        }
+    }
+
+    /// Whether the signal may end or freeze the process, and so should be confirmed first.
+    /// Custom signals are assumed to be destructive since most default to terminating
+    pub fn is_destructive(&self) -> bool {
+        !matches!(self, ProcessSignal::Cont)
+    }
+}
+
+/// Sends the signal to the process, returning the OS error (EPERM, ESRCH, etc.) if it fails
+pub fn send_signal(pid: Pid, signal: ProcessSignal) -> io::Result<()> {
+    let result = unsafe { libc::kill(pid.as_u32() as libc::pid_t, signal.number()) };
+
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Scheduling policies for normal, non realtime, processes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchedulingPolicy {
+    Other,
+    Batch,
+    Idle,
+}
+
+impl SchedulingPolicy {
+    pub const ALL: [SchedulingPolicy; 3] = [
+        SchedulingPolicy::Other,
+        SchedulingPolicy::Batch,
+        SchedulingPolicy::Idle,
+    ];
+
+    fn number(&self) -> i32 {
+        match self {
+            SchedulingPolicy::Other => libc::SCHED_OTHER,
+            SchedulingPolicy::Batch => libc::SCHED_BATCH,
+            SchedulingPolicy::Idle => libc::SCHED_IDLE,
+        }
+    }
+
+    fn from_number(number: i32) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|policy| policy.number() == number)
+    }
+}
+
+impl std::fmt::Display for SchedulingPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SchedulingPolicy::Other => "SCHED_OTHER",
+            SchedulingPolicy::Batch => "SCHED_BATCH",
+            SchedulingPolicy::Idle => "SCHED_IDLE",
+        })
+    }
+}
+
+/// The nice value of the process, from -20 (highest priority) to 19
+pub fn niceness(pid: Pid) -> io::Result<i32> {
+    // getpriority can legitimately return -1, so errors have to be told apart through errno
+    let niceness = unsafe {
+        *libc::__errno_location() = 0;
+        libc::getpriority(libc::PRIO_PROCESS, pid.as_u32() as libc::id_t)
+    };
+
+    if niceness == -1 {
+        let error = io::Error::last_os_error();
+
+        if error.raw_os_error().is_some_and(|code| code != 0) {
+            return Err(error);
+        }
+    }
+
+    Ok(niceness)
+}
+
+pub fn set_niceness(pid: Pid, niceness: i32) -> io::Result<()> {
+    let result =
+        unsafe { libc::setpriority(libc::PRIO_PROCESS, pid.as_u32() as libc::id_t, niceness) };
+
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// The policy of the process, or None if it is realtime (SCHED_FIFO, SCHED_RR, etc.)
+pub fn scheduling_policy(pid: Pid) -> io::Result<Option<SchedulingPolicy>> {
+    let policy = unsafe { libc::sched_getscheduler(pid.as_u32() as libc::pid_t) };
+
+    if policy == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // Ignore SCHED_RESET_ON_FORK, which is or'd into the policy
+    Ok(SchedulingPolicy::from_number(
+        policy & !libc::SCHED_RESET_ON_FORK,
+    ))
+}
+
+pub fn set_scheduling_policy(pid: Pid, policy: SchedulingPolicy) -> io::Result<()> {
+    // Non realtime policies require a priority of 0
+    let param = libc::sched_param { sched_priority: 0 };
+
+    let result =
+        unsafe { libc::sched_setscheduler(pid.as_u32() as libc::pid_t, policy.number(), &param) };
+
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// The logical cores the process is allowed to run on
+pub fn affinity(pid: Pid) -> io::Result<Vec<usize>> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+
+    let result = unsafe {
+        libc::sched_getaffinity(
+            pid.as_u32() as libc::pid_t,
+            std::mem::size_of::<libc::cpu_set_t>(),
+            &mut set,
+        )
+    };
+
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok((0..libc::CPU_SETSIZE as usize)
+        .filter(|core| unsafe { libc::CPU_ISSET(*core, &set) })
+        .collect())
+}
+
+pub fn set_affinity(pid: Pid, cores: &[usize]) -> io::Result<()> {
+    if cores.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "at least one core has to be allowed",
+        ));
+    }
+
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+
+    for core in cores {
+        unsafe { libc::CPU_SET(*core, &mut set) };
+    }
+
+    let result = unsafe {
+        libc::sched_setaffinity(
+            pid.as_u32() as libc::pid_t,
+            std::mem::size_of::<libc::cpu_set_t>(),
+            &set,
+        )
+    };
+
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Formats cores as ranges, e.g. 0-3,6
+pub fn format_cores(cores: &[usize]) -> String {
+    let mut ranges: Vec<String> = Vec::new();
+    let mut index = 0;
+
+    while index < cores.len() {
+        let start = cores[index];
+        let mut end = start;
+
+        while index + 1 < cores.len() && cores[index + 1] == end + 1 {
+            index += 1;
+            end = cores[index];
+        }
+
+        ranges.push(if start == end {
+            start.to_string()
+        } else {
+            format!("{}-{}", start, end)
+        });
+
+        index += 1;
+    }
+
+    ranges.join(",")
+}
+
+/// Explains an error from changing a process in terms the user can act on
+pub fn describe_error(error: &io::Error) -> String {
+    match error.raw_os_error() {
+        Some(libc::EPERM) | Some(libc::EACCES) => String::from(
+            "permission denied, the process may belong to another user or the change may require root \
+             (lowering niceness needs CAP_SYS_NICE)",
+        ),
+        Some(libc::ESRCH) => String::from("the process no longer exists"),
+        _ => error.to_string(),
+    }
+}
+
+/// How an action applied to several processes went
+#[derive(Debug, Default)]
+pub struct BatchResult {
+    pub succeeded: usize,
+    pub failed: Vec<(Pid, io::Error)>,
+}
+
+impl BatchResult {
+    /// A one line summary for the user, as an error if anything failed
+    pub fn summary(&self, action: &str) -> Result<String, String> {
+        if self.succeeded + self.failed.len() == 1 {
+            return match self.failed.first() {
+                Some((_, error)) => Err(format!("{}: failed, {}", action, describe_error(error))),
+                None => Ok(format!("{}: succeeded", action)),
+            };
+        }
+
+        let Some((pid, error)) = self.failed.first() else {
+            return Ok(format!(
+                "{}: succeeded for {} {}",
+                action,
+                self.succeeded,
+                processes_noun(self.succeeded)
+            ));
+        };
+
+        Err(format!(
+            "{}: succeeded for {}, failed for {} {} (PID {}: {})",
+            action,
+            self.succeeded,
+            self.failed.len(),
+            processes_noun(self.failed.len()),
+            pid,
+            describe_error(error)
+        ))
+    }
+}
+
+fn processes_noun(count: usize) -> &'static str {
+    if count == 1 {
+        "process"
+    } else {
+        "processes"
+    }
+}
+
+/// When the process started, in seconds since the epoch as sysinfo reports it
+pub fn start_time(pid: Pid) -> io::Result<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid))?;
+
+    // The name is in parentheses and may contain spaces, so fields are counted from after it. starttime is the 22nd
+    let ticks: u64 = stat
+        .rsplit_once(')')
+        .and_then(|(_, fields)| fields.split_whitespace().nth(19))
+        .and_then(|ticks| ticks.parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed /proc stat"))?;
+
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+
+    if ticks_per_second <= 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(System::boot_time() + ticks / ticks_per_second as u64)
+}
+
+/// Runs the action for every process, carrying on past failures. Processes are given by PID and start time, and a
+/// PID that has since been reused by another process counts as the process no longer existing
+pub fn apply_to_all(
+    processes: &[(Pid, u64)],
+    mut action: impl FnMut(Pid) -> io::Result<()>,
+) -> BatchResult {
+    let mut result = BatchResult::default();
+
+    for (pid, expected_start_time) in processes {
+        let outcome = match start_time(*pid) {
+            Ok(start_time) if start_time == *expected_start_time => action(*pid),
+            _ => Err(io::Error::from_raw_os_error(libc::ESRCH)),
+        };
+
+        match outcome {
+            Ok(()) => result.succeeded += 1,
+            Err(error) => result.failed.push((*pid, error)),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use sysinfo::{Pid, System};
+
+    use super::{apply_to_all, start_time};
+
+    #[test]
+    fn start_time_matches_sysinfo() {
+        let pid = Pid::from_u32(std::process::id());
+
+        let mut system = System::new();
+        system.refresh_process(pid);
+
+        assert_eq!(
+            start_time(pid).ok(),
+            system.process(pid).map(|process| process.start_time())
+        );
+    }
+
+    #[test]
+    fn reused_pids_are_not_acted_on() {
+        let pid = Pid::from_u32(std::process::id());
+        let current = start_time(pid).expect("own start time not read");
+
+        let mut acted_on = Vec::new();
+        let result = apply_to_all(&[(pid, current), (pid, current + 1)], |pid| {
+            acted_on.push(pid);
+            Ok(())
+        });
+
+        assert_eq!(acted_on, vec![pid]);
+        assert_eq!(result.succeeded, 1);
+        assert_eq!(result.failed[0].1.raw_os_error(), Some(libc::ESRCH));
+    }
+}
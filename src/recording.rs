@@ -0,0 +1,136 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{cpu_info::CpuInfo, snapshot::Snapshot};
+
+/// Bumped when snapshots change in a way older recordings can't be read as
+pub const RECORDING_VERSION: u32 = 1;
+
+pub const RECORDING_EXTENSION: &str = "ctrec";
+
+/// The first line of a recording, with what isn't part of every snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub version: u32,
+    pub logical_core_count: u32,
+    pub physical_core_count: u32,
+    pub cpu_brand: String,
+    /// Missing from recordings made before it was recorded
+    #[serde(default)]
+    pub cpu_info: CpuInfo,
+}
+
+/// Writes snapshots to a recording as they're collected.
+/// A recording is gzipped JSON lines: the header, then one snapshot per line
+pub struct Recorder {
+    writer: GzEncoder<BufWriter<File>>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, header: &RecordingHeader) -> Result<Self, String> {
+        let file = File::create(path).map_err(|error| error.to_string())?;
+
+        let mut recorder = Self {
+            writer: GzEncoder::new(BufWriter::new(file), Compression::default()),
+        };
+
+        recorder.write_line(header)?;
+
+        Ok(recorder)
+    }
+
+    pub fn record(&mut self, snapshot: &Snapshot) -> Result<(), String> {
+        self.write_line(snapshot)
+    }
+
+    /// Writes the end of the gzip stream. A recording that isn't finished still opens up to its last full line
+    pub fn finish(self) -> Result<(), String> {
+        self.writer
+            .finish()
+            .and_then(|mut writer| writer.flush())
+            .map_err(|error| error.to_string())
+    }
+
+    fn write_line(&mut self, value: &impl Serialize) -> Result<(), String> {
+        serde_json::to_writer(&mut self.writer, value).map_err(|error| error.to_string())?;
+        self.writer
+            .write_all(b"\n")
+            .map_err(|error| error.to_string())
+    }
+}
+
+/// A recording read back to be replayed
+#[derive(Debug)]
+pub struct Recording {
+    pub header: RecordingHeader,
+    /// Oldest first
+    pub snapshots: Vec<Arc<Snapshot>>,
+}
+
+impl Recording {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|error| error.to_string())?;
+        let mut lines = BufReader::new(GzDecoder::new(file)).lines();
+
+        let header: RecordingHeader = match lines.next() {
+            Some(Ok(line)) => {
+                serde_json::from_str(&line).map_err(|_| String::from("Not a cTasks recording"))?
+            }
+            Some(Err(error)) => return Err(error.to_string()),
+            None => return Err(String::from("The recording is empty")),
+        };
+
+        if header.version != RECORDING_VERSION {
+            return Err(format!(
+                "Recording version {} isn't supported",
+                header.version
+            ));
+        }
+
+        let mut snapshots = Vec::new();
+
+        // A recording that was cut off, like when the app crashed, ends in a partial line
+        for line in lines.map_while(Result::ok) {
+            let Ok(snapshot) = serde_json::from_str::<Snapshot>(&line) else {
+                break;
+            };
+
+            snapshots.push(Arc::new(snapshot));
+        }
+
+        if snapshots.is_empty() {
+            return Err(String::from("The recording has no snapshots"));
+        }
+
+        Ok(Self { header, snapshots })
+    }
+}
+
+/// Asks where to save a new recording. None if cancelled
+pub async fn pick_recording_path() -> Option<PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .set_title("Record snapshots")
+        .set_file_name(format!("c-tasks-recording.{}", RECORDING_EXTENSION))
+        .add_filter("cTasks recording", &[RECORDING_EXTENSION])
+        .save_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
+
+/// Asks for a recording and reads it. None if cancelled
+pub async fn pick_recording() -> Option<Result<Arc<Recording>, String>> {
+    let file = rfd::AsyncFileDialog::new()
+        .set_title("Open recording")
+        .add_filter("cTasks recording", &[RECORDING_EXTENSION])
+        .pick_file()
+        .await?;
+
+    Some(Recording::open(file.path()).map(Arc::new))
+}
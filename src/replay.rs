@@ -0,0 +1,144 @@
+use std::{
+    sync::Arc,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use iced::{
+    theme,
+    widget::{button, container, row, slider, text},
+    Alignment, Element, Length,
+};
+use iced_aw::BootstrapIcon;
+
+use crate::{
+    constants::{custom_theme, padding},
+    general_widgets::icons::bootstrap_icon,
+    recording::Recording,
+    styles::container::resource_details_header,
+    types::snapshot::Snapshot,
+    utils::{format_duration, format_unix_time},
+};
+
+/// Replays faster than this look the same, and would only keep the UI busy
+const MIN_PLAYBACK_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+pub enum ReplayMessage {
+    TogglePlaying,
+    Seek(u32),
+    /// Time to show the next snapshot while playing
+    Tick,
+    Close,
+}
+
+/// A recording being shown in place of live data, at a position on its timeline
+#[derive(Debug)]
+pub struct Replay {
+    pub recording: Arc<Recording>,
+    /// Index of the snapshot being shown
+    pub position: usize,
+    pub playing: bool,
+}
+
+impl Replay {
+    pub fn new(recording: Arc<Recording>) -> Self {
+        Self {
+            recording,
+            position: 0,
+            playing: false,
+        }
+    }
+
+    pub fn snapshot(&self) -> &Arc<Snapshot> {
+        &self.recording.snapshots[self.position]
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.position + 1 >= self.recording.snapshots.len()
+    }
+
+    /// The first snapshot still in the history of the current position, so seeking can rebuild it
+    pub fn history_start(&self, history_retention: Duration) -> usize {
+        let timestamp = self.snapshot().timestamp;
+
+        self.recording.snapshots[..self.position]
+            .iter()
+            .position(|snapshot| {
+                timestamp
+                    .duration_since(snapshot.timestamp)
+                    .unwrap_or_default()
+                    <= history_retention
+            })
+            .unwrap_or(self.position)
+    }
+
+    /// How long to wait before showing the next snapshot, as long as it took to collect. None if paused
+    pub fn tick_interval(&self) -> Option<Duration> {
+        if !self.playing || self.is_at_end() {
+            return None;
+        }
+
+        let next = &self.recording.snapshots[self.position + 1];
+
+        Some(next.refresh_interval.max(MIN_PLAYBACK_INTERVAL))
+    }
+
+    pub fn view(&self) -> Element<'_, ReplayMessage> {
+        let first = &self.recording.snapshots[0];
+        let last = &self.recording.snapshots[self.recording.snapshots.len() - 1];
+        let current = self.snapshot();
+
+        let since_start = |snapshot: &Snapshot| {
+            snapshot
+                .timestamp
+                .duration_since(first.timestamp)
+                .unwrap_or_default()
+                .as_secs()
+        };
+
+        let play_icon = if self.playing {
+            BootstrapIcon::PauseFill
+        } else {
+            BootstrapIcon::PlayFill
+        };
+
+        let timeline = slider(
+            0..=(self.recording.snapshots.len() - 1) as u32,
+            self.position as u32,
+            ReplayMessage::Seek,
+        )
+        .width(Length::Fill);
+
+        let time = format!(
+            "{} / {}",
+            format_duration(since_start(current)),
+            format_duration(since_start(last))
+        );
+
+        let recorded_at = current
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .map(|since_epoch| format_unix_time(since_epoch.as_secs()))
+            .unwrap_or_default();
+
+        container(
+            row![
+                button(bootstrap_icon(play_icon))
+                    .on_press(ReplayMessage::TogglePlaying)
+                    .style(theme::Button::Text),
+                timeline,
+                text(time),
+                text(recorded_at).style(theme::Text::Color(custom_theme::GREY_TEXT)),
+                button(text(String::from("Back to live")))
+                    .on_press(ReplayMessage::Close)
+                    .style(theme::Button::Text),
+            ]
+            .spacing(padding::MAIN)
+            .align_items(Alignment::Center),
+        )
+        .style(resource_details_header())
+        .width(Length::Fill)
+        .padding(padding::MAIN)
+        .into()
+    }
+}
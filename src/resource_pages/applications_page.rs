@@ -1,34 +1,286 @@
-use iced::{widget::{column, container, text}, Command, Element};
+use iced::{
+    alignment, theme,
+    widget::{
+        button, column, container, horizontal_space, image, row, scrollable, text, Row, Space,
+    },
+    Alignment, Command, Element, Length,
+};
+use iced_aw::{grid_row, BootstrapIcon, Grid, GridRow};
 
-use crate::{preferences::{self, Preferences}, types::resource_data::{ApplicationData, ApplicationsData}};
+use crate::{
+    constants::{custom_theme, padding},
+    general_widgets::icons::bootstrap_icon,
+    preferences::Preferences,
+    process_control::{apply_to_all, send_signal, ProcessSignal},
+    styles::{self, container::resource_details_header},
+    types::resource_data::{ApplicationData, ApplicationsData},
+    utils::{format_bytes, format_rate},
+};
 
-use super::chart::{ResourceChart, ResourceChartMessage};
+use super::chart::ResourceChartMessage;
+
+/// Width and height of application icons
+const ICON_SIZE: f32 = 24.;
 
 #[derive(Debug, Clone)]
 pub enum ApplicationsPageMessage {
     ResourceChartMessage(ResourceChartMessage),
+    /// Show the actions for the application, by desktop entry id, or hide them if they are already shown
+    ToggleActions(String),
+    SendSignal(ProcessSignal),
+    ConfirmSignal,
+    CancelSignal,
 }
 
 #[derive(Debug)]
 pub struct ApplicationsPage {
-    
+    /// The desktop entry id of the application whose actions are shown
+    actions_for: Option<String>,
+    /// A destructive signal waiting on the user to confirm it
+    pending_signal: Option<ProcessSignal>,
+    /// The outcome of the last signal sent, as a message or an error
+    signal_result: Option<Result<String, String>>,
 }
 
 impl ApplicationsPage {
     pub fn new(preferences: &Preferences) -> Self {
         Self {
-            
+            actions_for: None,
+            pending_signal: None,
+            signal_result: None,
         }
     }
 
-    pub fn update(&mut self, message: ApplicationsPageMessage) -> Command<ApplicationsPageMessage> {
+    /// Sends the signal to every process of the application
+    fn send_signal(&mut self, signal: ProcessSignal, data: &ApplicationsData) {
+        self.pending_signal = None;
+
+        let Some(application) = self.application(data) else {
+            self.signal_result = Some(Err(String::from("The application is no longer running")));
+            return;
+        };
+
+        let result = apply_to_all(&application.processes, |pid| send_signal(pid, signal));
+
+        self.signal_result =
+            Some(result.summary(&format!("Send {} to {}", signal.name(), application.name)));
+    }
+
+    fn application<'a>(&self, data: &'a ApplicationsData) -> Option<&'a ApplicationData> {
+        let id = self.actions_for.as_ref()?;
+
+        data.in_depth
+            .applications
+            .iter()
+            .find(|application| application.id == *id)
+    }
+
+    pub fn update(
+        &mut self,
+        message: ApplicationsPageMessage,
+        data: &ApplicationsData,
+    ) -> Command<ApplicationsPageMessage> {
         match message {
-            _ => Command::none(),
+            ApplicationsPageMessage::ToggleActions(id) => {
+                self.pending_signal = None;
+                self.signal_result = None;
+
+                if self.actions_for.as_ref() == Some(&id) {
+                    self.actions_for = None;
+                } else {
+                    self.actions_for = Some(id);
+                }
+
+                Command::none()
+            }
+            ApplicationsPageMessage::SendSignal(signal) => {
+                if signal.is_destructive() {
+                    self.pending_signal = Some(signal);
+                    return Command::none();
+                }
+
+                self.send_signal(signal, data);
+
+                Command::none()
+            }
+            ApplicationsPageMessage::ConfirmSignal => {
+                let Some(signal) = self.pending_signal else {
+                    return Command::none();
+                };
+
+                self.send_signal(signal, data);
+
+                Command::none()
+            }
+            ApplicationsPageMessage::CancelSignal => {
+                self.pending_signal = None;
+
+                Command::none()
+            }
+            ApplicationsPageMessage::ResourceChartMessage(_) => Command::none(),
         }
     }
 
-    pub fn view(&self, preferences: &Preferences, data: &ApplicationsData) -> Element<ApplicationsPageMessage> {
-        let content = column![text(String::from("apps"))];
+    fn actions_view<'a>(
+        &'a self,
+        data: &'a ApplicationsData,
+    ) -> Element<'a, ApplicationsPageMessage> {
+        if self.actions_for.is_none() {
+            return column![].into();
+        }
+
+        let controls: Element<ApplicationsPageMessage> =
+            match (self.application(data), self.pending_signal) {
+                (None, _) => text("The application is no longer running").into(),
+                (Some(application), Some(signal)) => row![
+                    text(format!(
+                        "Send {} to all {} processes of {}?",
+                        signal.name(),
+                        application.processes.len(),
+                        application.name
+                    )),
+                    horizontal_space(),
+                    button(text("Confirm"))
+                        .on_press(ApplicationsPageMessage::ConfirmSignal)
+                        .style(theme::Button::Destructive),
+                    button(text("Cancel"))
+                        .on_press(ApplicationsPageMessage::CancelSignal)
+                        .style(theme::Button::Text),
+                ]
+                .spacing(padding::PORTION)
+                .align_items(Alignment::Center)
+                .into(),
+                (Some(application), None) => Row::with_children({
+                    let mut children: Vec<Element<ApplicationsPageMessage>> = vec![
+                        text(format!(
+                            "{} ({} processes)",
+                            application.name,
+                            application.processes.len()
+                        ))
+                        .into(),
+                        horizontal_space().into(),
+                    ];
+
+                    for signal in ProcessSignal::NAMED {
+                        children.push(
+                            button(text(signal.name()))
+                                .on_press(ApplicationsPageMessage::SendSignal(signal))
+                                .style(iced::theme::Button::Custom(Box::new(
+                                    styles::button::Primary {},
+                                )))
+                                .into(),
+                        );
+                    }
+
+                    children
+                })
+                .spacing(padding::PORTION)
+                .align_items(Alignment::Center)
+                .into(),
+            };
+
+        let result = match &self.signal_result {
+            Some(Ok(message)) => {
+                text(message.clone()).style(theme::Text::Color(custom_theme::GREY_TEXT))
+            }
+            Some(Err(error)) => text(error.clone()).style(theme::Text::Color(custom_theme::DANGER)),
+            None => text(""),
+        };
+
+        container(column![controls, result].spacing(padding::PORTION))
+            .padding(padding::MAIN)
+            .width(Length::Fill)
+            .into()
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        preferences: &Preferences,
+        data: &'a ApplicationsData,
+    ) -> Element<'a, ApplicationsPageMessage> {
+        let header = container(
+            row![horizontal_space(), text("Applications"), horizontal_space()]
+                .align_items(Alignment::Center),
+        )
+        .center_x()
+        .style(resource_details_header())
+        .width(Length::Fill)
+        .padding(padding::MAIN);
+
+        let header_strings = [
+            "Name",
+            "Type",
+            "Processes",
+            "CPU",
+            "Memory",
+            "Disk Read",
+            "Disk Written",
+            "Actions",
+        ];
+
+        let headers = GridRow::with_elements(
+            header_strings
+                .iter()
+                .map(|content| container(text(*content)).padding(padding::PORTION))
+                .collect(),
+        );
+
+        let main = container(
+            Grid::with_rows({
+                let mut rows = vec![headers];
+
+                for application in &data.in_depth.applications {
+                    let icon: Element<ApplicationsPageMessage> = match &application.icon {
+                        Some(icon) => image(image::Handle::from_path(icon))
+                            .width(ICON_SIZE)
+                            .height(ICON_SIZE)
+                            .into(),
+                        None => container(bootstrap_icon(BootstrapIcon::WindowStack))
+                            .center_x()
+                            .width(ICON_SIZE)
+                            .into(),
+                    };
+
+                    rows.push(grid_row!(
+                        row![
+                            icon,
+                            Space::with_width(padding::PORTION),
+                            text(&application.name)
+                        ]
+                        .align_items(Alignment::Center),
+                        text(application.kind.name()),
+                        text(application.processes.len()),
+                        text(format!("{:.2}%", application.cpu_usage)),
+                        text(format_bytes(preferences, application.memory_usage as f32)),
+                        text(format_rate(preferences, application.disk_read_rate)),
+                        text(format_rate(preferences, application.disk_written_rate)),
+                        button(bootstrap_icon(BootstrapIcon::ThreeDots))
+                            .on_press(ApplicationsPageMessage::ToggleActions(
+                                application.id.clone()
+                            ))
+                            .style(iced::theme::Button::Custom(Box::new(
+                                styles::button::Primary {},
+                            ))),
+                    ));
+                }
+
+                rows
+            })
+            .column_width(Length::Shrink)
+            .row_spacing(10)
+            .column_spacing(padding::MAIN),
+        )
+        .padding(padding::MAIN)
+        .width(Length::Fill)
+        .align_x(alignment::Horizontal::Center);
+
+        let content = column![
+            header,
+            self.actions_view(data),
+            scrollable(main).style(iced::theme::Scrollable::Custom(Box::new(
+                styles::scrollable::Background1 {},
+            )))
+        ];
 
         let container = container(content);
         container.into()
@@ -19,9 +19,11 @@ use crate::{
     },
     preferences::Preferences,
     styles::{self, container::resource_details_header},
-    types::resource_data::{BatteryData, CpuData},
+    types::{
+        resource_data::{BatteryData, CpuData},
+        time_series::TimeSeries,
+    },
     utils::format_hz,
-    ResourceHistory,
 };
 
 use super::chart::{ResourceChart, ResourceChartMessage};
@@ -37,20 +39,20 @@ pub struct BatteryPage {
 }
 
 impl BatteryPage {
-    pub fn new(preferences: &Preferences) -> Self {
+    pub fn new(preferences: &Preferences, charge_history: &TimeSeries) -> Self {
         Self {
-            battery_charge_chart: ResourceChart::new(preferences),
+            battery_charge_chart: ResourceChart::new(preferences, charge_history),
         }
     }
 
     pub fn update(&mut self, message: BatteryPageMessage) -> Command<BatteryPageMessage> {
         match message {
-            _ => Command::none(),
-        }
-    }
+            BatteryPageMessage::ResourceChartMessage(message) => {
+                self.battery_charge_chart.update(message);
 
-    pub fn update_history(&mut self, resource_history: &ResourceHistory) {
-        self.battery_charge_chart.data_points = resource_history.battery_charge.clone();
+                Command::none()
+            }
+        }
     }
 
     pub fn view(
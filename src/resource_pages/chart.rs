@@ -1,28 +1,53 @@
-use std::collections::{HashMap, VecDeque};
+use std::time::SystemTime;
 
-use iced::{Element, Length};
+use iced::{
+    theme,
+    widget::{button, column, row, text},
+    Element, Length,
+};
 use plotters::{
-    series::AreaSeries,
+    chart::SeriesLabelPosition,
+    element::{Polygon, Rectangle},
+    series::{AreaSeries, LineSeries},
     style::{Color, FontTransform, HSLColor, IntoFont, RGBAColor, RGBColor, ShapeStyle},
 };
 use plotters_iced::{Chart, ChartBuilder, ChartWidget, DrawingBackend};
 
 use crate::{
-    constants::{custom_theme, padding, sizings::DEFAULT_CHART_HEIGHT, spacings},
+    constants::{
+        custom_theme, padding,
+        sizings::{DEFAULT_CHART_HEIGHT, SPARKLINE_HEIGHT, SPARKLINE_WIDTH},
+        spacings,
+    },
+    metrics_store::Rollups,
     preferences::Preferences,
+    types::time_series::{Sample, TimeSeries, Unit},
+    utils::{format_bytes, format_celsius, format_rate},
 };
 
 use super::resource_details::ResourceDetailsMessage;
 
 #[derive(Debug, Clone, Copy)]
-pub enum ResourceChartMessage {}
+pub enum ResourceChartMessage {
+    /// 0 is the live history, the rest are the long term tiers
+    SelectRange(usize),
+}
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct ResourceChart {
-    // 31 ticks of data
-    pub data_points: VecDeque<(i32, i32)>,
+    /// Shared with the resource that pushes to it, so the chart always draws the latest samples
+    pub series: TimeSeries,
+    /// For formatting the y axis labels in the series' unit
+    preferences: Preferences,
     chart_y_axis_labels: bool,
     chart_y_axis_major_grid_lines: usize,
+    /// If set, the y axis grows to fit the data but never shrinks below this. Percents are otherwise fixed to 0..100
+    auto_scale_min: Option<f64>,
+    /// Values to draw a line at, like the temperature a CPU throttles at. The y axis always fits them
+    thresholds: Vec<f64>,
+    /// Long term history from the store, from finest to coarsest
+    long_term: Vec<Rollups>,
+    range: usize,
 }
 
 impl Chart<ResourceChartMessage> for ResourceChart {
@@ -39,10 +64,43 @@ impl Chart<ResourceChartMessage> for ResourceChart {
             build.y_label_area_size(0);
         }
 
+        // Long term history is drawn as the average with a line for the max, and relative to now since it may have
+        // been recorded before the app was last closed
+        let (series, max_series, now) = match self.long_term_range() {
+            Some(rollups) => (&rollups.avg, Some(&rollups.max), Some(SystemTime::now())),
+            None => (&self.series, None, None),
+        };
+
+        let y_max = match (self.auto_scale_min, series.unit()) {
+            (None, Unit::Percent) => 100.,
+            (auto_scale_min, _) => max_series
+                .unwrap_or(series)
+                .max_value()
+                .max(auto_scale_min.unwrap_or(1.)),
+        };
+
+        // Leave room above the highest threshold so its line isn't drawn on the edge
+        let y_max = self
+            .thresholds
+            .iter()
+            .map(|threshold| threshold * 1.1)
+            .fold(y_max, f64::max);
+
+        let retention = series.retention().as_secs_f64();
+
         let mut chart = build
-            .build_cartesian_2d(0..30, 0..100)
+            .build_cartesian_2d(-retention..0., 0. ..y_max)
             .expect("failed to build chart");
 
+        let unit = series.unit();
+        let format_label = |value: &f64| match unit {
+            Unit::Percent => format!("{:.0}%", value),
+            Unit::Bytes => format_bytes(&self.preferences, *value as f32),
+            Unit::BytesPerSecond => format_rate(&self.preferences, *value),
+            Unit::Celsius => format_celsius(*value as f32),
+            Unit::Tasks => format!("{:.1}", value),
+        };
+
         let mut mesh = chart.configure_mesh();
 
         mesh.bold_line_style(plotters::style::colors::full_palette::GREY_600)
@@ -53,7 +111,8 @@ impl Chart<ResourceChartMessage> for ResourceChart {
             .y_max_light_lines(2)
             .y_labels(self.chart_y_axis_major_grid_lines)
             .x_max_light_lines(30)
-            .x_labels(3);
+            .x_labels(3)
+            .y_label_formatter(&format_label);
 
         if self.chart_y_axis_labels == true {
             mesh.y_label_style(
@@ -69,12 +128,57 @@ impl Chart<ResourceChartMessage> for ResourceChart {
 
         mesh.draw().expect("failed to draw chart mesh");
 
+        let samples = series.samples();
+        let latest = now.or(samples.back().map(|sample| sample.timestamp));
+
+        if let Some(max_series) = max_series {
+            let max_samples = max_series.samples();
+
+            chart
+                .draw_series(LineSeries::new(
+                    max_samples.iter().map(|sample| {
+                        (
+                            sample.seconds_before(latest.unwrap_or(sample.timestamp)),
+                            sample.value,
+                        )
+                    }),
+                    ShapeStyle::from(RGBAColor(
+                        (custom_theme::PRIMARY.r * 255.) as u8,
+                        (custom_theme::PRIMARY.g * 255.) as u8,
+                        (custom_theme::PRIMARY.b * 255.) as u8,
+                        0.5,
+                    ))
+                    .stroke_width(1),
+                ))
+                .expect("failed to draw chart max");
+        }
+
+        for threshold in &self.thresholds {
+            chart
+                .draw_series(LineSeries::new(
+                    [(-retention, *threshold), (0., *threshold)],
+                    ShapeStyle::from(RGBAColor(
+                        (custom_theme::DANGER.r * 255.) as u8,
+                        (custom_theme::DANGER.g * 255.) as u8,
+                        (custom_theme::DANGER.b * 255.) as u8,
+                        0.6,
+                    ))
+                    .stroke_width(1),
+                ))
+                .expect("failed to draw chart threshold");
+        }
+
         chart
             .draw_series(
                 AreaSeries::new(
-                    self.data_points.iter().map(|x| (x.0, x.1)),
+                    samples.iter().map(|sample| {
+                        (
+                            sample.seconds_before(latest.unwrap_or(sample.timestamp)),
+                            sample.value,
+                        )
+                    }),
                     // self.data.iter(),
-                    0,
+                    0.,
                     RGBAColor {
                         0: (custom_theme::PRIMARY.r * 255.) as u8,
                         1: (custom_theme::PRIMARY.g * 255.) as u8,
@@ -96,8 +200,7 @@ impl Chart<ResourceChartMessage> for ResourceChart {
 }
 
 impl ResourceChart {
-    pub fn new(preferences: &Preferences) -> Self {
-        let mut data_points = VecDeque::new();
+    pub fn new(preferences: &Preferences, series: &TimeSeries) -> Self {
         // data_points.push_back((0, 0));
         // data_points.push_back((1, 5));
         // data_points.push_back((2, 25));
@@ -131,18 +234,359 @@ impl ResourceChart {
         // data_points.push_back((30, 16));
 
         Self {
-            data_points,
+            series: series.clone(),
+            preferences: *preferences,
             chart_y_axis_labels: preferences.chart_y_axis_labels,
             chart_y_axis_major_grid_lines: preferences.chart_y_axis_major_grid_lines,
+            auto_scale_min: None,
+            thresholds: Vec::new(),
+            long_term: Vec::new(),
+            range: 0,
+        }
+    }
+
+    /// Lets the range be picked from the live history and the long term tiers
+    pub fn set_long_term(&mut self, long_term: Vec<Rollups>) {
+        self.long_term = long_term;
+        self.range = 0;
+    }
+
+    pub fn update(&mut self, message: ResourceChartMessage) {
+        match message {
+            ResourceChartMessage::SelectRange(range) => {
+                self.range = range.min(self.long_term.len());
+            }
         }
     }
 
+    /// The rollups of the picked tier, if one is
+    fn long_term_range(&self) -> Option<&Rollups> {
+        self.long_term.get(self.range.checked_sub(1)?)
+    }
+
+    /// Lines to draw across the chart, replacing the previous ones
+    pub fn set_thresholds(&mut self, thresholds: Vec<f64>) {
+        self.thresholds = thresholds;
+    }
+
+    /// Scale the y axis to fit the data, for values that aren't percentages
+    pub fn with_auto_scale(mut self, auto_scale_min: f64) -> Self {
+        self.auto_scale_min = Some(auto_scale_min);
+        self
+    }
+
     pub fn set_data() {}
 
     pub fn view(&self, height: Option<Length>) -> Element<ResourceChartMessage> {
-        ChartWidget::new(self)
+        let chart = ChartWidget::new(self)
             // .width(Length::Fixed(600.))
+            .height(height.unwrap_or(Length::Fixed(DEFAULT_CHART_HEIGHT)));
+
+        if self.long_term.is_empty() {
+            return chart.into();
+        }
+
+        let labels =
+            std::iter::once("Live").chain(self.long_term.iter().map(|rollups| rollups.tier.name));
+
+        let ranges = row(labels
+            .enumerate()
+            .map(|(range, label)| {
+                button(text(label))
+                    .on_press(ResourceChartMessage::SelectRange(range))
+                    .style(if range == self.range {
+                        theme::Button::Primary
+                    } else {
+                        theme::Button::Text
+                    })
+                    .into()
+            })
+            .collect::<Vec<_>>())
+        .spacing(padding::PORTION);
+
+        column![ranges, chart].into()
+    }
+}
+
+/// A minimal chart without a mesh, small enough to show inline in a table row
+pub struct Sparkline<'a> {
+    series: &'a TimeSeries,
+    y_max: f64,
+}
+
+impl<'a> Sparkline<'a> {
+    /// The y axis fits the data, but never shrinks below min_y_max
+    pub fn new(series: &'a TimeSeries, min_y_max: f64) -> Self {
+        Self {
+            series,
+            y_max: series.max_value().max(min_y_max),
+        }
+    }
+
+    pub fn view<Message: 'a>(self) -> Element<'a, Message> {
+        ChartWidget::new(self)
+            .width(Length::Fixed(SPARKLINE_WIDTH))
+            .height(Length::Fixed(SPARKLINE_HEIGHT))
+            .into()
+    }
+}
+
+impl<'a, Message> Chart<Message> for Sparkline<'a> {
+    type State = ();
+
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, mut builder: ChartBuilder<DB>) {
+        let mut chart = builder
+            .margin(1)
+            .x_label_area_size(0)
+            .y_label_area_size(0)
+            .build_cartesian_2d(-self.series.retention().as_secs_f64()..0., 0. ..self.y_max)
+            .expect("failed to build sparkline");
+
+        let samples = self.series.samples();
+        let latest = samples.back().map(|sample| sample.timestamp);
+
+        chart
+            .draw_series(
+                AreaSeries::new(
+                    samples.iter().map(|sample| {
+                        (
+                            sample.seconds_before(latest.unwrap_or(sample.timestamp)),
+                            sample.value,
+                        )
+                    }),
+                    0.,
+                    RGBAColor(
+                        (custom_theme::PRIMARY.r * 255.) as u8,
+                        (custom_theme::PRIMARY.g * 255.) as u8,
+                        (custom_theme::PRIMARY.b * 255.) as u8,
+                        0.2,
+                    ),
+                )
+                .border_style(
+                    ShapeStyle::from(RGBColor(
+                        (custom_theme::PRIMARY.r * 255.) as u8,
+                        (custom_theme::PRIMARY.g * 255.) as u8,
+                        (custom_theme::PRIMARY.b * 255.) as u8,
+                    ))
+                    .stroke_width(1),
+                ),
+            )
+            .expect("failed to draw sparkline data");
+    }
+}
+
+/// Series of the same unit drawn on top of each other, like the time spent in each CPU state. Only draws the live
+/// history
+#[derive(Debug)]
+pub struct StackedChart {
+    /// From the bottom up, with the name shown in the legend. Pushed to together, so their samples line up
+    layers: Vec<(String, TimeSeries)>,
+    chart_y_axis_labels: bool,
+    chart_y_axis_major_grid_lines: usize,
+}
+
+impl StackedChart {
+    pub fn new(preferences: &Preferences, layers: Vec<(String, TimeSeries)>) -> Self {
+        Self {
+            layers,
+            chart_y_axis_labels: preferences.chart_y_axis_labels,
+            chart_y_axis_major_grid_lines: preferences.chart_y_axis_major_grid_lines,
+        }
+    }
+
+    pub fn view<'a, Message: 'a>(&'a self, height: Option<Length>) -> Element<'a, Message> {
+        ChartWidget::new(self)
             .height(height.unwrap_or(Length::Fixed(DEFAULT_CHART_HEIGHT)))
             .into()
     }
+
+    /// The x of each sample and the top of every layer at it, from the bottom layer up
+    fn stacked(&self) -> (Vec<f64>, Vec<Vec<f64>>) {
+        let samples: Vec<Vec<Sample>> = self
+            .layers
+            .iter()
+            .map(|(_, series)| series.samples().iter().copied().collect())
+            .collect();
+
+        // Layers pushed to at the same time only differ in length right as one is pruned
+        let count = samples.iter().map(Vec::len).min().unwrap_or(0);
+        let newest = |layer: &[Sample]| layer[layer.len() - count..].to_vec();
+
+        let Some(bottom) = samples.first().map(|layer| newest(layer)) else {
+            return (Vec::new(), Vec::new());
+        };
+
+        let latest = bottom.last().map(|sample| sample.timestamp);
+        let xs = bottom
+            .iter()
+            .map(|sample| sample.seconds_before(latest.unwrap_or(sample.timestamp)))
+            .collect();
+
+        let mut totals = vec![0.; count];
+        let tops = samples
+            .iter()
+            .map(|layer| {
+                for (total, sample) in totals.iter_mut().zip(newest(layer)) {
+                    *total += sample.value;
+                }
+
+                totals.clone()
+            })
+            .collect();
+
+        (xs, tops)
+    }
+}
+
+impl<Message> Chart<Message> for StackedChart {
+    type State = ();
+
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, mut builder: ChartBuilder<DB>) {
+        let build = builder.x_label_area_size(0).margin(spacings::MEDIUM as u32);
+
+        if self.chart_y_axis_labels {
+            build.y_label_area_size(28);
+        } else {
+            build.y_label_area_size(0);
+        }
+
+        let Some((_, first)) = self.layers.first() else {
+            return;
+        };
+
+        let (xs, tops) = self.stacked();
+
+        let y_max = match first.unit() {
+            Unit::Percent => 100.,
+            _ => tops
+                .last()
+                .into_iter()
+                .flatten()
+                .copied()
+                .fold(1., f64::max),
+        };
+
+        let retention = first.retention().as_secs_f64();
+
+        let mut chart = build
+            .build_cartesian_2d(-retention..0., 0. ..y_max)
+            .expect("failed to build stacked chart");
+
+        let mut mesh = chart.configure_mesh();
+
+        mesh.bold_line_style(plotters::style::colors::full_palette::GREY_600)
+            .light_line_style(plotters::style::colors::full_palette::GREY_800)
+            .axis_style(
+                ShapeStyle::from(plotters::style::colors::full_palette::GREY_500).stroke_width(1),
+            )
+            .y_max_light_lines(2)
+            .y_labels(self.chart_y_axis_major_grid_lines)
+            .x_max_light_lines(30)
+            .x_labels(3)
+            .y_label_formatter(&|value| format!("{:.0}%", value));
+
+        if self.chart_y_axis_labels {
+            mesh.y_label_style(
+                ("sans-serif", 15)
+                    .into_font()
+                    .color(&plotters::style::colors::WHITE)
+                    .transform(FontTransform::Rotate90),
+            );
+        } else {
+            mesh.disable_y_axis();
+        }
+
+        mesh.draw().expect("failed to draw stacked chart mesh");
+
+        let zeros = vec![0.; xs.len()];
+
+        for (index, ((name, _), top)) in self.layers.iter().zip(&tops).enumerate() {
+            let bottom = index.checked_sub(1).map_or(&zeros, |below| &tops[below]);
+            let color = custom_theme::CHART_LAYERS[index % custom_theme::CHART_LAYERS.len()];
+            let color = RGBColor(
+                (color.r * 255.) as u8,
+                (color.g * 255.) as u8,
+                (color.b * 255.) as u8,
+            );
+
+            // The band between the top of the layer below and this one
+            let band: Vec<(f64, f64)> = xs
+                .iter()
+                .copied()
+                .zip(top.iter().copied())
+                .chain(xs.iter().copied().zip(bottom.iter().copied()).rev())
+                .collect();
+
+            chart
+                .draw_series(std::iter::once(Polygon::new(band, color.mix(0.6).filled())))
+                .expect("failed to draw stacked chart layer")
+                .label(name.as_str())
+                .legend(move |(x, y)| {
+                    Rectangle::new([(x, y - 5), (x + 10, y + 5)], color.filled())
+                });
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .background_style(RGBAColor(
+                (custom_theme::BACKGROUND_1.r * 255.) as u8,
+                (custom_theme::BACKGROUND_1.g * 255.) as u8,
+                (custom_theme::BACKGROUND_1.b * 255.) as u8,
+                0.8,
+            ))
+            .label_font(
+                ("sans-serif", 13)
+                    .into_font()
+                    .color(&plotters::style::colors::WHITE),
+            )
+            .draw()
+            .expect("failed to draw stacked chart legend");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::{
+        preferences::Preferences,
+        types::time_series::{TimeSeries, Unit},
+    };
+
+    use super::StackedChart;
+
+    #[test]
+    fn layers_stack_on_the_ones_below() {
+        let mut user = TimeSeries::new(Unit::Percent, Duration::from_secs(60));
+        let mut system = TimeSeries::new(Unit::Percent, Duration::from_secs(60));
+
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+
+        for (second, (user_percent, system_percent)) in
+            [(10., 5.), (20., 10.)].into_iter().enumerate()
+        {
+            let timestamp = start + Duration::from_secs(second as u64);
+            user.push(timestamp, user_percent);
+            system.push(timestamp, system_percent);
+        }
+
+        // A layer that started later only stacks where the others have samples
+        let mut steal = TimeSeries::new(Unit::Percent, Duration::from_secs(60));
+        steal.push(start + Duration::from_secs(1), 1.);
+
+        let chart = StackedChart::new(
+            &Preferences::default(),
+            vec![
+                (String::from("user"), user),
+                (String::from("system"), system),
+                (String::from("steal"), steal),
+            ],
+        );
+
+        let (xs, tops) = chart.stacked();
+
+        assert_eq!(xs, vec![0.]);
+        assert_eq!(tops, vec![vec![20.], vec![30.], vec![31.]]);
+    }
 }
@@ -1,13 +1,13 @@
 use iced::{
     alignment,
-    widget::{checkbox, column, container, row, scrollable, text},
+    widget::{checkbox, column, container, row, scrollable, text, Column},
     Command, Element, Length,
 };
 use iced_aw::{BootstrapIcon, Wrap};
 
 use crate::{
     constants::{
-        padding,
+        font_sizes, padding,
         sizings::{self, DEFAULT_CHART_HEIGHT},
     }, general_widgets::{
         icons::bootstrap_icon,
@@ -15,11 +15,12 @@ use crate::{
         seperators::seperator_background_1,
         split_table_double::split_table_double,
         split_table_single::split_table_single,
-    }, preferences::Preferences, styles::{self, container::resource_details_header}, types::resource_data::CpuData, utils::format_hz, ResourceHistory
+    }, preferences::Preferences, styles::{self, container::resource_details_header}, types::{cpu_info::CpuInfo, cpu_times::CpuState, resource_data::CpuData, time_series::TimeSeries}, utils::{format_bytes, format_celsius, format_hz}
 };
 
 use super::{
-    chart::{ResourceChart, ResourceChartMessage},
+    chart::{ResourceChart, ResourceChartMessage, StackedChart},
+    pressure_charts::PressureCharts,
 };
 
 #[derive(Debug, Clone)]
@@ -32,20 +33,42 @@ pub enum CpuPageMessage {
 pub struct CpuPage {
     pub cpu_chart: ResourceChart,
     pub logical_core_charts: Vec<ResourceChart>,
+    /// The time spent in each state but idle, stacked up to the usage
+    pub times_chart: StackedChart,
+    /// Of the hottest package, with its high and critical temperatures as thresholds
+    pub temperature_chart: ResourceChart,
+    /// The 1 minute load average, with a line at the logical core count where the CPU is saturated
+    pub load_chart: ResourceChart,
+    pub pressure_charts: PressureCharts,
 }
 
 impl CpuPage {
-    pub fn new(preferences: &Preferences, logical_core_count: u32) -> Self {
+    pub fn new(preferences: &Preferences, cpu_history: &TimeSeries, logical_cores_history: &[TimeSeries], times_history: &[(CpuState, TimeSeries)], temperature_history: &TimeSeries, load_history: &TimeSeries, pressure_charts: PressureCharts) -> Self {
 
         let mut logical_core_charts = Vec::new();
 
-        for _ in 0..logical_core_count {
-            logical_core_charts.push(ResourceChart::new(preferences));
+        for logical_core_history in logical_cores_history {
+            logical_core_charts.push(ResourceChart::new(preferences, logical_core_history));
         }
 
+        let logical_core_count = logical_cores_history.len() as f64;
+
+        let mut load_chart = ResourceChart::new(preferences, load_history).with_auto_scale(logical_core_count);
+        load_chart.set_thresholds(vec![logical_core_count]);
+
         Self {
-            cpu_chart: ResourceChart::new(preferences),
+            cpu_chart: ResourceChart::new(preferences, cpu_history),
             logical_core_charts,
+            times_chart: StackedChart::new(
+                preferences,
+                times_history
+                    .iter()
+                    .map(|(state, history)| (state.title().to_string(), history.clone()))
+                    .collect(),
+            ),
+            temperature_chart: ResourceChart::new(preferences, temperature_history).with_auto_scale(100.),
+            load_chart,
+            pressure_charts,
         }
     }
 
@@ -56,19 +79,23 @@ impl CpuPage {
 
                 Command::none()
             }
-            _ => Command::none(),
-        }
-    }
+            // The range is the same for every chart on the page
+            CpuPageMessage::ResourceChartMessage(message) => {
+                self.cpu_chart.update(message);
+                self.temperature_chart.update(message);
+                self.load_chart.update(message);
+                self.pressure_charts.update(message);
 
-    pub fn update_history(&mut self, resource_history: &ResourceHistory) {
-        self.cpu_chart.data_points = resource_history.cpu.clone();
+                for chart in &mut self.logical_core_charts {
+                    chart.update(message);
+                }
 
-        for (i, chart) in &mut self.logical_core_charts.iter_mut().enumerate() {
-            chart.data_points = resource_history.logical_cores[i].clone();
+                Command::none()
+            }
         }
     }
 
-    pub fn view(&self, preferences: &Preferences, data: &CpuData, physical_core_count: u32, logical_core_count: u32, brand: String) -> Element<CpuPageMessage> {
+    pub fn view(&self, preferences: &Preferences, data: &CpuData, physical_core_count: u32, logical_core_count: u32, brand: String, info: &CpuInfo) -> Element<'_, CpuPageMessage> {
 
         let header = container(row!["CPU"])
             .center_x()
@@ -87,45 +114,108 @@ impl CpuPage {
                                 .on_toggle(CpuPageMessage::ToggleLogicalCores)
                         ],
                     ),
-                    column![Wrap::with_elements({
+                    Column::with_children({
+                        let topology = &info.topology;
                         let mut children: Vec<Element<'_, CpuPageMessage>> = Vec::new();
 
-                        for (i, usage_percent) in data.logical_cores_usage_percents.iter().enumerate() {
-                            children.push(
-                                section_box_headless(column![
-                                    self.logical_core_charts[i]
+                        let logical_core = |i: usize| -> Option<Element<'_, CpuPageMessage>> {
+                            let usage_percent = data.logical_cores_usage_percents.get(i)?;
+
+                            Some(
+                                column![
+                                    self.logical_core_charts.get(i)?
                                         .view(Some(Length::Fixed(DEFAULT_CHART_HEIGHT / 2.)))
                                         .map(move |message| {
                                             CpuPageMessage::ResourceChartMessage(message)
                                         }),
                                     seperator_background_1(),
-                                    split_table_double(vec![(
-                                        (
-                                            text(String::from("Percent used")),
-                                            text(format!("{:.1}%", usage_percent)),
-                                        ),
-                                        (
-                                            text(String::from("Frequency")),
-                                            text(format_hz(
-                                                preferences,
-                                                data.logical_cores_frequencies[i] as f32
-                                            ))
-                                        ),
-                                    )]),
-                                ])
-                                .max_width(
-                                    sizings::MAX_MAIN_CONTENT_CHILDREN_WIDTH as f32 / 3.
-                                        - padding::MAIN as f32 * 3.,
+                                    split_table_double({
+                                        let mut rows = vec![(
+                                            (
+                                                text(String::from("Percent used")),
+                                                text(format!("{:.1}%", usage_percent)),
+                                            ),
+                                            (
+                                                text(String::from("Frequency")),
+                                                text(format_hz(
+                                                    preferences,
+                                                    data.logical_cores_frequencies[i] as f32
+                                                ))
+                                            ),
+                                        )];
+
+                                        if let Some(times) = data.times.logical_cores.get(i) {
+                                            let percent = |state: CpuState| text(format!("{:.1}%", times.percent(state)));
+
+                                            rows.push((
+                                                (text(String::from("System")), percent(CpuState::System)),
+                                                (text(String::from("I/O wait")), percent(CpuState::Iowait)),
+                                            ));
+                                        }
+
+                                        rows
+                                    }),
+                                ]
+                                .into(),
+                            )
+                        };
+
+                        for group in topology.groups(logical_core_count as usize) {
+                            // Only what tells the groups apart
+                            let mut title = Vec::new();
+
+                            if topology.package_count() > 1 {
+                                title.push(format!("Package {}", group.package));
+                            }
+
+                            if let (Some(node), true) = (group.node, topology.node_count() > 1) {
+                                title.push(format!("NUMA node {}", node));
+                            }
+
+                            if let Some(kind) = group.kind {
+                                title.push(kind.title().to_string());
+                            }
+
+                            if !title.is_empty() {
+                                children.push(text(title.join(" · ")).size(font_sizes::H2).into());
+                            }
+
+                            children.push(
+                                Wrap::with_elements(
+                                    group
+                                        .physical_cores
+                                        .iter()
+                                        .map(|physical_core| {
+                                            let logical_cores: Vec<String> = physical_core.logical_cores.iter().map(|i| i.to_string()).collect();
+
+                                            // Without a topology each logical core is shown as its own
+                                            let name = if topology.logical_cores.is_empty() {
+                                                format!("CPU {}", logical_cores.join(", "))
+                                            } else {
+                                                format!("Core {} (CPU {})", physical_core.core, logical_cores.join(", "))
+                                            };
+
+                                            let mut core = vec![text(name).into()];
+                                            core.extend(physical_core.logical_cores.iter().filter_map(|i| logical_core(*i)));
+
+                                            section_box_headless(Column::with_children(core).spacing(padding::PORTION).padding(padding::PORTION))
+                                                .max_width(
+                                                    sizings::MAX_MAIN_CONTENT_CHILDREN_WIDTH as f32 / 3.
+                                                        - padding::MAIN as f32 * 3.,
+                                                )
+                                                .into()
+                                        })
+                                        .collect(),
                                 )
-                                //.max_width((sizings::MAX_MAIN_CONTENT_CHILDREN_WIDTH as f32 - padding::MAIN as f32) / 2./* sizings::MAX_MAIN_CONTENT_CHILDREN_WIDTH as f32 / 2. - padding::MAIN as f32 */)
+                                .line_spacing(padding::MAIN as f32)
+                                .spacing(padding::MAIN as f32)
                                 .into(),
                             );
                         }
 
                         children
                     })
-                    .line_spacing(padding::MAIN as f32)
-                    .spacing(padding::MAIN as f32)],
+                    .spacing(padding::MAIN),
                 )
             } else {
                 section_box(
@@ -157,16 +247,119 @@ impl CpuPage {
             }
         };
 
+        let times = section_box(
+            (
+                bootstrap_icon(BootstrapIcon::Stack),
+                text(String::from("Time breakdown")),
+                row![],
+            ),
+            if data.times.total.total() > 0 {
+                let percent = |state: CpuState| text(format!("{:.1}%", data.times.total.percent(state)));
+                let state_pair = |left: CpuState, right: CpuState| {
+                    (
+                        (text(left.title().to_string()), percent(left)),
+                        (text(right.title().to_string()), percent(right)),
+                    )
+                };
+
+                column![
+                    self.times_chart.view(None),
+                    seperator_background_1(),
+                    split_table_double(vec![
+                        state_pair(CpuState::User, CpuState::System),
+                        state_pair(CpuState::Nice, CpuState::Iowait),
+                        state_pair(CpuState::Irq, CpuState::Softirq),
+                        state_pair(CpuState::Steal, CpuState::Idle),
+                    ]),
+                ]
+            } else {
+                split_table_single(vec![(
+                    text(String::from("Time breakdown")),
+                    text(String::from("Not available on this system")),
+                )])
+            },
+        );
+
+        let load = section_box(
+            (
+                bootstrap_icon(BootstrapIcon::Speedometer),
+                text(String::from("Load average")),
+                row![],
+            ),
+            match data.load {
+                Some(load) => column![
+                    self.load_chart.view(None).map(move |message| {
+                        CpuPageMessage::ResourceChartMessage(message)
+                    }),
+                    seperator_background_1(),
+                    split_table_double(vec![
+                        (
+                            (text(String::from("1 minute")), text(format!("{:.2}", load.one))),
+                            (text(String::from("5 minutes")), text(format!("{:.2}", load.five))),
+                        ),
+                        (
+                            (text(String::from("15 minutes")), text(format!("{:.2}", load.fifteen))),
+                            (
+                                text(String::from("Runnable tasks")),
+                                text(format!("{} of {}", load.runnable, load.total)),
+                            ),
+                        ),
+                    ]),
+                ],
+                None => split_table_single(vec![(
+                    text(String::from("Load average")),
+                    text(String::from("Not available on this system")),
+                )]),
+            },
+        );
+
+        let pressure = self
+            .pressure_charts
+            .view("CPU", data.pressure.as_ref())
+            .map(CpuPageMessage::ResourceChartMessage);
+
         let thermals = section_box(
             (
                 bootstrap_icon(BootstrapIcon::Thermometer),
                 text(String::from("Thermals")),
                 row![],
             ),
-            split_table_single(vec![(
-                text(String::from("Temperature")),
-                text(String::from("25℃")), /* format!("{:.2}°C") */
-            )]),
+            match data.temperatures.package() {
+                Some(package) => {
+                    let optional_celsius = |celsius: Option<f32>| celsius.map(format_celsius).unwrap_or(String::from("Unknown"));
+
+                    let mut temperatures = vec![
+                        (
+                            text(String::from("Temperature")),
+                            text(format_celsius(package.celsius)),
+                        ),
+                        (text(String::from("High")), text(optional_celsius(package.high))),
+                        (text(String::from("Critical")), text(optional_celsius(package.critical))),
+                    ];
+
+                    // Every package and core when there's more than the one charted
+                    if data.temperatures.packages.len() > 1 || !data.temperatures.cores.is_empty() {
+                        for temperature in data.temperatures.packages.iter().chain(&data.temperatures.cores) {
+                            temperatures.push((
+                                text(temperature.label.clone()),
+                                text(format_celsius(temperature.celsius)),
+                            ));
+                        }
+                    }
+
+                    column![
+                        self.temperature_chart.view(None).map(move |message| {
+                            CpuPageMessage::ResourceChartMessage(message)
+                        }),
+                        seperator_background_1(),
+                        split_table_single(temperatures),
+                    ]
+                }
+                None => split_table_single(vec![(
+                    text(String::from("Temperature")),
+                    text(String::from("No sensors found")),
+                )]),
+            },
         );
 
         let about = section_box(
@@ -175,30 +368,103 @@ impl CpuPage {
                 text(String::from("About")),
                 row![],
             ),
-            column![split_table_single(vec![
-                (
-                    text(String::from("Physical cores")),
-                    text(format!("{}", physical_core_count)),
-                ),
-                (
-                    text(String::from("Logical cores")),
-                    text(format!("{}", logical_core_count)),
-                ),
-                (
-                    text(String::from("Brand")),
-                    text(format!("{}", brand)),
-                ),
-                (
-                    text(String::from("Max frequency")),
-                    text(format_hz(preferences, 0.)),
-                ),
-                (text(String::from("Architecture")), text("x_96"),),
-                (text(String::from("Virtualization")), text("Muh virtual"),),
-            ])],
+            column![split_table_single({
+                let unknown = || String::from("Unknown");
+                let optional_hz = |frequency: Option<u64>| frequency.map(|frequency| format_hz(preferences, frequency as f32)).unwrap_or_else(unknown);
+                let optional_number = |number: Option<u32>| number.map(|number| number.to_string()).unwrap_or_else(unknown);
+
+                let mut about = vec![
+                    (
+                        text(String::from("Physical cores")),
+                        text(format!("{}", physical_core_count)),
+                    ),
+                    (
+                        text(String::from("Logical cores")),
+                        text(format!("{}", logical_core_count)),
+                    ),
+                    (
+                        text(String::from("Brand")),
+                        text(brand),
+                    ),
+                    (
+                        text(String::from("Vendor")),
+                        text(info.vendor.clone().unwrap_or_else(unknown)),
+                    ),
+                    (
+                        text(String::from("Family / model / stepping")),
+                        text(format!(
+                            "{} / {} / {}",
+                            optional_number(info.family),
+                            optional_number(info.model),
+                            optional_number(info.stepping)
+                        )),
+                    ),
+                    (
+                        text(String::from("Microcode")),
+                        text(info.microcode.clone().unwrap_or_else(unknown)),
+                    ),
+                    (text(String::from("Architecture")), text(info.architecture.clone())),
+                ];
+
+                // Only some drivers know the base frequency
+                if let Some(base_frequency) = info.base_frequency {
+                    about.push((
+                        text(String::from("Base frequency")),
+                        text(format_hz(preferences, base_frequency as f32)),
+                    ));
+                }
+
+                about.push((text(String::from("Min frequency")), text(optional_hz(info.min_frequency))));
+                about.push((text(String::from("Max frequency")), text(optional_hz(info.max_frequency))));
+
+                for cache in &info.caches {
+                    about.push((
+                        text(format!("{} cache", cache.name())),
+                        text(format!(
+                            "{} ({} {})",
+                            format_bytes(preferences, cache.size as f32),
+                            cache.instances,
+                            if cache.instances == 1 { "instance" } else { "instances" }
+                        )),
+                    ));
+                }
+
+                about.push((
+                    text(String::from("Virtualization")),
+                    text(info.virtualization.clone().unwrap_or(String::from("Not supported"))),
+                ));
+
+                if let Some(hypervisor) = &info.hypervisor {
+                    about.push((text(String::from("Hypervisor")), text(hypervisor.clone())));
+                }
+
+                about
+            })],
+        );
+
+        let vulnerabilities = section_box(
+            (
+                bootstrap_icon(BootstrapIcon::ShieldExclamation),
+                text(String::from("Vulnerabilities")),
+                row![],
+            ),
+            if info.vulnerabilities.is_empty() {
+                split_table_single(vec![(
+                    text(String::from("Vulnerabilities")),
+                    text(String::from("None reported")),
+                )])
+            } else {
+                split_table_single(
+                    info.vulnerabilities
+                        .iter()
+                        .map(|(name, status)| (text(name.clone()), text(status.clone())))
+                        .collect(),
+                )
+            },
         );
 
         let main = container(
-            column![cpu_details_ui, thermals, about]
+            column![cpu_details_ui, times, load, pressure, thermals, about, vulnerabilities]
                 .spacing(20)
                 .align_items(alignment::Alignment::Center),
         )
@@ -10,14 +10,18 @@ use crate::{
     constants::padding, general_widgets::{
         icons::bootstrap_icon, section::section_box, seperators::seperator_background_1,
         split_table_double::split_table_double, split_table_single::split_table_single,
-    }, preferences::Preferences, styles::{self, container::resource_details_header}, utils::{format_bytes, round_bytes_list}, ActivePreview, DiskData, ResourceHistory
+    }, preferences::Preferences, styles::{self, container::resource_details_header}, types::time_series::TimeSeries, utils::{format_bytes, format_rate, round_bytes_list}, DiskData
 };
 
 use super::{
     chart::{ResourceChart, ResourceChartMessage},
+    pressure_charts::PressureCharts,
     resource_details::ResourceDetailsMessage,
 };
 
+/// Bytes per second the charts scale to at least, so an idle disk doesn't fill them with noise
+const MIN_RATE_SCALE: f64 = 1024. * 1024.;
+
 #[derive(Debug, Clone)]
 pub enum DiskPageMessage {
     ResourceChartMessage(ResourceChartMessage),
@@ -25,31 +29,37 @@ pub enum DiskPageMessage {
 
 #[derive(Debug)]
 pub struct DiskPage {
-    written_chart: ResourceChart,
-    read_chart: ResourceChart,
+    pub written_chart: ResourceChart,
+    pub read_chart: ResourceChart,
+    pub pressure_charts: PressureCharts,
 }
 
 impl DiskPage {
-    pub fn new(preferences: &Preferences) -> Self {
+    pub fn new(
+        preferences: &Preferences,
+        read_history: &TimeSeries,
+        written_history: &TimeSeries,
+        pressure_charts: PressureCharts,
+    ) -> Self {
         Self {
-            written_chart: ResourceChart::new(preferences),
-            read_chart: ResourceChart::new(preferences),
+            written_chart: ResourceChart::new(preferences, written_history)
+                .with_auto_scale(MIN_RATE_SCALE),
+            read_chart: ResourceChart::new(preferences, read_history)
+                .with_auto_scale(MIN_RATE_SCALE),
+            pressure_charts,
         }
     }
 
-    pub fn update_history(&mut self, active_preview: &ActivePreview, resource_history: &ResourceHistory) {
-
-        let disk_name = active_preview.name.as_ref().unwrap();
-        let disk_written = resource_history.disk_write.get(disk_name).unwrap();
-        let disk_read = resource_history.disk_read.get(disk_name).unwrap();
-
-        self.written_chart.data_points = disk_written.clone();
-        self.read_chart.data_points = disk_read.clone();
-    }
-
     pub fn update(&mut self, message: DiskPageMessage) -> Command<DiskPageMessage> {
         match message {
-            _ => Command::none(),
+            // The range is the same for every chart on the page
+            DiskPageMessage::ResourceChartMessage(message) => {
+                self.read_chart.update(message);
+                self.written_chart.update(message);
+                self.pressure_charts.update(message);
+
+                Command::none()
+            }
         }
     }
 
@@ -81,7 +91,7 @@ impl DiskPage {
                     seperator_background_1(),
                     split_table_single(vec![(
                         text("Reads".to_string()),
-                        text(format_bytes(preferences, data.read as f32))
+                        text(format_rate(preferences, data.read))
                     )]),
                 ]
             },
@@ -103,12 +113,17 @@ impl DiskPage {
                     seperator_background_1(),
                     split_table_single(vec![(
                         text("Writes".to_string()),
-                        text(format_bytes(preferences, data.written as f32))
+                        text(format_rate(preferences, data.written))
                     )]),
                 ]
             },
         );
 
+        let pressure = self
+            .pressure_charts
+            .view("I/O", data.pressure.as_ref())
+            .map(DiskPageMessage::ResourceChartMessage);
+
         let thermals = section_box(
             (
                 bootstrap_icon(BootstrapIcon::Thermometer),
@@ -186,7 +201,7 @@ impl DiskPage {
         );
 
         let main = container(
-            column![read_ui, write_ui, thermals, about]
+            column![read_ui, write_ui, pressure, thermals, about]
                 .spacing(20)
                 .align_items(alignment::Alignment::Center),
         )
@@ -17,11 +17,13 @@ use crate::{
         self,
         container::{divider_background_1, resource_details_child, resource_details_header},
     },
-    types::resource_data::MemoryData,
-    ResourceHistory,
+    types::{resource_data::MemoryData, time_series::TimeSeries},
 };
 
-use super::chart::{ResourceChart, ResourceChartMessage};
+use super::{
+    chart::{ResourceChart, ResourceChartMessage},
+    pressure_charts::PressureCharts,
+};
 
 #[derive(Debug, Clone)]
 pub enum MemoryPageMessage {
@@ -33,23 +35,33 @@ pub enum MemoryPageMessage {
 pub struct MemoryPage {
     pub ram_chart: ResourceChart,
     pub swap_chart: ResourceChart,
+    pub pressure_charts: PressureCharts,
 }
 
 impl MemoryPage {
-    pub fn new(preferences: &Preferences) -> Self {
+    pub fn new(
+        preferences: &Preferences,
+        ram_history: &TimeSeries,
+        swap_history: &TimeSeries,
+        pressure_charts: PressureCharts,
+    ) -> Self {
         Self {
-            ram_chart: ResourceChart::new(preferences),
-            swap_chart: ResourceChart::new(preferences),
+            ram_chart: ResourceChart::new(preferences, ram_history),
+            swap_chart: ResourceChart::new(preferences, swap_history),
+            pressure_charts,
         }
     }
 
-    pub fn update_history(&mut self, resource_history: &ResourceHistory) {
-        self.ram_chart.data_points = resource_history.ram.clone();
-        self.swap_chart.data_points = resource_history.swap.clone();
-    }
-
     pub fn update(&mut self, message: MemoryPageMessage) -> Command<MemoryPageMessage> {
         match message {
+            // The range is the same for every chart on the page
+            MemoryPageMessage::ResourceChartMessage(message) => {
+                self.ram_chart.update(message);
+                self.swap_chart.update(message);
+                self.pressure_charts.update(message);
+
+                Command::none()
+            }
             _ => Command::none(),
         }
     }
@@ -137,6 +149,11 @@ impl MemoryPage {
             },
         );
 
+        let pressure = self
+            .pressure_charts
+            .view("Memory", data.pressure.as_ref())
+            .map(MemoryPageMessage::ResourceChartMessage);
+
         let thermals = section_box(
             (
                 bootstrap_icon(BootstrapIcon::Thermometer),
@@ -197,7 +214,7 @@ impl MemoryPage {
         .spacing(padding::PORTION);
 
         let main = container(
-            column![ram_details, swap_details, thermals, about, advanced]
+            column![ram_details, swap_details, pressure, thermals, about, advanced]
                 .spacing(20)
                 .align_items(alignment::Alignment::Center),
         )
@@ -5,4 +5,6 @@ pub mod disk_page;
 pub mod cpu_page;
 pub mod resource_details;
 pub mod processes_page;
+pub mod process_details_page;
+pub mod pressure_charts;
 pub mod battery_page;
\ No newline at end of file
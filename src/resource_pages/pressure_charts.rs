@@ -0,0 +1,108 @@
+use iced::{
+    widget::{column, row, text},
+    Element, Length,
+};
+use iced_aw::BootstrapIcon;
+
+use crate::{
+    constants::sizings::DEFAULT_CHART_HEIGHT,
+    general_widgets::{
+        icons::bootstrap_icon, section::section_box, seperators::seperator_background_1,
+        split_table_single::split_table_single,
+    },
+    preferences::Preferences,
+    types::{
+        pressure::{Pressure, PressureAverages},
+        time_series::TimeSeries,
+    },
+};
+
+use super::chart::{ResourceChart, ResourceChartMessage};
+
+/// Stall percents are usually low, so the y axis starts out small and grows to fit
+const PRESSURE_SCALE: f64 = 10.;
+
+/// A resource's pressure stall information, the same on the CPU, memory and disk pages
+#[derive(Debug)]
+pub struct PressureCharts {
+    pub some_chart: ResourceChart,
+    pub full_chart: ResourceChart,
+}
+
+impl PressureCharts {
+    pub fn new(
+        preferences: &Preferences,
+        some_history: &TimeSeries,
+        full_history: &TimeSeries,
+    ) -> Self {
+        Self {
+            some_chart: ResourceChart::new(preferences, some_history)
+                .with_auto_scale(PRESSURE_SCALE),
+            full_chart: ResourceChart::new(preferences, full_history)
+                .with_auto_scale(PRESSURE_SCALE),
+        }
+    }
+
+    pub fn update(&mut self, message: ResourceChartMessage) {
+        self.some_chart.update(message);
+        self.full_chart.update(message);
+    }
+
+    /// A section titled like "Memory pressure". Explains why there's nothing to show if the pressure couldn't be read
+    pub fn view(
+        &self,
+        title: &str,
+        pressure: Option<&Pressure>,
+    ) -> Element<'_, ResourceChartMessage> {
+        let header = (
+            bootstrap_icon(BootstrapIcon::HourglassSplit),
+            text(format!("{} pressure", title)),
+            row![],
+        );
+
+        let Some(pressure) = pressure else {
+            return section_box(
+                header,
+                split_table_single(vec![(
+                    text(String::from("Pressure")),
+                    text(String::from(
+                        "Not available, needs Linux 4.20 or later with PSI enabled",
+                    )),
+                )]),
+            )
+            .into();
+        };
+
+        let averages = |averages: Option<PressureAverages>| match averages {
+            Some(averages) => format!(
+                "{:.1}% / {:.1}% / {:.1}%",
+                averages.avg10, averages.avg60, averages.avg300
+            ),
+            None => String::from("Not reported"),
+        };
+
+        let half_height = Some(Length::Fixed(DEFAULT_CHART_HEIGHT / 2.));
+
+        section_box(
+            header,
+            column![
+                row![
+                    column![text("Some"), self.some_chart.view(half_height)],
+                    column![text("Full"), self.full_chart.view(half_height)],
+                ],
+                seperator_background_1(),
+                split_table_single(vec![
+                    (
+                        text(String::from("Some stalled, 10s / 1m / 5m")),
+                        text(averages(Some(pressure.some))),
+                    ),
+                    (
+                        text(String::from("All stalled, 10s / 1m / 5m")),
+                        text(averages(pressure.full)),
+                    ),
+                ]),
+            ],
+        )
+        .into()
+    }
+}
@@ -0,0 +1,394 @@
+use iced::{
+    alignment, theme,
+    widget::{button, column, container, horizontal_space, row, scrollable, text, Column},
+    Alignment, Command, Element, Length,
+};
+use iced_aw::BootstrapIcon;
+use sysinfo::Pid;
+
+use crate::{
+    constants::{custom_theme, padding, sizings::DEFAULT_CHART_HEIGHT},
+    general_widgets::{
+        icons::bootstrap_icon, section::section_box, seperators::seperator_background_1,
+        split_table_double::split_table_double, split_table_single::split_table_single,
+    },
+    preferences::Preferences,
+    styles::{self, container::resource_details_header},
+    types::resource_data::ProcessDetailsData,
+    utils::{format_bytes, format_duration, format_rate, format_unix_time},
+    ProcessHistory,
+};
+
+use super::chart::{ResourceChart, ResourceChartMessage};
+
+/// How many open files to list before summarizing the rest
+const MAX_LISTED_OPEN_FILES: usize = 200;
+
+#[derive(Debug, Clone)]
+pub enum ProcessDetailsPageMessage {
+    ResourceChartMessage(ResourceChartMessage),
+    /// Go back to the processes page
+    Back,
+}
+
+#[derive(Debug)]
+pub struct ProcessDetailsPage {
+    pub pid: Pid,
+    pub cpu_chart: ResourceChart,
+    pub memory_chart: ResourceChart,
+    pub disk_read_chart: ResourceChart,
+    pub disk_written_chart: ResourceChart,
+}
+
+impl ProcessDetailsPage {
+    pub fn new(preferences: &Preferences, pid: Pid, history: &ProcessHistory) -> Self {
+        Self {
+            pid,
+            // A process can use more than one logical core, so let the chart grow past 100%
+            cpu_chart: ResourceChart::new(preferences, &history.cpu).with_auto_scale(100.),
+            memory_chart: ResourceChart::new(preferences, &history.memory),
+            disk_read_chart: ResourceChart::new(preferences, &history.disk_read),
+            disk_written_chart: ResourceChart::new(preferences, &history.disk_written),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        message: ProcessDetailsPageMessage,
+    ) -> Command<ProcessDetailsPageMessage> {
+        match message {
+            ProcessDetailsPageMessage::ResourceChartMessage(_) => Command::none(),
+            // Changing the page is handled by the app
+            ProcessDetailsPageMessage::Back => Command::none(),
+        }
+    }
+
+    fn chart_view<'a>(
+        chart: &'a ResourceChart,
+        label: &str,
+        value: String,
+    ) -> Column<'a, ProcessDetailsPageMessage> {
+        column![
+            chart
+                .view(Some(Length::Fixed(DEFAULT_CHART_HEIGHT / 2.)))
+                .map(ProcessDetailsPageMessage::ResourceChartMessage),
+            split_table_single(vec![(text(String::from(label)), text(value))]),
+        ]
+    }
+
+    pub fn view(
+        &self,
+        preferences: &Preferences,
+        data: &ProcessDetailsData,
+    ) -> Element<'_, ProcessDetailsPageMessage> {
+        let header = container(
+            row![
+                button(bootstrap_icon(BootstrapIcon::ArrowLeft))
+                    .on_press(ProcessDetailsPageMessage::Back)
+                    .style(theme::Button::Text),
+                horizontal_space(),
+                text(format!("{} ({})", data.name, data.pid)),
+                horizontal_space(),
+            ]
+            .align_items(Alignment::Center),
+        )
+        .center_x()
+        .style(resource_details_header())
+        .width(Length::Fill)
+        .padding(padding::MAIN);
+
+        if !data.exists {
+            let content = column![
+                header,
+                container(text(format!("Process {} is no longer running", data.pid)))
+                    .padding(padding::SECTION)
+            ];
+
+            return container(content).into();
+        }
+
+        let usage = section_box(
+            (
+                bootstrap_icon(BootstrapIcon::Activity),
+                text(String::from("Usage")),
+                row![],
+            ),
+            column![
+                Self::chart_view(&self.cpu_chart, "CPU", format!("{:.2}%", data.cpu_usage)),
+                seperator_background_1(),
+                Self::chart_view(
+                    &self.memory_chart,
+                    "Memory",
+                    format_bytes(preferences, data.memory_usage as f32)
+                ),
+                seperator_background_1(),
+                Self::chart_view(
+                    &self.disk_read_chart,
+                    "Disk read",
+                    format_rate(preferences, chart_latest(&self.disk_read_chart))
+                ),
+                seperator_background_1(),
+                Self::chart_view(
+                    &self.disk_written_chart,
+                    "Disk written",
+                    format_rate(preferences, chart_latest(&self.disk_written_chart))
+                ),
+            ],
+        );
+
+        let overview = section_box(
+            (
+                bootstrap_icon(BootstrapIcon::InfoCircle),
+                text(String::from("Overview")),
+                row![],
+            ),
+            column![
+                split_table_double(vec![
+                    (
+                        (
+                            text(String::from("CPU")),
+                            text(format!("{:.2}%", data.cpu_usage)),
+                        ),
+                        (
+                            text(String::from("Memory")),
+                            text(format_bytes(preferences, data.memory_usage as f32)),
+                        ),
+                    ),
+                    (
+                        (text(String::from("State")), text(data.state.clone())),
+                        (
+                            text(String::from("Threads")),
+                            text(optional_to_string(data.thread_count)),
+                        ),
+                    ),
+                ]),
+                split_table_single(vec![
+                    (
+                        text(String::from("User")),
+                        text(id_with_name(data.user_id, &data.user)),
+                    ),
+                    (
+                        text(String::from("Group")),
+                        text(id_with_name(data.group_id, &data.group)),
+                    ),
+                    (
+                        text(String::from("Start time")),
+                        text(format!(
+                            "{} ({} ago)",
+                            format_unix_time(data.start_time),
+                            format_duration(data.run_time)
+                        )),
+                    ),
+                    (
+                        text(String::from("Virtual memory")),
+                        text(format_bytes(preferences, data.virtual_memory as f32)),
+                    ),
+                ]),
+                match data.io {
+                    Some(io) => split_table_single(vec![
+                        (
+                            text(String::from("Total disk read")),
+                            text(format_bytes(preferences, io.read_bytes as f32)),
+                        ),
+                        (
+                            text(String::from("Total disk written")),
+                            text(format_bytes(preferences, io.write_bytes as f32)),
+                        ),
+                        (
+                            text(String::from("Cancelled writes")),
+                            text(format_bytes(preferences, io.cancelled_write_bytes as f32)),
+                        ),
+                    ]),
+                    None => split_table_single(vec![(
+                        text(String::from("Disk I/O totals")),
+                        text(String::from(
+                            "Unavailable, this may require elevated permissions"
+                        )),
+                    )]),
+                },
+            ],
+        );
+
+        let command = section_box(
+            (
+                bootstrap_icon(BootstrapIcon::Terminal),
+                text(String::from("Command")),
+                row![],
+            ),
+            column![split_table_single(vec![
+                (
+                    text(String::from("Command line")),
+                    text(if data.command.is_empty() {
+                        String::from("Unknown")
+                    } else {
+                        data.command.join(" ")
+                    }),
+                ),
+                (
+                    text(String::from("Executable")),
+                    text(optional_path_to_string(&data.exe)),
+                ),
+                (
+                    text(String::from("Working directory")),
+                    text(optional_path_to_string(&data.cwd)),
+                ),
+                (
+                    text(String::from("Cgroup")),
+                    text(data.cgroup.clone().unwrap_or(String::from("Unknown"))),
+                ),
+            ])],
+        );
+
+        let memory_maps = section_box(
+            (
+                bootstrap_icon(BootstrapIcon::Map),
+                text(String::from("Memory maps")),
+                row![],
+            ),
+            match &data.memory_maps {
+                Some(memory_maps) => column![split_table_double(vec![
+                    (
+                        (
+                            text(String::from("Mappings")),
+                            text(format!("{}", memory_maps.mappings)),
+                        ),
+                        (
+                            text(String::from("Mapped files")),
+                            text(format!("{}", memory_maps.mapped_files)),
+                        ),
+                    ),
+                    (
+                        (
+                            text(String::from("Anonymous")),
+                            text(format_bytes(preferences, memory_maps.anonymous_size as f32)),
+                        ),
+                        (
+                            text(String::from("File backed")),
+                            text(format_bytes(
+                                preferences,
+                                memory_maps.file_backed_size as f32
+                            )),
+                        ),
+                    ),
+                ])],
+                None => unavailable(),
+            },
+        );
+
+        let open_files = section_box(
+            (
+                bootstrap_icon(BootstrapIcon::FileEarmark),
+                text(match &data.open_files {
+                    Some(open_files) => format!("Open files ({})", open_files.len()),
+                    None => String::from("Open files"),
+                }),
+                row![],
+            ),
+            match &data.open_files {
+                Some(open_files) if !open_files.is_empty() => {
+                    let mut rows: Vec<(iced::widget::Text, iced::widget::Text)> = open_files
+                        .iter()
+                        .take(MAX_LISTED_OPEN_FILES)
+                        .map(|(fd, target)| (text(fd.clone()), text(target.clone())))
+                        .collect();
+
+                    if open_files.len() > MAX_LISTED_OPEN_FILES {
+                        rows.push((
+                            text(String::from("...")),
+                            text(format!(
+                                "and {} more",
+                                open_files.len() - MAX_LISTED_OPEN_FILES
+                            )),
+                        ));
+                    }
+
+                    column![split_table_single(rows)]
+                }
+                Some(_) => column![container(text("None")).padding(padding::MAIN)],
+                None => unavailable(),
+            },
+        );
+
+        let environment = section_box(
+            (
+                bootstrap_icon(BootstrapIcon::ListUl),
+                text(String::from("Environment")),
+                row![],
+            ),
+            if data.environment.is_empty() {
+                unavailable()
+            } else {
+                column![container(Column::with_children(
+                    data.environment
+                        .iter()
+                        .map(|variable| text(variable.clone()).into())
+                ))
+                .padding(padding::MAIN)]
+            },
+        );
+
+        let main = container(
+            column![
+                usage,
+                overview,
+                command,
+                memory_maps,
+                open_files,
+                environment
+            ]
+            .spacing(20)
+            .align_items(alignment::Alignment::Center),
+        )
+        .center_x()
+        .width(Length::Fill)
+        .padding(padding::SECTION);
+
+        let content = column![
+            header,
+            scrollable(main).style(iced::theme::Scrollable::Custom(Box::new(
+                styles::scrollable::Background1 {},
+            )))
+        ];
+
+        let container = container(content);
+        container.into()
+    }
+}
+
+/// Shown when /proc couldn't be read, usually because the process belongs to another user
+fn unavailable<'a>() -> Column<'a, ProcessDetailsPageMessage> {
+    column![container(
+        text("Unavailable, this may require elevated permissions")
+            .style(theme::Text::Color(custom_theme::GREY_TEXT))
+    )
+    .padding(padding::MAIN)]
+}
+
+fn optional_to_string<T: ToString>(value: Option<T>) -> String {
+    value
+        .map(|value| value.to_string())
+        .unwrap_or(String::from("Unknown"))
+}
+
+fn optional_path_to_string(path: &Option<std::path::PathBuf>) -> String {
+    path.as_ref()
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or(String::from("Unknown"))
+}
+
+fn id_with_name(id: Option<u32>, name: &Option<String>) -> String {
+    match (id, name) {
+        (Some(id), Some(name)) => format!("{} ({})", name, id),
+        (Some(id), None) => format!("{}", id),
+        _ => String::from("Unknown"),
+    }
+}
+
+/// The most recent value of a chart
+fn chart_latest(chart: &ResourceChart) -> f64 {
+    chart
+        .series
+        .latest()
+        .map(|sample| sample.value)
+        .unwrap_or(0.)
+}
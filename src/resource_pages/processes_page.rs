@@ -1,81 +1,481 @@
-
+use std::io;
 
 use iced::{
-    alignment, theme,
-    widget::{button, column, container, row, scrollable, text},
-    Command, Element, Length,
+    alignment, keyboard, theme,
+    widget::{
+        button, checkbox, column, container, horizontal_space, pick_list, row, scrollable, text,
+        text_input, Column, Row, Space,
+    },
+    Alignment, Command, Element, Length,
 };
-use iced_aw::{grid_row, BootstrapIcon, Grid, GridRow};
-use sysinfo::{Pid, System};
+use iced_aw::{BootstrapIcon, Grid, GridRow, Wrap};
+use sysinfo::Pid;
 
 use crate::{
-    constants::padding,
+    constants::{custom_theme, padding},
+    general_widgets::icons::bootstrap_icon,
     preferences::{self, Preferences},
+    process_control::{
+        self, apply_to_all, format_cores, send_signal, ProcessSignal, SchedulingPolicy,
+    },
     styles::{self, container::resource_details_header},
+    types::process_columns::ProcessColumn,
     types::resource_data::{ApplicationData, ProcessesData},
+    types::snapshot::Snapshot,
+    types::time_series::TimeSeries,
+    utils::{format_bytes, format_duration, format_rate, format_unix_time},
+    ResourceHistory,
 };
 
 use super::{
-    chart::{ResourceChart, ResourceChartMessage},
+    chart::{ResourceChart, ResourceChartMessage, Sparkline},
     resource_details::{ResourceDetailsMessage, SortDirection},
 };
 
+/// Horizontal space per level of depth in the process tree
+const TREE_INDENT: f32 = 16.;
+/// Width reserved for the expand button so leaf names line up with their siblings
+const TREE_TOGGLE_WIDTH: f32 = 28.;
+/// Characters of the command line to show in its column, so long ones don't stretch the table
+const MAX_COMMAND_LENGTH: usize = 60;
+
 #[derive(Debug, Clone)]
 pub enum ProcessesPageMessage {
     ResourceChartMessage(ResourceChartMessage),
     SwitchSortDirection,
-    SortByIndex(u32),
-    KillProcess(Pid),
+    SortByColumn(ProcessColumn),
+    /// Show or hide the list of columns to choose from
+    ToggleColumnsMenu,
+    SetColumnShown(ProcessColumn, bool),
+    /// Move a column left (negative) or right by the offset
+    MoveColumn(ProcessColumn, isize),
+    /// Show the actions for the process, by PID, start time and name, or hide them if they are already shown
+    ToggleActions(Pid, u64, String),
+    /// Show the actions for the selected processes, or hide them if they are already shown
+    ToggleSelectionActions,
+    SendSignal(ProcessSignal),
+    CustomSignalChanged(String),
+    SendCustomSignal,
+    ConfirmSignal,
+    CancelSignal,
+    ToggleTree(bool),
+    FilterChanged(String),
+    ToggleExpanded(Pid),
+    /// Open the details page for the process. Handled by the app since it changes the page
+    OpenDetails(Pid),
+    NicenessChanged(String),
+    ApplyNiceness,
+    SetSchedulingPolicy(SchedulingPolicy),
+    /// Check or uncheck a logical core in the affinity checklist
+    ToggleCore(usize, bool),
+    ApplyAffinity,
+    /// Select or deselect a process. With shift held, everything since the last toggled process is changed too
+    ToggleSelected(Pid, bool),
+    /// Select every process that matches the filter
+    SelectAllMatching,
+    ClearSelection,
+    /// Forwarded by the app so shift-clicks can be recognised
+    ModifiersChanged(keyboard::Modifiers),
+}
+
+/// What the actions panel acts on
+#[derive(Debug, Clone, PartialEq)]
+enum ActionTarget {
+    /// A single process, by PID, start time and name
+    Process(Pid, u64, String),
+    /// Every selected process
+    Selected,
 }
 
 #[derive(Debug)]
-pub struct ProcessesPage {}
+pub struct ProcessesPage {
+    actions_for: Option<ActionTarget>,
+    /// A destructive signal waiting on the user to confirm it
+    pending_signal: Option<ProcessSignal>,
+    custom_signal: String,
+    /// The outcome of the last action taken, as a message or an error
+    action_result: Option<Result<String, String>>,
+    /// The niceness as typed by the user
+    niceness: String,
+    /// None if the process uses a realtime policy or it couldn't be read
+    scheduling_policy: Option<SchedulingPolicy>,
+    /// Whether each logical core is checked in the affinity checklist
+    affinity: Vec<bool>,
+    logical_core_count: u32,
+    show_columns_menu: bool,
+    /// Why the column layout couldn't be saved, if it couldn't
+    layout_error: Option<String>,
+    /// The last process selected or deselected, where shift-click ranges start from
+    selection_anchor: Option<Pid>,
+    shift_held: bool,
+}
 
 impl ProcessesPage {
-    pub fn new(preferences: &Preferences) -> Self {
-        Self {}
+    pub fn new(preferences: &Preferences, logical_core_count: u32) -> Self {
+        Self {
+            actions_for: None,
+            pending_signal: None,
+            custom_signal: String::new(),
+            action_result: None,
+            niceness: String::new(),
+            scheduling_policy: None,
+            affinity: Vec::new(),
+            logical_core_count,
+            show_columns_menu: false,
+            layout_error: None,
+            selection_anchor: None,
+            shift_held: false,
+        }
+    }
+
+    /// Loads the current niceness, policy and affinity of the process into the action controls.
+    /// For a selection, the values of its first process are used as a starting point
+    fn load_scheduling(&mut self, data: &ProcessesData) {
+        let Some((pid, _)) = self.target_processes(data).first().copied() else {
+            return;
+        };
+
+        self.niceness = process_control::niceness(pid)
+            .map(|niceness| niceness.to_string())
+            .unwrap_or_default();
+        self.scheduling_policy = process_control::scheduling_policy(pid).ok().flatten();
+
+        let allowed_cores = process_control::affinity(pid).ok();
+
+        self.affinity = (0..self.logical_core_count as usize)
+            .map(|core| {
+                allowed_cores
+                    .as_ref()
+                    .is_none_or(|allowed_cores| allowed_cores.contains(&core))
+            })
+            .collect();
+    }
+
+    fn save_layout(&mut self, data: &ProcessesData) {
+        self.layout_error = data.layout.save().err();
+    }
+
+    /// The targeted processes, by PID and start time
+    fn target_processes(&self, data: &ProcessesData) -> Vec<(Pid, u64)> {
+        match &self.actions_for {
+            Some(ActionTarget::Process(pid, start_time, _)) => vec![(*pid, *start_time)],
+            Some(ActionTarget::Selected) => {
+                let mut processes: Vec<(Pid, u64)> = data
+                    .selected
+                    .iter()
+                    .map(|(pid, start_time)| (*pid, *start_time))
+                    .collect();
+                processes.sort();
+                processes
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Describes the target for the user, like "firefox (1234)" or "3 selected processes"
+    fn target_label(&self, data: &ProcessesData) -> String {
+        match &self.actions_for {
+            Some(ActionTarget::Process(pid, _, name)) => format!("{} ({})", name, pid),
+            Some(ActionTarget::Selected) if data.selected.len() == 1 => {
+                String::from("1 selected process")
+            }
+            Some(ActionTarget::Selected) => format!("{} selected processes", data.selected.len()),
+            None => String::new(),
+        }
+    }
+
+    /// Runs the action on every targeted process and keeps a summary of how it went
+    fn run_action(
+        &mut self,
+        data: &ProcessesData,
+        action: String,
+        run: impl FnMut(Pid) -> io::Result<()>,
+    ) {
+        let result = apply_to_all(&self.target_processes(data), run);
+
+        self.action_result = Some(result.summary(&action));
+    }
+
+    fn send_signal(&mut self, data: &ProcessesData, signal: ProcessSignal) {
+        self.pending_signal = None;
+
+        let action = format!("Send {} to {}", signal.name(), self.target_label(data));
+
+        self.run_action(data, action, |pid| send_signal(pid, signal));
+    }
+
+    /// Opens the actions panel for the target, or closes it if it is already open
+    fn toggle_actions(&mut self, target: ActionTarget, data: &ProcessesData) {
+        self.pending_signal = None;
+        self.action_result = None;
+
+        if self.actions_for.as_ref() == Some(&target) {
+            self.actions_for = None;
+            return;
+        }
+
+        self.actions_for = Some(target);
+        self.load_scheduling(data);
     }
 
     pub fn update(
         &mut self,
         message: ProcessesPageMessage,
         data: &mut ProcessesData,
-        system_info: &System,
+        snapshot: &Snapshot,
     ) -> Command<ProcessesPageMessage> {
         match message {
-            ProcessesPageMessage::SortByIndex(sort_index) => {
-
-                if data.sort_index == sort_index {
-                    return Command::none()
+            ProcessesPageMessage::SortByColumn(column) => {
+                if data.layout.sort_column == column {
+                    return Command::none();
                 }
 
-                data.sort_index = sort_index;
+                data.layout.sort_column = column;
                 // Also reset the sort direction since the user is sorting a different category
-                data.sort_direction = SortDirection::default();
+                data.layout.sort_direction = SortDirection::default();
 
-                data.sort_by_index();
+                data.sort();
+                self.save_layout(data);
 
                 Command::none()
             }
             ProcessesPageMessage::SwitchSortDirection => {
-
-                data.sort_direction = match data.sort_direction {
+                data.layout.sort_direction = match data.layout.sort_direction {
                     SortDirection::Descending => SortDirection::Ascending,
                     SortDirection::Ascending => SortDirection::Descending,
                 };
 
+                data.sort();
+                self.save_layout(data);
+
+                Command::none()
+            }
+            ProcessesPageMessage::ToggleColumnsMenu => {
+                self.show_columns_menu = !self.show_columns_menu;
+
+                Command::none()
+            }
+            ProcessesPageMessage::SetColumnShown(column, shown) => {
+                data.layout.set_shown(column, shown);
+                self.save_layout(data);
+
+                // Newly shown columns may need values that aren't read while hidden. They are filled in by the
+                // snapshot the collector is asked for once the page is updated
+                data.update_in_depth(snapshot);
+
+                Command::none()
+            }
+            ProcessesPageMessage::MoveColumn(column, offset) => {
+                data.layout.move_column(column, offset);
+                self.save_layout(data);
+
+                Command::none()
+            }
+            ProcessesPageMessage::ToggleActions(pid, start_time, name) => {
+                self.toggle_actions(ActionTarget::Process(pid, start_time, name), data);
+
+                Command::none()
+            }
+            ProcessesPageMessage::ToggleSelectionActions => {
+                self.toggle_actions(ActionTarget::Selected, data);
+
+                Command::none()
+            }
+            ProcessesPageMessage::SendSignal(signal) => {
+                if signal.is_destructive() {
+                    self.pending_signal = Some(signal);
+                    return Command::none();
+                }
+
+                self.send_signal(data, signal);
+
+                Command::none()
+            }
+            ProcessesPageMessage::CustomSignalChanged(custom_signal) => {
+                self.custom_signal = custom_signal;
+
                 Command::none()
             }
-            ProcessesPageMessage::KillProcess(pid) => {
+            ProcessesPageMessage::SendCustomSignal => {
+                let Ok(number) = self.custom_signal.trim().parse::<i32>() else {
+                    self.action_result = Some(Err(format!(
+                        "\"{}\" is not a signal number",
+                        self.custom_signal
+                    )));
+                    return Command::none();
+                };
 
-                let Some(process) = system_info.process(pid) else {
+                // Custom signals are always confirmed
+                self.pending_signal = Some(ProcessSignal::Custom(number));
+
+                Command::none()
+            }
+            ProcessesPageMessage::ConfirmSignal => {
+                let Some(signal) = self.pending_signal else {
                     return Command::none();
                 };
 
-                // The process still exists. Kill it
+                self.send_signal(data, signal);
+
+                Command::none()
+            }
+            ProcessesPageMessage::CancelSignal => {
+                self.pending_signal = None;
+
+                Command::none()
+            }
+            ProcessesPageMessage::ToggleTree(new_state) => {
+                data.show_tree = new_state;
+
+                Command::none()
+            }
+            ProcessesPageMessage::FilterChanged(query) => {
+                data.set_filter_query(query);
+                // Refilter right away instead of waiting for the next tick
+                data.update_in_depth(snapshot);
+
+                Command::none()
+            }
+            ProcessesPageMessage::ToggleExpanded(pid) => {
+                if !data.expanded.remove(&pid) {
+                    data.expanded.insert(pid);
+                }
+
+                Command::none()
+            }
+            ProcessesPageMessage::NicenessChanged(niceness) => {
+                self.niceness = niceness;
+
+                Command::none()
+            }
+            ProcessesPageMessage::ApplyNiceness => {
+                let niceness = match self.niceness.trim().parse::<i32>() {
+                    Ok(niceness) if (-20..=19).contains(&niceness) => niceness,
+                    _ => {
+                        self.action_result = Some(Err(format!(
+                            "\"{}\" is not a niceness between -20 and 19",
+                            self.niceness
+                        )));
+                        return Command::none();
+                    }
+                };
+
+                let action = format!(
+                    "Set the niceness of {} to {}",
+                    self.target_label(data),
+                    niceness
+                );
+
+                self.run_action(data, action, |pid| {
+                    process_control::set_niceness(pid, niceness)
+                });
+                self.load_scheduling(data);
+
+                Command::none()
+            }
+            ProcessesPageMessage::SetSchedulingPolicy(policy) => {
+                let action = format!(
+                    "Set the policy of {} to {}",
+                    self.target_label(data),
+                    policy
+                );
+
+                self.run_action(data, action, |pid| {
+                    process_control::set_scheduling_policy(pid, policy)
+                });
+                self.load_scheduling(data);
+
+                Command::none()
+            }
+            ProcessesPageMessage::ToggleCore(core, checked) => {
+                if let Some(allowed) = self.affinity.get_mut(core) {
+                    *allowed = checked;
+                }
+
+                Command::none()
+            }
+            ProcessesPageMessage::ApplyAffinity => {
+                let cores: Vec<usize> = (0..self.affinity.len())
+                    .filter(|core| self.affinity[*core])
+                    .collect();
+
+                let action = format!(
+                    "Set the affinity of {} to cores {}",
+                    self.target_label(data),
+                    format_cores(&cores)
+                );
+
+                self.run_action(data, action, |pid| {
+                    process_control::set_affinity(pid, &cores)
+                });
+                self.load_scheduling(data);
+
+                Command::none()
+            }
+            ProcessesPageMessage::ToggleSelected(pid, selected) => {
+                let visible_processes: Vec<(Pid, u64)> = data
+                    .visible_rows()
+                    .into_iter()
+                    .map(|(index, _)| {
+                        let process = &data.in_depth.processes[index];
+                        (process.pid, process.start_time)
+                    })
+                    .collect();
+
+                let position = |pid: Pid| {
+                    visible_processes
+                        .iter()
+                        .position(|(visible_pid, _)| *visible_pid == pid)
+                };
+
+                let range = self
+                    .selection_anchor
+                    .filter(|_| self.shift_held)
+                    .and_then(position)
+                    .zip(position(pid));
+
+                let processes = match range {
+                    Some((anchor_index, index)) => {
+                        &visible_processes[anchor_index.min(index)..=anchor_index.max(index)]
+                    }
+                    None => match position(pid) {
+                        Some(index) => &visible_processes[index..=index],
+                        None => &[],
+                    },
+                };
+
+                for (pid, start_time) in processes {
+                    if selected {
+                        data.selected.insert(*pid, *start_time);
+                    } else {
+                        data.selected.remove(pid);
+                    }
+                }
 
-                process.kill();
-                println!("Killed {}", process.name());
+                self.selection_anchor = Some(pid);
+                self.close_empty_selection_actions(data);
+
+                Command::none()
+            }
+            ProcessesPageMessage::SelectAllMatching => {
+                data.selected.extend(
+                    data.in_depth
+                        .processes
+                        .iter()
+                        .map(|process| (process.pid, process.start_time)),
+                );
+
+                Command::none()
+            }
+            ProcessesPageMessage::ClearSelection => {
+                data.selected.clear();
+                self.selection_anchor = None;
+                self.close_empty_selection_actions(data);
+
+                Command::none()
+            }
+            ProcessesPageMessage::ModifiersChanged(modifiers) => {
+                self.shift_held = modifiers.shift();
 
                 Command::none()
             }
@@ -83,32 +483,238 @@ impl ProcessesPage {
         }
     }
 
-    pub fn view(
-        &self,
-        preferences: &Preferences,
-        data: &ProcessesData,
-    ) -> Element<ProcessesPageMessage> {
-        let header = container(row!["Processes"])
-            .center_x()
-            .style(resource_details_header())
+    /// There is nothing to act on once the selection is empty
+    fn close_empty_selection_actions(&mut self, data: &ProcessesData) {
+        if self.actions_for == Some(ActionTarget::Selected) && data.selected.is_empty() {
+            self.actions_for = None;
+        }
+    }
+
+    fn actions_view(&self, data: &ProcessesData) -> Element<'_, ProcessesPageMessage> {
+        if self.actions_for.is_none() {
+            return column![].into();
+        }
+
+        let label = self.target_label(data);
+
+        let controls: Element<ProcessesPageMessage> = if let Some(signal) = self.pending_signal {
+            row![
+                text(format!("Send {} to {}?", signal.name(), label)),
+                horizontal_space(),
+                button(text("Confirm"))
+                    .on_press(ProcessesPageMessage::ConfirmSignal)
+                    .style(theme::Button::Destructive),
+                button(text("Cancel"))
+                    .on_press(ProcessesPageMessage::CancelSignal)
+                    .style(theme::Button::Text),
+            ]
+            .spacing(padding::PORTION)
+            .align_items(Alignment::Center)
+            .into()
+        } else {
+            Row::with_children({
+                let mut children: Vec<Element<ProcessesPageMessage>> =
+                    vec![text(label).into(), horizontal_space().into()];
+
+                for signal in ProcessSignal::NAMED {
+                    children.push(
+                        button(text(signal.name()))
+                            .on_press(ProcessesPageMessage::SendSignal(signal))
+                            .style(iced::theme::Button::Custom(Box::new(
+                                styles::button::Primary {},
+                            )))
+                            .into(),
+                    );
+                }
+
+                children.push(
+                    text_input("signal", &self.custom_signal)
+                        .on_input(ProcessesPageMessage::CustomSignalChanged)
+                        .on_submit(ProcessesPageMessage::SendCustomSignal)
+                        .width(Length::Fixed(70.))
+                        .into(),
+                );
+                children.push(
+                    button(text("Send"))
+                        .on_press(ProcessesPageMessage::SendCustomSignal)
+                        .style(theme::Button::Text)
+                        .into(),
+                );
+
+                children
+            })
+            .spacing(padding::PORTION)
+            .align_items(Alignment::Center)
+            .into()
+        };
+
+        let scheduling = row![
+            text("Niceness"),
+            text_input("0", &self.niceness)
+                .on_input(ProcessesPageMessage::NicenessChanged)
+                .on_submit(ProcessesPageMessage::ApplyNiceness)
+                .width(Length::Fixed(70.)),
+            button(text("Apply"))
+                .on_press(ProcessesPageMessage::ApplyNiceness)
+                .style(theme::Button::Text),
+            horizontal_space(),
+            text("Policy"),
+            pick_list(
+                &SchedulingPolicy::ALL[..],
+                self.scheduling_policy,
+                ProcessesPageMessage::SetSchedulingPolicy
+            )
+            .placeholder("Realtime"),
+        ]
+        .spacing(padding::PORTION)
+        .align_items(Alignment::Center);
+
+        let affinity = column![
+            row![
+                text("CPU affinity"),
+                horizontal_space(),
+                button(text("Apply"))
+                    .on_press(ProcessesPageMessage::ApplyAffinity)
+                    .style(theme::Button::Text),
+            ]
+            .align_items(Alignment::Center),
+            Wrap::with_elements(
+                self.affinity
+                    .iter()
+                    .enumerate()
+                    .map(|(core, allowed)| {
+                        checkbox(format!("{}", core), *allowed)
+                            .on_toggle(move |checked| {
+                                ProcessesPageMessage::ToggleCore(core, checked)
+                            })
+                            .into()
+                    })
+                    .collect(),
+            )
+            .spacing(padding::MAIN as f32)
+            .line_spacing(padding::PORTION as f32),
+        ]
+        .spacing(padding::PORTION);
+
+        let result = match &self.action_result {
+            Some(Ok(message)) => {
+                text(message.clone()).style(theme::Text::Color(custom_theme::GREY_TEXT))
+            }
+            Some(Err(error)) => text(error.clone()).style(theme::Text::Color(custom_theme::DANGER)),
+            None => text(""),
+        };
+
+        container(column![controls, scheduling, affinity, result].spacing(padding::PORTION))
+            .padding(padding::MAIN)
+            .width(Length::Fill)
+            .into()
+    }
+
+    /// Lists the shown columns in order, with buttons to move them, followed by the hidden ones
+    fn columns_menu_view(&self, data: &ProcessesData) -> Element<'_, ProcessesPageMessage> {
+        if !self.show_columns_menu {
+            return column![].into();
+        }
+
+        let hidden_columns = ProcessColumn::ALL
+            .into_iter()
+            .filter(|column| !data.layout.is_shown(*column));
+
+        let mut rows: Vec<Element<ProcessesPageMessage>> = Vec::new();
+
+        for column in data.layout.columns.iter().copied().chain(hidden_columns) {
+            let shown = data.layout.is_shown(column);
+
+            let mut column_row = row![if column == ProcessColumn::Name {
+                // The name can't be hidden
+                checkbox(column.title(), true)
+            } else {
+                checkbox(column.title(), shown)
+                    .on_toggle(move |shown| ProcessesPageMessage::SetColumnShown(column, shown))
+            }]
+            .spacing(padding::PORTION)
+            .align_items(Alignment::Center);
+
+            if shown && column != ProcessColumn::Name {
+                column_row = column_row.push(horizontal_space()).push(
+                    button(bootstrap_icon(BootstrapIcon::ArrowUp))
+                        .on_press(ProcessesPageMessage::MoveColumn(column, -1))
+                        .style(theme::Button::Text),
+                );
+                column_row = column_row.push(
+                    button(bootstrap_icon(BootstrapIcon::ArrowDown))
+                        .on_press(ProcessesPageMessage::MoveColumn(column, 1))
+                        .style(theme::Button::Text),
+                );
+            }
+
+            rows.push(column_row.width(Length::Fixed(250.)).into());
+        }
+
+        if let Some(error) = &self.layout_error {
+            rows.push(
+                text(format!("Couldn't save the columns: {}", error))
+                    .style(theme::Text::Color(custom_theme::DANGER))
+                    .into(),
+            );
+        }
+
+        container(Column::with_children(rows).spacing(padding::PORTION))
+            .padding(padding::MAIN)
             .width(Length::Fill)
-            .padding(padding::MAIN);
+            .into()
+    }
 
-        let processes_header_strings =
-            vec!["Name", "CPU", "Memory", "Disk Read", "Disk Written", "Kill"];
+    pub fn view<'a>(
+        &'a self,
+        preferences: &Preferences,
+        data: &'a ProcessesData,
+        resource_history: &'a ResourceHistory,
+    ) -> Element<'a, ProcessesPageMessage> {
+        let header = container(
+            row![
+                horizontal_space(),
+                text("Processes"),
+                horizontal_space(),
+                checkbox("tree", data.show_tree).on_toggle(ProcessesPageMessage::ToggleTree),
+                button(bootstrap_icon(BootstrapIcon::LayoutThreeColumns))
+                    .on_press(ProcessesPageMessage::ToggleColumnsMenu)
+                    .style(theme::Button::Text),
+            ]
+            .align_items(Alignment::Center),
+        )
+        .center_x()
+        .style(resource_details_header())
+        .width(Length::Fill)
+        .padding(padding::MAIN);
+
+        let all_selected = !data.in_depth.processes.is_empty()
+            && data
+                .in_depth
+                .processes
+                .iter()
+                .all(|process| data.selected.contains_key(&process.pid));
 
         let processes_headers = GridRow::with_elements({
-            let mut elements = Vec::new();
+            let mut elements: Vec<Element<ProcessesPageMessage>> = vec![checkbox("", all_selected)
+                .on_toggle(|checked| {
+                    if checked {
+                        ProcessesPageMessage::SelectAllMatching
+                    } else {
+                        ProcessesPageMessage::ClearSelection
+                    }
+                })
+                .into()];
 
-            for (i, content) in processes_header_strings.iter().enumerate() {
-                if i as u32 == data.sort_index {
+            for column in &data.layout.columns {
+                if *column == data.layout.sort_column {
                     elements.push(
                         button(
                             row![
-                                text(content),
+                                text(column.title()),
                                 // Icon
                                 text(String::from({
-                                    match data.sort_direction {
+                                    match data.layout.sort_direction {
                                         SortDirection::Descending => BootstrapIcon::CaretDownFill,
                                         SortDirection::Ascending => BootstrapIcon::CaretUpFill,
                                     }
@@ -119,20 +725,29 @@ impl ProcessesPage {
                         )
                         .width(Length::Fill)
                         .on_press(ProcessesPageMessage::SwitchSortDirection)
-                        .style(theme::Button::Text),
+                        .style(theme::Button::Text)
+                        .into(),
                     );
 
                     continue;
                 }
 
                 elements.push(
-                    button(*content)
+                    button(column.title())
                         .width(Length::Fill)
-                        .on_press(ProcessesPageMessage::SortByIndex(i as u32))
-                        .style(theme::Button::Text),
+                        .on_press(ProcessesPageMessage::SortByColumn(*column))
+                        .style(theme::Button::Text)
+                        .into(),
                 );
             }
 
+            elements.push(
+                button("Actions")
+                    .width(Length::Fill)
+                    .style(theme::Button::Text)
+                    .into(),
+            );
+
             elements
         });
 
@@ -141,7 +756,10 @@ impl ProcessesPage {
                 let mut rows = Vec::new();
                 rows.push(processes_headers);
 
-                for (i, process_data) in (0_u32..).zip(data.in_depth.processes.iter()) {
+                // (index into processes, depth in the tree)
+                for (i, (index, depth)) in (0_u32..).zip(data.visible_rows()) {
+                    let process_data = &data.in_depth.processes[index];
+
                     let is_odd = i % 2 == 1;
                     // let styler = if is_odd {
                     //     alternate_process_grid_row()
@@ -149,27 +767,185 @@ impl ProcessesPage {
                     //     primary_process_grid_row()
                     // };
 
-                    rows.push(grid_row!(
-                        text(process_data.name.to_string()),
-                        text(format!["{:.2}%", process_data.cpu_usage]),
-                        text(format![
-                            "{:.2} MB",
-                            process_data.memory_usage as f64 / 1024. / 1024.
-                        ]),
-                        text(format![
-                            "{:.2} MB",
-                            process_data.disk_read as f64 / 1024. / 1024.
-                        ]),
-                        text(format![
-                            "{:.2} MB",
-                            process_data.disk_written as f64 / 1024. / 1024.
-                        ]),
-                        button(text("Kill"))
-                            .on_press(ProcessesPageMessage::KillProcess(process_data.pid))
+                    // Parents in the tree also show the usage summed with their descendants
+                    let subtree_totals = if data.show_tree
+                        && data.in_depth.tree_children.contains_key(&process_data.pid)
+                    {
+                        data.in_depth.subtree_totals.get(&process_data.pid)
+                    } else {
+                        None
+                    };
+
+                    let name_button = button(text(process_data.name.to_string()))
+                        .on_press(ProcessesPageMessage::OpenDetails(process_data.pid))
+                        .style(theme::Button::Text);
+
+                    let name: Element<ProcessesPageMessage> = if data.show_tree {
+                        let toggle: Element<ProcessesPageMessage> = if subtree_totals.is_some() {
+                            button(bootstrap_icon(
+                                if data.expanded.contains(&process_data.pid) {
+                                    BootstrapIcon::CaretDownFill
+                                } else {
+                                    BootstrapIcon::CaretRightFill
+                                },
+                            ))
+                            .on_press(ProcessesPageMessage::ToggleExpanded(process_data.pid))
+                            .style(theme::Button::Text)
+                            .into()
+                        } else {
+                            Space::with_width(TREE_TOGGLE_WIDTH).into()
+                        };
+
+                        row![
+                            Space::with_width(depth as f32 * TREE_INDENT),
+                            toggle,
+                            name_button
+                        ]
+                        .align_items(Alignment::Center)
+                        .into()
+                    } else {
+                        name_button.into()
+                    };
+
+                    // Taken by the name column
+                    let mut name = Some(name);
+
+                    let history = resource_history.processes.get(&process_data.pid);
+
+                    let pid = process_data.pid;
+
+                    let mut cells: Vec<Element<ProcessesPageMessage>> =
+                        vec![checkbox("", data.selected.contains_key(&pid))
+                            .on_toggle(move |selected| {
+                                ProcessesPageMessage::ToggleSelected(pid, selected)
+                            })
+                            .into()];
+
+                    for column in &data.layout.columns {
+                        cells.push(match column {
+                            ProcessColumn::Name => name.take().unwrap_or_else(|| text("").into()),
+                            ProcessColumn::Cpu => with_sparkline(
+                                with_total(
+                                    format!("{:.2}%", process_data.cpu_usage),
+                                    subtree_totals
+                                        .map(|totals| format!("{:.2}%", totals.cpu_usage)),
+                                ),
+                                history.map(|history| &history.cpu),
+                                100.,
+                            ),
+                            ProcessColumn::Memory => with_sparkline(
+                                with_total(
+                                    format_bytes(preferences, process_data.memory_usage as f32),
+                                    subtree_totals.map(|totals| {
+                                        format_bytes(preferences, totals.memory_usage as f32)
+                                    }),
+                                ),
+                                history.map(|history| &history.memory),
+                                1.,
+                            ),
+                            ProcessColumn::DiskRead => with_sparkline(
+                                with_total(
+                                    format_rate(preferences, process_data.disk_read_rate),
+                                    subtree_totals.map(|totals| {
+                                        format_rate(preferences, totals.disk_read_rate)
+                                    }),
+                                ),
+                                history.map(|history| &history.disk_read),
+                                1.,
+                            ),
+                            ProcessColumn::DiskWritten => with_sparkline(
+                                with_total(
+                                    format_rate(preferences, process_data.disk_written_rate),
+                                    subtree_totals.map(|totals| {
+                                        format_rate(preferences, totals.disk_written_rate)
+                                    }),
+                                ),
+                                history.map(|history| &history.disk_written),
+                                1.,
+                            ),
+                            // Reading another user's I/O counters requires elevated permissions
+                            ProcessColumn::TotalRead => text(
+                                process_data
+                                    .io
+                                    .map(|io| format_bytes(preferences, io.read_bytes as f32))
+                                    .unwrap_or(String::from("-")),
+                            )
+                            .into(),
+                            ProcessColumn::TotalWritten => text(match process_data.io {
+                                Some(io) if io.cancelled_write_bytes > 0 => format!(
+                                    "{} ({} cancelled)",
+                                    format_bytes(preferences, io.write_bytes as f32),
+                                    format_bytes(preferences, io.cancelled_write_bytes as f32)
+                                ),
+                                Some(io) => format_bytes(preferences, io.write_bytes as f32),
+                                None => String::from("-"),
+                            })
+                            .into(),
+                            ProcessColumn::User => {
+                                text(process_data.user.clone().unwrap_or_default()).into()
+                            }
+                            ProcessColumn::State => text(&process_data.state).into(),
+                            ProcessColumn::Threads => {
+                                text(optional_to_string(process_data.thread_count)).into()
+                            }
+                            ProcessColumn::Nice => {
+                                text(optional_to_string(process_data.niceness)).into()
+                            }
+                            ProcessColumn::Affinity => text(
+                                process_data
+                                    .affinity
+                                    .as_ref()
+                                    .map(|cores| {
+                                        if cores.len() >= self.logical_core_count as usize {
+                                            String::from("All")
+                                        } else {
+                                            format_cores(cores)
+                                        }
+                                    })
+                                    .unwrap_or_default(),
+                            )
+                            .into(),
+                            ProcessColumn::StartTime => {
+                                text(format_unix_time(process_data.start_time)).into()
+                            }
+                            ProcessColumn::CpuTime => text(optional_to_string(
+                                process_data
+                                    .cpu_time
+                                    .map(|cpu_time| format_duration(cpu_time as u64)),
+                            ))
+                            .into(),
+                            ProcessColumn::Command => {
+                                text(truncate(&process_data.command, MAX_COMMAND_LENGTH)).into()
+                            }
+                            ProcessColumn::ParentPid => {
+                                text(optional_to_string(process_data.parent_pid)).into()
+                            }
+                            ProcessColumn::Swap => {
+                                text(optional_to_string(process_data.swap_usage.map(
+                                    |swap_usage| format_bytes(preferences, swap_usage as f32),
+                                )))
+                                .into()
+                            }
+                            ProcessColumn::FileDescriptors => {
+                                text(optional_to_string(process_data.open_file_count)).into()
+                            }
+                        });
+                    }
+
+                    cells.push(
+                        button(bootstrap_icon(BootstrapIcon::ThreeDots))
+                            .on_press(ProcessesPageMessage::ToggleActions(
+                                process_data.pid,
+                                process_data.start_time,
+                                process_data.name.clone(),
+                            ))
                             .style(iced::theme::Button::Custom(Box::new(
                                 styles::button::Primary {},
-                            ))),
-                    ));
+                            )))
+                            .into(),
+                    );
+
+                    rows.push(GridRow::with_elements(cells));
                 }
 
                 rows
@@ -182,8 +958,57 @@ impl ProcessesPage {
         .width(Length::Fill)
         .align_x(alignment::Horizontal::Center);
 
+        let selection: Element<ProcessesPageMessage> = if data.selected.is_empty() {
+            row![].into()
+        } else {
+            row![
+                text(format!("{} selected", data.selected.len())),
+                horizontal_space(),
+                button(text("Actions"))
+                    .on_press(ProcessesPageMessage::ToggleSelectionActions)
+                    .style(iced::theme::Button::Custom(Box::new(
+                        styles::button::Primary {},
+                    ))),
+                button(text("Clear"))
+                    .on_press(ProcessesPageMessage::ClearSelection)
+                    .style(theme::Button::Text),
+            ]
+            .spacing(padding::PORTION)
+            .align_items(Alignment::Center)
+            .into()
+        };
+
+        let filter = container(
+            column![
+                row![
+                    text_input(
+                        "Filter by name, PID, user or command, e.g. cpu>5 mem>200MB user:root",
+                        &data.filter_query,
+                    )
+                    .on_input(ProcessesPageMessage::FilterChanged),
+                    button(text("Select all matching"))
+                        .on_press(ProcessesPageMessage::SelectAllMatching)
+                        .style(theme::Button::Text),
+                ]
+                .spacing(padding::PORTION)
+                .align_items(Alignment::Center),
+                text(data.filter_error.clone().unwrap_or_default())
+                    .style(theme::Text::Color(custom_theme::GREY_TEXT)),
+                selection,
+            ]
+            .spacing(padding::PORTION),
+        )
+        .padding(padding::MAIN)
+        .width(Length::Fill);
+
+        let actions = self.actions_view(data);
+        let columns_menu = self.columns_menu_view(data);
+
         let content = column![
             header,
+            columns_menu,
+            filter,
+            actions,
             scrollable(main).style(iced::theme::Scrollable::Custom(Box::new(
                 styles::scrollable::Background1 {},
             )))
@@ -198,3 +1023,42 @@ impl ProcessesPage {
         container.into() */
     }
 }
+
+/// The value followed by the total of its subtree in parentheses, if there is one
+fn with_total(value: String, total: Option<String>) -> String {
+    match total {
+        Some(total) => format!("{} ({})", value, total),
+        None => value,
+    }
+}
+
+fn optional_to_string<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Shortens text to at most max_length characters, ending it with an ellipsis if it was cut
+fn truncate(text: &str, max_length: usize) -> String {
+    if text.chars().count() <= max_length {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(max_length - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// The formatted value followed by a sparkline of its recent history, if there is any
+fn with_sparkline<'a>(
+    value: String,
+    history: Option<&'a TimeSeries>,
+    min_y_max: f64,
+) -> Element<'a, ProcessesPageMessage> {
+    let Some(history) = history else {
+        return text(value).into();
+    };
+
+    row![text(value), Sparkline::new(history, min_y_max).view()]
+        .spacing(padding::PORTION)
+        .align_items(Alignment::Center)
+        .into()
+}
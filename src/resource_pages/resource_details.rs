@@ -21,7 +21,6 @@ use crate::{
     constants::{
         custom_theme, font_sizes, padding,
         sizings::{self, DEFAULT_CHART_HEIGHT},
-        HISTORY_TICKS,
     },
     general_widgets::{
         icons::bootstrap_icon,
@@ -39,13 +38,16 @@ use crate::{
         },
     },
     utils::{format_bytes, format_hz},
-    ActivePreview, DiskData, ResourceData, ResourceHistory, ResourceType,
+    resources::ResourceRegistry,
+    types::time_series::{TimeSeries, Unit},
+    ActivePreview, DiskData, ResourceData, ResourceType,
 };
 
 use super::{
     applications_page::{ApplicationsPage, ApplicationsPageMessage},
     chart::{ResourceChart, ResourceChartMessage},
     memory_page::{self, MemoryPage, MemoryPageMessage},
+    pressure_charts::PressureCharts,
 };
 
 #[derive(Debug)]
@@ -178,8 +180,13 @@ impl ResourceDetails {
             }
             ResourceType::Memory => {
                 self.memory_details = Some(MemoryPage {
-                    ram_chart: ResourceChart::new(preferences),
-                    swap_chart: ResourceChart::new(preferences),
+                    ram_chart: ResourceChart::new(preferences, &TimeSeries::new(Unit::Percent, preferences.history_retention)),
+                    swap_chart: ResourceChart::new(preferences, &TimeSeries::new(Unit::Percent, preferences.history_retention)),
+                    pressure_charts: PressureCharts::new(
+                        preferences,
+                        &TimeSeries::new(Unit::Percent, preferences.history_retention),
+                        &TimeSeries::new(Unit::Percent, preferences.history_retention),
+                    ),
                 })
             }
             ResourceType::Cpu => {
@@ -189,7 +196,7 @@ impl ResourceDetails {
                     logical_core_count: 0,
                     brand: String::new(),
                     frequency: 0,
-                    cpu_chart: ResourceChart::new(preferences),
+                    cpu_chart: ResourceChart::new(preferences, &TimeSeries::new(Unit::Percent, preferences.history_retention)),
                     logical_core_charts: Vec::new(),
                     logical_cores_usage_percents: Vec::new(),
                     logical_cores_frequencies: Vec::new(),
@@ -210,13 +217,23 @@ impl ResourceDetails {
         logical_cpu_count: u32,
         cpu_brand: String,
         cpu_frequency: u64,
-        resource_history: &ResourceHistory,
+        resources: &ResourceRegistry,
         logical_core_usage_percent: &Vec<f32>,
         logical_cores_frequencies: &Vec<u64>,
         resource_data: &ResourceData,
         preferences: &Preferences,
         active_preview: &ActivePreview,
     ) {
+        // A history series of a resource by name
+        let series = |key: &ActivePreview, name: &str| {
+            resources
+                .get(key)?
+                .history()
+                .into_iter()
+                .find(|(series_name, _)| series_name == name)
+                .map(|(_, series)| series.clone())
+        };
+
         match self.resource {
             ResourceType::Applications => {}
             ResourceType::Processes => {
@@ -285,18 +302,32 @@ impl ResourceDetails {
                     return;
                 };
 
+                let memory_key = ActivePreview {
+                    resource: ResourceType::Memory,
+                    name: None,
+                };
+
                 // RAM usage history
 
-                memory_details.ram_chart.data_points = resource_history.ram.clone();
+                if let Some(series) = series(&memory_key, "ram") {
+                    memory_details.ram_chart.series = series;
+                }
 
                 // Swap usage history
-                memory_details.swap_chart.data_points = resource_history.swap.clone();
+                if let Some(series) = series(&memory_key, "swap") {
+                    memory_details.swap_chart.series = series;
+                }
             }
             ResourceType::Cpu => {
                 let Some(cpu_details) = &mut self.cpu_details else {
                     return;
                 };
 
+                let cpu_key = ActivePreview {
+                    resource: ResourceType::Cpu,
+                    name: None,
+                };
+
                 cpu_details.physical_core_count = physical_cpu_count;
                 cpu_details.logical_core_count = logical_cpu_count;
                 cpu_details.brand = cpu_brand;
@@ -309,14 +340,16 @@ impl ResourceDetails {
                         for _ in 0..logical_cpu_count {
                             cpu_details
                                 .logical_core_charts
-                                .push(ResourceChart::new(preferences));
+                                .push(ResourceChart::new(preferences, &TimeSeries::new(Unit::Percent, preferences.history_retention)));
                         }
                     }
 
                     // update chart data to match current core usage
 
                     for (i, chart) in cpu_details.logical_core_charts.iter_mut().enumerate() {
-                        chart.data_points = resource_history.logical_cores[i].clone();
+                        if let Some(series) = series(&cpu_key, &format!("logical_core_{}", i)) {
+                            chart.series = series;
+                        }
                     }
 
                     cpu_details.logical_cores_usage_percents = logical_core_usage_percent.clone();
@@ -326,7 +359,9 @@ impl ResourceDetails {
 
                     // cpu usage history
 
-                    cpu_details.cpu_chart.data_points = resource_history.cpu.clone();
+                    if let Some(series) = series(&cpu_key, "cpu") {
+                        cpu_details.cpu_chart.series = series;
+                    }
                 }
             }
             _ => {}
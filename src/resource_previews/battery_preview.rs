@@ -18,20 +18,18 @@ use crate::{
 
 use super::{
     preview_widgets::{preview_header, preview_metrics},
-    resource_preview::{ResourcePreview, ResourcePreviewDisplayState, ResourcePreviewMessage},
+    resource_preview::ResourcePreviewMessage,
 };
 
 #[derive(Debug)]
 pub struct BatteryPreview {
     pub resource: ResourceType,
-    pub display_state: ResourcePreviewDisplayState,
 }
 
 impl Default for BatteryPreview {
     fn default() -> Self {
         Self {
             resource: ResourceType::Battery,
-            display_state: ResourcePreviewDisplayState::Shown,
         }
     }
 }
@@ -10,25 +10,23 @@ use sysinfo::{Disk, DiskKind};
 
 use crate::{
     constants::padding, general_widgets::icons::bootstrap_icon, preferences::Preferences, styles,
-    utils::format_bytes, ActivePreview, DiskData, ResourceType,
+    utils::{format_bytes, format_rate}, ActivePreview, DiskData, ResourceType,
 };
 
 use super::{
     preview_widgets::{preview_header, preview_metrics},
-    resource_preview::{ResourcePreview, ResourcePreviewDisplayState, ResourcePreviewMessage},
+    resource_preview::ResourcePreviewMessage,
 };
 
 #[derive(Debug)]
 pub struct DiskPreview {
     pub resource: ResourceType,
-    pub display_state: ResourcePreviewDisplayState,
 }
 
 impl Default for DiskPreview {
     fn default() -> Self {
         Self {
             resource: ResourceType::Disk,
-            display_state: ResourcePreviewDisplayState::Shown,
         }
     }
 }
@@ -58,11 +56,11 @@ impl DiskPreview {
             preview_metrics(vec![
                 (
                     bootstrap_icon(BootstrapIcon::Eye),
-                    text(format_bytes(preferences, data.read as f32)),
+                    text(format_rate(preferences, data.read)),
                 ),
                 (
                     bootstrap_icon(BootstrapIcon::Pen),
-                    text(format_bytes(preferences, data.written as f32)),
+                    text(format_rate(preferences, data.written)),
                 )
             ]),
             progress_bar(0.0..=1., data.space_used as f32 / data.space_total as f32)
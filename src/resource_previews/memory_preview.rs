@@ -10,20 +10,18 @@ use crate::{
 
 use super::{
     preview_widgets::{preview_header, preview_metrics},
-    resource_preview::{ResourcePreview, ResourcePreviewDisplayState, ResourcePreviewMessage},
+    resource_preview::ResourcePreviewMessage,
 };
 
 #[derive(Debug)]
 pub struct MemoryPreview {
     pub resource: ResourceType,
-    pub display_state: ResourcePreviewDisplayState,
 }
 
 impl Default for MemoryPreview {
     fn default() -> Self {
         Self {
             resource: ResourceType::Memory,
-            display_state: ResourcePreviewDisplayState::Shown,
         }
     }
 }
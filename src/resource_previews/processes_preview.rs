@@ -18,7 +18,7 @@ use crate::{
 
 use super::{
     preview_widgets::{preview_header, preview_metrics},
-    resource_preview::{ResourcePreview, ResourcePreviewDisplayState, ResourcePreviewMessage},
+    resource_preview::{ResourcePreviewDisplayState, ResourcePreviewMessage},
 };
 
 #[derive(Debug)]
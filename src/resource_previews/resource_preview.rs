@@ -1,6 +1,5 @@
 use std::ffi::OsString;
 
-use iced::Element;
 
 use crate::{ActivePreview, ResourceType};
 
@@ -14,11 +13,4 @@ pub enum ResourcePreviewDisplayState {
     Shown,
     Hidden,
     Active
-}
-
-pub trait ResourcePreview<Message, OnTickParams> {
-    fn new() -> Self;
-    // need a way to pass data
-    fn on_tick(&mut self, params: OnTickParams);
-    fn view(&self) -> Element<Message>;
 }
\ No newline at end of file
@@ -0,0 +1,152 @@
+use std::time::{Duration, SystemTime};
+
+use iced::{Command, Element};
+
+use crate::{
+    export::Detail,
+    metrics_store::MetricsStore,
+    preferences::Preferences,
+    resource_pages::battery_page::BatteryPage,
+    resource_previews::{
+        battery_preview::BatteryPreview, resource_preview::ResourcePreviewMessage,
+    },
+    types::{
+        resource_data::BatteryData,
+        snapshot::Snapshot,
+        time_series::{TimeSeries, Unit},
+    },
+    ActivePreview, ResourcePageMessage, ResourceType,
+};
+
+use super::{history_key, ResourceCollector};
+
+#[derive(Debug)]
+pub struct BatteryResource {
+    pub data: BatteryData,
+    /// Percent charged
+    pub charge_history: TimeSeries,
+    pub preview: BatteryPreview,
+    /// Only exists while the page is open
+    pub page: Option<BatteryPage>,
+}
+
+impl BatteryResource {
+    pub fn new(history_retention: Duration) -> Self {
+        Self {
+            data: BatteryData::new(),
+            charge_history: TimeSeries::new(Unit::Percent, history_retention),
+            preview: BatteryPreview::new(),
+            page: None,
+        }
+    }
+}
+
+impl ResourceCollector for BatteryResource {
+    fn key(&self) -> ActivePreview {
+        ActivePreview {
+            resource: ResourceType::Battery,
+            name: None,
+        }
+    }
+
+    fn collect(&mut self, snapshot: &Snapshot) {
+        // TODO: should probably have a resource per battery, like disks
+        if let Some(battery) = snapshot.batteries.last() {
+            self.data = battery.clone();
+        }
+    }
+
+    fn push_history(&mut self, timestamp: SystemTime) {
+        self.charge_history
+            .push(timestamp, self.data.state_of_charge.value as f64 * 100.);
+    }
+
+    fn history(&self) -> Vec<(String, &TimeSeries)> {
+        vec![(String::from("battery_charge"), &self.charge_history)]
+    }
+
+    fn details(&self) -> Vec<Detail> {
+        let data = &self.data;
+
+        vec![
+            Detail::text("Vendor", data.vendor.clone()),
+            Detail::text("Model", data.model.clone()),
+            Detail::text("Technology", data.technology.to_string()),
+            Detail::text("State", data.state.to_string()),
+            Detail::number(
+                "Charge",
+                data.state_of_charge.value as f64 * 100.,
+                "percent",
+            ),
+            Detail::number(
+                "Health",
+                data.state_of_health.value as f64 * 100.,
+                "percent",
+            ),
+            Detail::number("Energy", data.energy.value as f64, "J"),
+            Detail::number("Capacity", data.current_capacity.value as f64, "J"),
+            Detail::number(
+                "Designed capacity",
+                data.designed_capacity.value as f64,
+                "J",
+            ),
+            Detail::number("Energy rate", data.energy_rate.value as f64, "W"),
+            Detail::number("Voltage", data.voltage.value as f64, "V"),
+            Detail::optional(
+                "Temperature",
+                data.temperature
+                    .map(|temperature| temperature.value as f64 - 273.15),
+                "°C",
+            ),
+            Detail::optional("Cycles", data.cycles.map(f64::from), ""),
+            Detail::optional(
+                "Time to full or empty",
+                data.time_to_behaviour.map(|time| time.value as f64),
+                "seconds",
+            ),
+        ]
+    }
+
+    fn preview(
+        &self,
+        preferences: &Preferences,
+        active_preview: &ActivePreview,
+    ) -> Element<'_, ResourcePreviewMessage> {
+        self.preview.view(preferences, active_preview, &self.data)
+    }
+
+    fn open_page(&mut self, preferences: &Preferences, store: &mut MetricsStore) {
+        let mut page = BatteryPage::new(preferences, &self.charge_history);
+
+        page.battery_charge_chart.set_long_term(store.rollups(
+            &history_key(&self.key(), "battery_charge"),
+            self.charge_history.unit(),
+        ));
+
+        self.page = Some(page);
+    }
+
+    fn close_page(&mut self) {
+        self.page = None;
+    }
+
+    fn update_page(&mut self, message: ResourcePageMessage) -> Command<ResourcePageMessage> {
+        let (Some(page), ResourcePageMessage::BatteryPageMessage(message)) =
+            (&mut self.page, message)
+        else {
+            return Command::none();
+        };
+
+        page.update(message)
+            .map(ResourcePageMessage::BatteryPageMessage)
+    }
+
+    fn view_page(&self, preferences: &Preferences) -> Element<'_, ResourcePageMessage> {
+        let Some(page) = &self.page else {
+            return iced::widget::text("").into();
+        };
+
+        page.view(preferences, &self.data)
+            .map(ResourcePageMessage::BatteryPageMessage)
+    }
+}
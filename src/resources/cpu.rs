@@ -0,0 +1,347 @@
+use std::time::{Duration, SystemTime};
+
+use iced::{Command, Element};
+
+use crate::{
+    export::Detail,
+    metrics_store::MetricsStore,
+    preferences::Preferences,
+    resource_pages::cpu_page::CpuPage,
+    resource_previews::{cpu_preview::CpuPreview, resource_preview::ResourcePreviewMessage},
+    types::{
+        cpu_info::CpuInfo,
+        cpu_times::CpuState,
+        pressure::SystemPressure,
+        resource_data::CpuData,
+        snapshot::Snapshot,
+        time_series::{TimeSeries, Unit},
+    },
+    ActivePreview, ResourcePageMessage, ResourceType,
+};
+
+use super::{
+    history_key,
+    pressure::{pressure_details, PressureHistory},
+    ResourceCollector,
+};
+
+#[derive(Debug)]
+pub struct CpuResource {
+    pub data: CpuData,
+    pub logical_core_count: u32,
+    pub physical_core_count: u32,
+    pub brand: String,
+    pub info: CpuInfo,
+    /// Percent used
+    pub history: TimeSeries,
+    /// Percent used of each logical core
+    pub logical_cores_history: Vec<TimeSeries>,
+    /// Percent of the time spent in each state but idle. Empty where /proc/stat can't be read
+    pub times_history: Vec<(CpuState, TimeSeries)>,
+    /// Of the hottest package. Empty without temperature sensors
+    pub temperature_history: TimeSeries,
+    /// The 1 minute load average
+    pub load_history: TimeSeries,
+    pub pressure_history: PressureHistory,
+    pub preview: CpuPreview,
+    /// Only exists while the page is open
+    pub page: Option<CpuPage>,
+}
+
+impl CpuResource {
+    pub fn new(
+        logical_core_count: u32,
+        physical_core_count: u32,
+        brand: String,
+        info: CpuInfo,
+        history_retention: Duration,
+    ) -> Self {
+        Self {
+            data: CpuData::new(),
+            logical_core_count,
+            physical_core_count,
+            brand,
+            info,
+            history: TimeSeries::new(Unit::Percent, history_retention),
+            // initialize history for each logical core. Not with vec! since clones share their samples
+            logical_cores_history: (0..logical_core_count)
+                .map(|_| TimeSeries::new(Unit::Percent, history_retention))
+                .collect(),
+            times_history: CpuState::BUSY
+                .iter()
+                .map(|state| (*state, TimeSeries::new(Unit::Percent, history_retention)))
+                .collect(),
+            temperature_history: TimeSeries::new(Unit::Celsius, history_retention),
+            load_history: TimeSeries::new(Unit::Tasks, history_retention),
+            pressure_history: PressureHistory::new(history_retention),
+            preview: CpuPreview::new(),
+            page: None,
+        }
+    }
+}
+
+impl ResourceCollector for CpuResource {
+    fn key(&self) -> ActivePreview {
+        ActivePreview {
+            resource: ResourceType::Cpu,
+            name: None,
+        }
+    }
+
+    fn collect(&mut self, snapshot: &Snapshot) {
+        self.data.update(&snapshot.cpus, self.logical_core_count);
+        self.data.times = snapshot.cpu_times.clone();
+        self.data.temperatures = snapshot.cpu_temperatures.clone();
+
+        let SystemPressure { load, cpu, .. } = snapshot.pressure;
+        self.data.load = load;
+        self.data.pressure = cpu;
+
+        if let (Some(page), Some(package)) = (&mut self.page, self.data.temperatures.package()) {
+            page.temperature_chart.set_thresholds(package.thresholds());
+        }
+    }
+
+    fn push_history(&mut self, timestamp: SystemTime) {
+        self.history
+            .push(timestamp, self.data.cpu_usage_percent as f64);
+
+        for (history, usage_percent) in self
+            .logical_cores_history
+            .iter_mut()
+            .zip(&self.data.logical_cores_usage_percents)
+        {
+            history.push(timestamp, *usage_percent as f64);
+        }
+
+        let times = &self.data.times.total;
+
+        if times.total() > 0 {
+            for (state, history) in &mut self.times_history {
+                history.push(timestamp, times.percent(*state) as f64);
+            }
+        }
+
+        if let Some(package) = self.data.temperatures.package() {
+            self.temperature_history
+                .push(timestamp, package.celsius as f64);
+        }
+
+        if let Some(load) = self.data.load {
+            self.load_history.push(timestamp, load.one);
+        }
+
+        self.pressure_history
+            .push(timestamp, self.data.pressure.as_ref());
+    }
+
+    fn history(&self) -> Vec<(String, &TimeSeries)> {
+        let mut history = vec![(String::from("cpu"), &self.history)];
+
+        for (index, logical_core_history) in self.logical_cores_history.iter().enumerate() {
+            history.push((format!("logical_core_{}", index), logical_core_history));
+        }
+
+        for (state, times_history) in &self.times_history {
+            history.push((format!("time_{}", state.name()), times_history));
+        }
+
+        history.push((String::from("temperature"), &self.temperature_history));
+        history.push((String::from("load"), &self.load_history));
+        history.extend(self.pressure_history.history());
+
+        history
+    }
+
+    fn details(&self) -> Vec<Detail> {
+        let mut details = vec![
+            Detail::text("Brand", self.brand.clone()),
+            Detail::number("Usage", self.data.cpu_usage_percent as f64, "percent"),
+            Detail::number("Frequency", self.data.frequency as f64, "MHz"),
+            Detail::number("Logical cores", self.logical_core_count as f64, ""),
+            Detail::number("Physical cores", self.physical_core_count as f64, ""),
+            Detail::text("Architecture", self.info.architecture.clone()),
+            Detail::optional("Vendor", self.info.vendor.clone(), ""),
+            Detail::optional("Family", self.info.family.map(f64::from), ""),
+            Detail::optional("Model", self.info.model.map(f64::from), ""),
+            Detail::optional("Stepping", self.info.stepping.map(f64::from), ""),
+            Detail::optional("Microcode", self.info.microcode.clone(), ""),
+            Detail::optional(
+                "Min frequency",
+                self.info.min_frequency.map(|frequency| frequency as f64),
+                "MHz",
+            ),
+            Detail::optional(
+                "Max frequency",
+                self.info.max_frequency.map(|frequency| frequency as f64),
+                "MHz",
+            ),
+            Detail::optional(
+                "Base frequency",
+                self.info.base_frequency.map(|frequency| frequency as f64),
+                "MHz",
+            ),
+            Detail::optional("Virtualization", self.info.virtualization.clone(), ""),
+            Detail::optional("Hypervisor", self.info.hypervisor.clone(), ""),
+        ];
+
+        for cache in &self.info.caches {
+            details.push(Detail::number(
+                format!("{} cache", cache.name()),
+                cache.size as f64,
+                "bytes",
+            ));
+        }
+
+        for (name, status) in &self.info.vulnerabilities {
+            details.push(Detail::text(
+                format!("Vulnerability {}", name),
+                status.clone(),
+            ));
+        }
+
+        for (index, (usage, frequency)) in self
+            .data
+            .logical_cores_usage_percents
+            .iter()
+            .zip(&self.data.logical_cores_frequencies)
+            .enumerate()
+        {
+            details.push(Detail::number(
+                format!("Logical core {} usage", index),
+                *usage as f64,
+                "percent",
+            ));
+            details.push(Detail::number(
+                format!("Logical core {} frequency", index),
+                *frequency as f64,
+                "MHz",
+            ));
+        }
+
+        let times = &self.data.times;
+
+        if times.total.total() > 0 {
+            for state in CpuState::ALL {
+                details.push(Detail::number(
+                    format!("{} time", state.title()),
+                    times.total.percent(state) as f64,
+                    "percent",
+                ));
+            }
+
+            for (index, core_times) in times.logical_cores.iter().enumerate() {
+                for state in CpuState::ALL {
+                    details.push(Detail::number(
+                        format!("Logical core {} {} time", index, state.title()),
+                        core_times.percent(state) as f64,
+                        "percent",
+                    ));
+                }
+            }
+        }
+
+        if let Some(load) = self.data.load {
+            details.push(Detail::number("Load 1 minute", load.one, ""));
+            details.push(Detail::number("Load 5 minutes", load.five, ""));
+            details.push(Detail::number("Load 15 minutes", load.fifteen, ""));
+            details.push(Detail::number("Runnable tasks", load.runnable as f64, ""));
+        }
+
+        details.extend(pressure_details("CPU", self.data.pressure.as_ref()));
+
+        let temperatures = &self.data.temperatures;
+
+        for temperature in temperatures.packages.iter().chain(&temperatures.cores) {
+            details.push(Detail::number(
+                format!("{} temperature", temperature.label),
+                temperature.celsius as f64,
+                "celsius",
+            ));
+        }
+
+        details
+    }
+
+    fn preview(
+        &self,
+        preferences: &Preferences,
+        active_preview: &ActivePreview,
+    ) -> Element<'_, ResourcePreviewMessage> {
+        self.preview.view(preferences, active_preview, &self.data)
+    }
+
+    fn open_page(&mut self, preferences: &Preferences, store: &mut MetricsStore) {
+        let mut page = CpuPage::new(
+            preferences,
+            &self.history,
+            &self.logical_cores_history,
+            &self.times_history,
+            &self.temperature_history,
+            &self.load_history,
+            self.pressure_history
+                .charts(preferences, store, &self.key()),
+        );
+
+        // Series are named the same as in history()
+        page.cpu_chart
+            .set_long_term(store.rollups(&history_key(&self.key(), "cpu"), self.history.unit()));
+
+        for (index, (chart, history)) in page
+            .logical_core_charts
+            .iter_mut()
+            .zip(&self.logical_cores_history)
+            .enumerate()
+        {
+            chart.set_long_term(store.rollups(
+                &history_key(&self.key(), &format!("logical_core_{}", index)),
+                history.unit(),
+            ));
+        }
+
+        page.temperature_chart.set_long_term(store.rollups(
+            &history_key(&self.key(), "temperature"),
+            self.temperature_history.unit(),
+        ));
+
+        if let Some(package) = self.data.temperatures.package() {
+            page.temperature_chart.set_thresholds(package.thresholds());
+        }
+
+        page.load_chart.set_long_term(
+            store.rollups(&history_key(&self.key(), "load"), self.load_history.unit()),
+        );
+
+        self.page = Some(page);
+    }
+
+    fn close_page(&mut self) {
+        self.page = None;
+    }
+
+    fn update_page(&mut self, message: ResourcePageMessage) -> Command<ResourcePageMessage> {
+        let (Some(page), ResourcePageMessage::CpuPageMessage(message)) = (&mut self.page, message)
+        else {
+            return Command::none();
+        };
+
+        page.update(message, &mut self.data)
+            .map(ResourcePageMessage::CpuPageMessage)
+    }
+
+    fn view_page(&self, preferences: &Preferences) -> Element<'_, ResourcePageMessage> {
+        let Some(page) = &self.page else {
+            return iced::widget::text("").into();
+        };
+
+        page.view(
+            preferences,
+            &self.data,
+            self.physical_core_count,
+            self.logical_core_count,
+            self.brand.clone(),
+            &self.info,
+        )
+        .map(ResourcePageMessage::CpuPageMessage)
+    }
+}
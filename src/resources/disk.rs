@@ -0,0 +1,197 @@
+use std::time::{Duration, SystemTime};
+
+use iced::{Command, Element};
+
+use crate::{
+    export::Detail,
+    metrics_store::MetricsStore,
+    preferences::Preferences,
+    resource_pages::disk_page::DiskPage,
+    resource_previews::{disk_preview::DiskPreview, resource_preview::ResourcePreviewMessage},
+    types::{
+        resource_data::DiskData,
+        snapshot::Snapshot,
+        time_series::{TimeSeries, Unit},
+    },
+    ActivePreview, ResourcePageMessage, ResourceType,
+};
+
+use super::{
+    history_key,
+    pressure::{pressure_details, PressureHistory},
+    ResourceCollector, ResourceRegistry,
+};
+
+/// A disk, by name. One is registered for every disk found
+#[derive(Debug)]
+pub struct DiskResource {
+    pub data: DiskData,
+    pub read_history: TimeSeries,
+    pub written_history: TimeSeries,
+    /// The registry's I/O pressure, shared by every disk since it's of the whole system
+    pub pressure_history: PressureHistory,
+    pub preview: DiskPreview,
+    /// Only exists while the page is open
+    pub page: Option<DiskPage>,
+}
+
+impl DiskResource {
+    pub fn new(
+        name: String,
+        history_retention: Duration,
+        pressure_history: PressureHistory,
+    ) -> Self {
+        let mut data = DiskData::new();
+        data.name = name;
+
+        Self {
+            data,
+            read_history: TimeSeries::new(Unit::BytesPerSecond, history_retention),
+            written_history: TimeSeries::new(Unit::BytesPerSecond, history_retention),
+            pressure_history,
+            preview: DiskPreview::new(),
+            page: None,
+        }
+    }
+
+    /// Registers disks that aren't yet
+    pub fn discover(snapshot: &Snapshot, registry: &mut ResourceRegistry) {
+        for disk in &snapshot.disks {
+            let key = ActivePreview {
+                resource: ResourceType::Disk,
+                name: Some(disk.name.clone()),
+            };
+
+            if registry.is_registered(&key) {
+                continue;
+            }
+
+            registry.register(Box::new(Self::new(
+                disk.name.clone(),
+                registry.history_retention,
+                registry.io_pressure.clone(),
+            )));
+        }
+    }
+
+    /// Where the shared I/O pressure is stored, as it's of no disk in particular
+    pub fn pressure_key() -> ActivePreview {
+        ActivePreview {
+            resource: ResourceType::Disk,
+            name: None,
+        }
+    }
+}
+
+impl ResourceCollector for DiskResource {
+    fn key(&self) -> ActivePreview {
+        ActivePreview {
+            resource: ResourceType::Disk,
+            name: Some(self.data.name.clone()),
+        }
+    }
+
+    fn collect(&mut self, snapshot: &Snapshot) {
+        // clean old custom resource data that won't necessarily be replaced
+        self.data.clean_in_depth();
+
+        let Some(disk) = snapshot
+            .disks
+            .iter()
+            .find(|disk| disk.name == self.data.name)
+        else {
+            return;
+        };
+
+        self.data.update(disk, snapshot.refresh_interval);
+        self.data.pressure = snapshot.pressure.io;
+
+        if self.page.is_some() {
+            self.data.update_in_depth(disk);
+        }
+    }
+
+    /// The pressure history is pushed and stored by the registry, once for all disks
+    fn push_history(&mut self, timestamp: SystemTime) {
+        self.read_history.push(timestamp, self.data.read);
+        self.written_history.push(timestamp, self.data.written);
+    }
+
+    fn history(&self) -> Vec<(String, &TimeSeries)> {
+        vec![
+            (String::from("disk_read"), &self.read_history),
+            (String::from("disk_write"), &self.written_history),
+        ]
+    }
+
+    fn details(&self) -> Vec<Detail> {
+        let mut details = vec![
+            Detail::text("Name", self.data.name.clone()),
+            Detail::text("Kind", self.data.kind.to_string()),
+            Detail::number("Space used", self.data.space_used as f64, "bytes"),
+            Detail::number("Space total", self.data.space_total as f64, "bytes"),
+            Detail::number("Read", self.data.read, "bytes/s"),
+            Detail::number("Written", self.data.written, "bytes/s"),
+        ];
+
+        if let Some(in_depth) = &self.data.in_depth {
+            details.push(Detail::text("Removable", in_depth.is_removable.to_string()));
+        }
+
+        details.extend(pressure_details("I/O", self.data.pressure.as_ref()));
+
+        details
+    }
+
+    fn preview(
+        &self,
+        preferences: &Preferences,
+        active_preview: &ActivePreview,
+    ) -> Element<'_, ResourcePreviewMessage> {
+        self.preview.view(preferences, active_preview, &self.data)
+    }
+
+    fn open_page(&mut self, preferences: &Preferences, store: &mut MetricsStore) {
+        let mut page = DiskPage::new(
+            preferences,
+            &self.read_history,
+            &self.written_history,
+            self.pressure_history
+                .charts(preferences, store, &Self::pressure_key()),
+        );
+
+        page.read_chart.set_long_term(store.rollups(
+            &history_key(&self.key(), "disk_read"),
+            self.read_history.unit(),
+        ));
+        page.written_chart.set_long_term(store.rollups(
+            &history_key(&self.key(), "disk_write"),
+            self.written_history.unit(),
+        ));
+
+        self.page = Some(page);
+    }
+
+    fn close_page(&mut self) {
+        self.page = None;
+    }
+
+    fn update_page(&mut self, message: ResourcePageMessage) -> Command<ResourcePageMessage> {
+        let (Some(page), ResourcePageMessage::DiskPageMessage(message)) = (&mut self.page, message)
+        else {
+            return Command::none();
+        };
+
+        page.update(message)
+            .map(ResourcePageMessage::DiskPageMessage)
+    }
+
+    fn view_page(&self, preferences: &Preferences) -> Element<'_, ResourcePageMessage> {
+        let Some(page) = &self.page else {
+            return iced::widget::text("").into();
+        };
+
+        page.view(preferences, &self.data)
+            .map(ResourcePageMessage::DiskPageMessage)
+    }
+}
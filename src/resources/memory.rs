@@ -0,0 +1,149 @@
+use std::time::{Duration, SystemTime};
+
+use iced::{Command, Element};
+
+use crate::{
+    export::Detail,
+    metrics_store::MetricsStore,
+    preferences::Preferences,
+    resource_pages::memory_page::MemoryPage,
+    resource_previews::{memory_preview::MemoryPreview, resource_preview::ResourcePreviewMessage},
+    types::{
+        resource_data::MemoryData,
+        snapshot::Snapshot,
+        time_series::{TimeSeries, Unit},
+    },
+    ActivePreview, ResourcePageMessage, ResourceType,
+};
+
+use super::{
+    history_key,
+    pressure::{pressure_details, PressureHistory},
+    ResourceCollector,
+};
+
+#[derive(Debug)]
+pub struct MemoryResource {
+    pub data: MemoryData,
+    /// Percent used
+    pub ram_history: TimeSeries,
+    /// Percent used
+    pub swap_history: TimeSeries,
+    pub pressure_history: PressureHistory,
+    pub preview: MemoryPreview,
+    /// Only exists while the page is open
+    pub page: Option<MemoryPage>,
+}
+
+impl MemoryResource {
+    pub fn new(history_retention: Duration) -> Self {
+        Self {
+            data: MemoryData::new(),
+            ram_history: TimeSeries::new(Unit::Percent, history_retention),
+            swap_history: TimeSeries::new(Unit::Percent, history_retention),
+            pressure_history: PressureHistory::new(history_retention),
+            preview: MemoryPreview::new(),
+            page: None,
+        }
+    }
+}
+
+impl ResourceCollector for MemoryResource {
+    fn key(&self) -> ActivePreview {
+        ActivePreview {
+            resource: ResourceType::Memory,
+            name: None,
+        }
+    }
+
+    fn collect(&mut self, snapshot: &Snapshot) {
+        self.data.update(&snapshot.memory);
+        self.data.pressure = snapshot.pressure.memory;
+    }
+
+    fn push_history(&mut self, timestamp: SystemTime) {
+        self.ram_history
+            .push(timestamp, self.data.ram_usage_percent as f64);
+        self.swap_history
+            .push(timestamp, self.data.swap_usage_percent as f64);
+        self.pressure_history
+            .push(timestamp, self.data.pressure.as_ref());
+    }
+
+    fn history(&self) -> Vec<(String, &TimeSeries)> {
+        let mut history = vec![
+            (String::from("ram"), &self.ram_history),
+            (String::from("swap"), &self.swap_history),
+        ];
+
+        history.extend(self.pressure_history.history());
+
+        history
+    }
+
+    fn details(&self) -> Vec<Detail> {
+        let mut details = vec![
+            Detail::number("RAM used", self.data.ram_usage as f64, "bytes"),
+            Detail::number("RAM total", self.data.ram_total as f64, "bytes"),
+            Detail::number("RAM usage", self.data.ram_usage_percent as f64, "percent"),
+            Detail::number("Swap used", self.data.swap_usage as f64, "bytes"),
+            Detail::number("Swap total", self.data.swap_total as f64, "bytes"),
+            Detail::number("Swap usage", self.data.swap_usage_percent as f64, "percent"),
+        ];
+
+        details.extend(pressure_details("Memory", self.data.pressure.as_ref()));
+
+        details
+    }
+
+    fn preview(
+        &self,
+        preferences: &Preferences,
+        active_preview: &ActivePreview,
+    ) -> Element<'_, ResourcePreviewMessage> {
+        self.preview.view(preferences, active_preview, &self.data)
+    }
+
+    fn open_page(&mut self, preferences: &Preferences, store: &mut MetricsStore) {
+        let mut page = MemoryPage::new(
+            preferences,
+            &self.ram_history,
+            &self.swap_history,
+            self.pressure_history
+                .charts(preferences, store, &self.key()),
+        );
+
+        page.ram_chart.set_long_term(
+            store.rollups(&history_key(&self.key(), "ram"), self.ram_history.unit()),
+        );
+        page.swap_chart.set_long_term(
+            store.rollups(&history_key(&self.key(), "swap"), self.swap_history.unit()),
+        );
+
+        self.page = Some(page);
+    }
+
+    fn close_page(&mut self) {
+        self.page = None;
+    }
+
+    fn update_page(&mut self, message: ResourcePageMessage) -> Command<ResourcePageMessage> {
+        let (Some(page), ResourcePageMessage::MemoryPageMessage(message)) =
+            (&mut self.page, message)
+        else {
+            return Command::none();
+        };
+
+        page.update(message)
+            .map(ResourcePageMessage::MemoryPageMessage)
+    }
+
+    fn view_page(&self, preferences: &Preferences) -> Element<'_, ResourcePageMessage> {
+        let Some(page) = &self.page else {
+            return iced::widget::text("").into();
+        };
+
+        page.view(preferences, &self.data)
+            .map(ResourcePageMessage::MemoryPageMessage)
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    fmt::Debug,
+    time::{Duration, SystemTime},
+};
+
+use iced::{Command, Element};
+
+use crate::{
+    export::Detail,
+    metrics_store::MetricsStore,
+    preferences::Preferences,
+    resource_previews::resource_preview::ResourcePreviewMessage,
+    types::{cpu_info::CpuInfo, snapshot::Snapshot, time_series::TimeSeries},
+    ActivePreview, ResourcePageMessage,
+};
+
+pub mod battery;
+pub mod cpu;
+pub mod disk;
+pub mod memory;
+pub mod pressure;
+
+use pressure::PressureHistory;
+
+/// A resource with a preview in the sidebar and its own page, like the CPU or a disk.
+/// Registered resources are kept up to date from every snapshot, so adding one doesn't mean touching the app
+pub trait ResourceCollector: Debug {
+    /// Identifies the resource. It's also what its preview asks to open
+    fn key(&self) -> ActivePreview;
+
+    /// Updates the data from a new snapshot
+    fn collect(&mut self, snapshot: &Snapshot);
+
+    /// Adds the latest data to the history series
+    fn push_history(&mut self, timestamp: SystemTime);
+
+    /// Every history series by name, like "ram" and "swap"
+    fn history(&self) -> Vec<(String, &TimeSeries)>;
+
+    /// The current values the page shows, for exports
+    fn details(&self) -> Vec<Detail>;
+
+    fn preview(
+        &self,
+        preferences: &Preferences,
+        active_preview: &ActivePreview,
+    ) -> Element<'_, ResourcePreviewMessage>;
+
+    /// Creates the page's state when it is opened. Charts can show long term history from the store
+    fn open_page(&mut self, preferences: &Preferences, store: &mut MetricsStore);
+
+    /// Drops the page's state when another page is opened
+    fn close_page(&mut self);
+
+    /// Handles a message from the page, ignoring ones meant for other pages
+    fn update_page(&mut self, message: ResourcePageMessage) -> Command<ResourcePageMessage>;
+
+    fn view_page(&self, preferences: &Preferences) -> Element<'_, ResourcePageMessage>;
+}
+
+/// Finds resources in a snapshot and registers the ones that aren't yet, like a newly mounted disk
+pub type Discover = fn(&Snapshot, &mut ResourceRegistry);
+
+/// The registered resources, ordered by resource type so the sidebar keeps a stable order
+#[derive(Debug)]
+pub struct ResourceRegistry {
+    resources: Vec<Box<dyn ResourceCollector>>,
+    discoveries: Vec<Discover>,
+    /// For the history series of resources as they're registered
+    pub history_retention: Duration,
+    /// I/O pressure is of the whole system, so it's kept once here and every disk is given a clone
+    pub io_pressure: PressureHistory,
+    /// Long term history of every resource's series
+    store: MetricsStore,
+}
+
+impl ResourceRegistry {
+    /// Registers the built in resources. Their long term history goes to the store
+    pub fn new(
+        logical_core_count: u32,
+        physical_core_count: u32,
+        cpu_brand: String,
+        cpu_info: CpuInfo,
+        history_retention: Duration,
+        store: MetricsStore,
+    ) -> Self {
+        let mut registry = Self {
+            resources: Vec::new(),
+            discoveries: Vec::new(),
+            history_retention,
+            io_pressure: PressureHistory::new(history_retention),
+            store,
+        };
+
+        registry.register(Box::new(cpu::CpuResource::new(
+            logical_core_count,
+            physical_core_count,
+            cpu_brand,
+            cpu_info,
+            history_retention,
+        )));
+        registry.register(Box::new(memory::MemoryResource::new(history_retention)));
+        registry.register(Box::new(battery::BatteryResource::new(history_retention)));
+        registry.register_discovery(disk::DiskResource::discover);
+
+        registry
+    }
+
+    pub fn register(&mut self, resource: Box<dyn ResourceCollector>) {
+        let resource_type = resource.key().resource;
+
+        let index = self
+            .resources
+            .iter()
+            .position(|registered| registered.key().resource > resource_type)
+            .unwrap_or(self.resources.len());
+
+        self.resources.insert(index, resource);
+    }
+
+    pub fn register_discovery(&mut self, discover: Discover) {
+        self.discoveries.push(discover);
+    }
+
+    pub fn is_registered(&self, key: &ActivePreview) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn get(&self, key: &ActivePreview) -> Option<&dyn ResourceCollector> {
+        self.resources
+            .iter()
+            .find(|resource| resource.key() == *key)
+            .map(|resource| resource.as_ref())
+    }
+
+    pub fn get_mut(&mut self, key: &ActivePreview) -> Option<&mut Box<dyn ResourceCollector>> {
+        self.resources
+            .iter_mut()
+            .find(|resource| resource.key() == *key)
+    }
+
+    /// Opens the resource's page, if it's registered
+    pub fn open_page(&mut self, key: &ActivePreview, preferences: &Preferences) -> bool {
+        let Some(resource) = self
+            .resources
+            .iter_mut()
+            .find(|resource| resource.key() == *key)
+        else {
+            return false;
+        };
+
+        resource.open_page(preferences, &mut self.store);
+        true
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn ResourceCollector> {
+        self.resources.iter().map(|resource| resource.as_ref())
+    }
+
+    /// Registers resources in the snapshot that aren't yet
+    pub fn discover(&mut self, snapshot: &Snapshot) {
+        for discover in self.discoveries.clone() {
+            discover(snapshot, self);
+        }
+    }
+
+    /// Registers newly found resources, then updates every resource and its history from the snapshot
+    pub fn collect(&mut self, snapshot: &Snapshot) {
+        self.discover(snapshot);
+
+        self.io_pressure
+            .push(snapshot.timestamp, snapshot.pressure.io.as_ref());
+
+        let pressure_key = disk::DiskResource::pressure_key();
+
+        for (name, series) in self.io_pressure.history() {
+            if let Some(latest) = series.latest() {
+                self.store
+                    .record(&history_key(&pressure_key, &name), series.unit(), latest);
+            }
+        }
+
+        for resource in &mut self.resources {
+            resource.collect(snapshot);
+            resource.push_history(snapshot.timestamp);
+
+            let key = resource.key();
+
+            for (name, series) in resource.history() {
+                if let Some(latest) = series.latest() {
+                    self.store
+                        .record(&history_key(&key, &name), series.unit(), latest);
+                }
+            }
+        }
+    }
+}
+
+/// Identifies a history series of a resource in the store, like "disk.sda.disk_read"
+pub fn history_key(key: &ActivePreview, series: &str) -> String {
+    let resource = format!("{:?}", key.resource).to_lowercase();
+
+    match &key.name {
+        Some(name) => format!("{}.{}.{}", resource, name, series),
+        None => format!("{}.{}", resource, series),
+    }
+}
@@ -0,0 +1,102 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{
+    export::Detail,
+    metrics_store::MetricsStore,
+    preferences::Preferences,
+    resource_pages::pressure_charts::PressureCharts,
+    types::{
+        pressure::Pressure,
+        time_series::{TimeSeries, Unit},
+    },
+    ActivePreview,
+};
+
+use super::history_key;
+
+/// The 10 second averages of a resource's pressure, for the pages that show it. Clones share the series
+#[derive(Debug, Clone)]
+pub struct PressureHistory {
+    /// Percent of time at least one task was stalled
+    pub some: TimeSeries,
+    /// Percent of time every non-idle task was stalled
+    pub full: TimeSeries,
+}
+
+impl PressureHistory {
+    pub fn new(history_retention: Duration) -> Self {
+        Self {
+            some: TimeSeries::new(Unit::Percent, history_retention),
+            full: TimeSeries::new(Unit::Percent, history_retention),
+        }
+    }
+
+    /// Nothing is pushed if the pressure couldn't be read
+    pub fn push(&mut self, timestamp: SystemTime, pressure: Option<&Pressure>) {
+        let Some(pressure) = pressure else {
+            return;
+        };
+
+        self.some.push(timestamp, pressure.some.avg10 as f64);
+
+        if let Some(full) = pressure.full {
+            self.full.push(timestamp, full.avg10 as f64);
+        }
+    }
+
+    /// The series as named in a resource's history
+    pub fn history(&self) -> Vec<(String, &TimeSeries)> {
+        vec![
+            (String::from("pressure_some"), &self.some),
+            (String::from("pressure_full"), &self.full),
+        ]
+    }
+
+    /// Charts of the series, with the long term history the store has for the resource
+    pub fn charts(
+        &self,
+        preferences: &Preferences,
+        store: &mut MetricsStore,
+        key: &ActivePreview,
+    ) -> PressureCharts {
+        let mut charts = PressureCharts::new(preferences, &self.some, &self.full);
+
+        charts
+            .some_chart
+            .set_long_term(store.rollups(&history_key(key, "pressure_some"), self.some.unit()));
+        charts
+            .full_chart
+            .set_long_term(store.rollups(&history_key(key, "pressure_full"), self.full.unit()));
+
+        charts
+    }
+}
+
+/// The averages of a resource's pressure, for exports
+pub fn pressure_details(resource: &str, pressure: Option<&Pressure>) -> Vec<Detail> {
+    let Some(pressure) = pressure else {
+        return Vec::new();
+    };
+
+    let mut details = Vec::new();
+
+    for (kind, averages) in [("some", Some(pressure.some)), ("full", pressure.full)] {
+        let Some(averages) = averages else {
+            continue;
+        };
+
+        for (window, average) in [
+            ("avg10", averages.avg10),
+            ("avg60", averages.avg60),
+            ("avg300", averages.avg300),
+        ] {
+            details.push(Detail::number(
+                format!("{} pressure {} {}", resource, kind, window),
+                average as f64,
+                "percent",
+            ));
+        }
+    }
+
+    details
+}
@@ -0,0 +1,518 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use sysinfo::{
+    CpuRefreshKind, Disks, Groups, MemoryRefreshKind, Pid, ProcessRefreshKind, System, UpdateKind,
+    Users, MINIMUM_CPU_UPDATE_INTERVAL,
+};
+
+use crate::{
+    collector::CollectorRequest,
+    process_control,
+    types::{
+        cpu_info::CpuInfo,
+        cpu_times::CpuStat,
+        cpu_topology::CpuTopology,
+        pressure::SystemPressure,
+        process_columns::ProcessColumn,
+        resource_data::{
+            read_cpu_time, status_field, BatteryData, DiskIo, ProcessDetailsData, ProcessIo,
+        },
+        snapshot::{CpuSample, DiskSample, MemorySample, ProcessExtras, ProcessSample},
+        thermals::CpuTemperatures,
+    },
+};
+
+/// Where the collector reads the system from. Everything sysinfo and battery specific lives behind it,
+/// so the rest of the app only ever sees snapshots
+pub trait SystemSource {
+    /// Reads the CPUs, memory and processes again. The getters return what was read by the last refresh
+    fn refresh(&mut self);
+
+    /// How long to wait between refreshes for usage to mean something
+    fn minimum_refresh_interval(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn physical_core_count(&self) -> u32;
+
+    fn cpu_brand(&self) -> String;
+
+    fn cpu_info(&self) -> CpuInfo;
+
+    /// One per logical core
+    fn cpus(&self) -> Vec<CpuSample>;
+
+    /// Counts the time spent in each state since the previous call
+    fn cpu_times(&mut self) -> CpuStat;
+
+    fn cpu_temperatures(&self) -> CpuTemperatures;
+
+    fn memory(&self) -> MemorySample;
+
+    /// The load average and pressure stall information
+    fn pressure(&self) -> SystemPressure;
+
+    /// Counts the bytes read and written since the previous call
+    fn disks(&mut self) -> Vec<DiskSample>;
+
+    fn batteries(&self) -> Vec<BatteryData>;
+
+    /// Reads the extra values the request's columns need on top of the regular refresh
+    fn processes(&mut self, request: &CollectorRequest) -> HashMap<Pid, ProcessSample>;
+
+    fn process_details(&mut self, pid: Pid) -> ProcessDetailsData;
+}
+
+/// The system we're running on, read through sysinfo, battery and /proc
+pub struct SysinfoSource {
+    system_info: System,
+    users_info: Users,
+    /// Group names by group id
+    group_names: HashMap<u32, String>,
+    /// None if batteries can't be read on this platform
+    battery_manager: Option<battery::Manager>,
+    /// Cgroups by PID, with the start time of the process so reused PIDs are read again
+    cgroups: HashMap<Pid, (u64, Option<String>)>,
+    /// I/O of every block device as of the previous disk read, to count what happened since
+    disk_io: HashMap<String, DiskIo>,
+    /// /proc/stat as of the previous read, to count the time spent since
+    cpu_stat: CpuStat,
+    /// Where procfs is mounted, for what sysinfo doesn't read
+    proc_root: PathBuf,
+    /// Where sysfs is mounted
+    sys_root: PathBuf,
+}
+
+impl SysinfoSource {
+    pub fn new() -> Self {
+        Self::with_roots(PathBuf::from("/proc"), PathBuf::from("/sys"))
+    }
+
+    /// Reads procfs and sysfs from other directories, like copies of another machine's. Only what sysinfo doesn't
+    /// read comes from them, and the nice and affinity columns are always of the live system
+    pub fn with_roots(proc_root: PathBuf, sys_root: PathBuf) -> Self {
+        Self {
+            system_info: System::new_all(),
+            users_info: Users::new_with_refreshed_list(),
+            group_names: Groups::new_with_refreshed_list()
+                .list()
+                .iter()
+                .map(|group| (**group.id(), group.name().to_string()))
+                .collect(),
+            battery_manager: battery::Manager::new().ok(),
+            cgroups: HashMap::new(),
+            disk_io: DiskIo::read_all(&proc_root),
+            cpu_stat: CpuStat::read(&proc_root),
+            proc_root,
+            sys_root,
+        }
+    }
+}
+
+impl SystemSource for SysinfoSource {
+    fn refresh(&mut self) {
+        self.system_info
+            .refresh_cpu_specifics(CpuRefreshKind::new().with_cpu_usage().with_frequency());
+        self.system_info.refresh_processes_specifics(
+            ProcessRefreshKind::new()
+                .with_cpu()
+                .with_memory()
+                .with_disk_usage()
+                .with_user(UpdateKind::Always)
+                .with_exe(UpdateKind::OnlyIfNotSet)
+                .with_cmd(UpdateKind::OnlyIfNotSet),
+        );
+        self.system_info
+            .refresh_memory_specifics(MemoryRefreshKind::new().with_ram().with_swap());
+    }
+
+    fn minimum_refresh_interval(&self) -> Duration {
+        // CPU usage is meaningless when refreshed too soon after the last refresh
+        MINIMUM_CPU_UPDATE_INTERVAL
+    }
+
+    fn physical_core_count(&self) -> u32 {
+        CpuTopology::read(&self.sys_root)
+            .physical_core_count()
+            .or(self
+                .system_info
+                .physical_core_count()
+                .map(|count| count as u32))
+            .unwrap_or(1)
+    }
+
+    fn cpu_brand(&self) -> String {
+        self.system_info.global_cpu_info().brand().to_string()
+    }
+
+    fn cpu_info(&self) -> CpuInfo {
+        CpuInfo::read(&self.proc_root, &self.sys_root)
+    }
+
+    fn cpus(&self) -> Vec<CpuSample> {
+        self.system_info
+            .cpus()
+            .iter()
+            .map(|cpu| CpuSample {
+                usage: cpu.cpu_usage(),
+                frequency: cpu.frequency(),
+            })
+            .collect()
+    }
+
+    fn cpu_times(&mut self) -> CpuStat {
+        let cpu_stat = CpuStat::read(&self.proc_root);
+        let since = cpu_stat.since(&self.cpu_stat);
+
+        self.cpu_stat = cpu_stat;
+
+        since
+    }
+
+    fn cpu_temperatures(&self) -> CpuTemperatures {
+        CpuTemperatures::read(&self.sys_root)
+    }
+
+    fn memory(&self) -> MemorySample {
+        MemorySample {
+            ram_used: self.system_info.used_memory(),
+            ram_total: self.system_info.total_memory(),
+            swap_used: self.system_info.used_swap(),
+            swap_total: self.system_info.total_swap(),
+        }
+    }
+
+    fn pressure(&self) -> SystemPressure {
+        SystemPressure::read(&self.proc_root)
+    }
+
+    fn disks(&mut self) -> Vec<DiskSample> {
+        let disk_io = DiskIo::read_all(&self.proc_root);
+
+        let disks = Disks::new_with_refreshed_list()
+            .iter()
+            .map(|disk| {
+                let name = disk.name().to_str().unwrap_or("default").to_string();
+
+                // Disks that aren't block devices, like tmpfs, have no I/O to count
+                let (read, written) = block_device(&name)
+                    .and_then(|device| Some((disk_io.get(&device)?, self.disk_io.get(&device)?)))
+                    .map(|(current, previous)| {
+                        (
+                            current.read_bytes.saturating_sub(previous.read_bytes),
+                            current.written_bytes.saturating_sub(previous.written_bytes),
+                        )
+                    })
+                    .unwrap_or_default();
+
+                DiskSample {
+                    name,
+                    mount_point: disk.mount_point().to_path_buf(),
+                    kind: disk.kind(),
+                    total_space: disk.total_space(),
+                    available_space: disk.available_space(),
+                    is_removable: disk.is_removable(),
+                    read,
+                    written,
+                }
+            })
+            .collect();
+
+        self.disk_io = disk_io;
+
+        disks
+    }
+
+    fn batteries(&self) -> Vec<BatteryData> {
+        let Some(batteries) = self
+            .battery_manager
+            .as_ref()
+            .and_then(|battery_manager| battery_manager.batteries().ok())
+        else {
+            return Vec::new();
+        };
+
+        batteries
+            .filter_map(|battery| battery.ok())
+            .map(|battery| {
+                let mut battery_data = BatteryData::new();
+                battery_data.update(&battery);
+                battery_data
+            })
+            .collect()
+    }
+
+    fn processes(&mut self, request: &CollectorRequest) -> HashMap<Pid, ProcessSample> {
+        let processes = self.system_info.processes();
+
+        // Values that aren't part of the sysinfo refresh are only read for shown columns, since it means reading
+        // more of /proc for every process
+        let shown = |column| request.process_columns.contains(&column);
+        let read_io = shown(ProcessColumn::TotalRead) || shown(ProcessColumn::TotalWritten);
+        let read_status = shown(ProcessColumn::Threads) || shown(ProcessColumn::Swap);
+
+        if request.cgroups {
+            self.cgroups.retain(|pid, (start_time, _)| {
+                processes
+                    .get(pid)
+                    .is_some_and(|process| process.start_time() == *start_time)
+            });
+        } else {
+            self.cgroups.clear();
+        }
+
+        let mut samples = HashMap::with_capacity(processes.len());
+
+        for (pid, process) in processes {
+            let disk_usage = process.disk_usage();
+
+            let status = if read_status {
+                fs::read_to_string(self.proc_root.join(pid.to_string()).join("status")).ok()
+            } else {
+                None
+            };
+
+            let cgroup = if request.cgroups {
+                self.cgroups
+                    .entry(*pid)
+                    .or_insert_with(|| {
+                        (
+                            process.start_time(),
+                            fs::read_to_string(self.proc_root.join(pid.to_string()).join("cgroup"))
+                                .ok(),
+                        )
+                    })
+                    .1
+                    .clone()
+            } else {
+                None
+            };
+
+            let extras = ProcessExtras {
+                io: read_io
+                    .then(|| ProcessIo::read(&self.proc_root, *pid))
+                    .flatten(),
+                niceness: shown(ProcessColumn::Nice)
+                    .then(|| process_control::niceness(*pid).ok())
+                    .flatten(),
+                affinity: shown(ProcessColumn::Affinity)
+                    .then(|| process_control::affinity(*pid).ok())
+                    .flatten(),
+                thread_count: status
+                    .as_deref()
+                    .and_then(|status| status_field(status, "Threads")),
+                swap_usage: status
+                    .as_deref()
+                    .and_then(|status| status_field::<u64>(status, "VmSwap"))
+                    .map(|kib| kib * 1024),
+                cpu_time: shown(ProcessColumn::CpuTime)
+                    .then(|| read_cpu_time(&self.proc_root, *pid))
+                    .flatten(),
+                open_file_count: shown(ProcessColumn::FileDescriptors)
+                    .then(|| {
+                        fs::read_dir(self.proc_root.join(pid.to_string()).join("fd"))
+                            .ok()
+                            .map(|entries| entries.count())
+                    })
+                    .flatten(),
+            };
+
+            samples.insert(
+                *pid,
+                ProcessSample {
+                    name: process.name().to_string(),
+                    parent_pid: process.parent(),
+                    user: process
+                        .user_id()
+                        .and_then(|user_id| self.users_info.get_user_by_id(user_id))
+                        .map(|user| user.name().to_string()),
+                    command: process.cmd().to_vec(),
+                    exe: process.exe().map(|exe| exe.to_path_buf()),
+                    memory: process.memory(),
+                    cpu_usage: process.cpu_usage(),
+                    disk_read: disk_usage.read_bytes,
+                    disk_written: disk_usage.written_bytes,
+                    state: process.status().to_string(),
+                    start_time: process.start_time(),
+                    cgroup,
+                    extras,
+                },
+            );
+        }
+
+        samples
+    }
+
+    fn process_details(&mut self, pid: Pid) -> ProcessDetailsData {
+        // Details like the environment and cwd aren't part of the regular process refresh
+        self.system_info
+            .refresh_process_specifics(pid, ProcessRefreshKind::everything());
+
+        let mut process_details = ProcessDetailsData::new(pid);
+        process_details.update(
+            &self.system_info,
+            &self.users_info,
+            &self.group_names,
+            &self.proc_root,
+        );
+        process_details
+    }
+}
+
+/// The block device a disk is on as /proc/diskstats names it, like nvme0n1p2 for /dev/nvme0n1p2.
+/// Names like /dev/mapper/root are links to the actual device, like /dev/dm-0
+fn block_device(disk_name: &str) -> Option<String> {
+    let path = Path::new(disk_name);
+
+    if !path.starts_with("/dev") {
+        return None;
+    }
+
+    let device = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    Some(device.file_name()?.to_str()?.to_string())
+}
+
+#[cfg(test)]
+pub use fixture::FixtureSource;
+
+#[cfg(test)]
+mod fixture {
+    use std::{collections::HashMap, fs};
+
+    use serde::Deserialize;
+    use sysinfo::Pid;
+
+    use crate::{
+        collector::CollectorRequest,
+        types::{
+            cpu_info::CpuInfo,
+            cpu_times::CpuStat,
+            pressure::SystemPressure,
+            resource_data::{BatteryData, ProcessDetailsData},
+            serialization,
+            snapshot::{CpuSample, DiskSample, MemorySample, ProcessSample},
+            thermals::CpuTemperatures,
+        },
+        utils::fixture,
+    };
+
+    use super::SystemSource;
+
+    /// What one refresh of a fixture reads
+    #[derive(Debug, Default, Deserialize)]
+    #[serde(default)]
+    pub struct FixtureFrame {
+        pub cpus: Vec<CpuSample>,
+        /// Already the time spent since the previous frame
+        pub cpu_times: CpuStat,
+        pub cpu_temperatures: CpuTemperatures,
+        pub memory: MemorySample,
+        pub pressure: SystemPressure,
+        pub disks: Vec<DiskSample>,
+        pub batteries: Vec<BatteryData>,
+        #[serde(with = "serialization::pid_map")]
+        pub processes: HashMap<Pid, ProcessSample>,
+        /// Returned for whichever PID is asked for
+        pub process_details: Option<ProcessDetailsData>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Fixture {
+        physical_core_count: u32,
+        cpu_brand: String,
+        #[serde(default)]
+        cpu_info: CpuInfo,
+        frames: Vec<FixtureFrame>,
+    }
+
+    /// Replays the frames of a JSON fixture, one per refresh, and keeps reading the last one once they run out
+    #[derive(Debug)]
+    pub struct FixtureSource {
+        fixture: Fixture,
+        /// None until the first refresh
+        frame: Option<usize>,
+    }
+
+    impl FixtureSource {
+        /// Reads tests/fixtures/<name>.json
+        pub fn load(name: &str) -> Self {
+            let path = fixture(&format!("{}.json", name));
+
+            let json = fs::read_to_string(&path).expect("fixture not found");
+
+            Self {
+                fixture: serde_json::from_str(&json).expect("invalid fixture"),
+                frame: None,
+            }
+        }
+
+        fn frame(&self) -> &FixtureFrame {
+            &self.fixture.frames[self.frame.expect("fixture read before its first refresh")]
+        }
+    }
+
+    impl SystemSource for FixtureSource {
+        fn refresh(&mut self) {
+            let last = self.fixture.frames.len() - 1;
+
+            self.frame = Some(self.frame.map_or(0, |frame| (frame + 1).min(last)));
+        }
+
+        fn physical_core_count(&self) -> u32 {
+            self.fixture.physical_core_count
+        }
+
+        fn cpu_brand(&self) -> String {
+            self.fixture.cpu_brand.clone()
+        }
+
+        fn cpu_info(&self) -> CpuInfo {
+            self.fixture.cpu_info.clone()
+        }
+
+        fn cpus(&self) -> Vec<CpuSample> {
+            self.frame().cpus.clone()
+        }
+
+        fn cpu_times(&mut self) -> CpuStat {
+            self.frame().cpu_times.clone()
+        }
+
+        fn cpu_temperatures(&self) -> CpuTemperatures {
+            self.frame().cpu_temperatures.clone()
+        }
+
+        fn memory(&self) -> MemorySample {
+            self.frame().memory
+        }
+
+        fn pressure(&self) -> SystemPressure {
+            self.frame().pressure
+        }
+
+        fn disks(&mut self) -> Vec<DiskSample> {
+            self.frame().disks.clone()
+        }
+
+        fn batteries(&self) -> Vec<BatteryData> {
+            self.frame().batteries.clone()
+        }
+
+        fn processes(&mut self, _request: &CollectorRequest) -> HashMap<Pid, ProcessSample> {
+            self.frame().processes.clone()
+        }
+
+        fn process_details(&mut self, pid: Pid) -> ProcessDetailsData {
+            self.frame()
+                .process_details
+                .clone()
+                .unwrap_or_else(|| ProcessDetailsData::new(pid))
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{read_number, read_trimmed};
+
+use super::cpu_topology::CpuTopology;
+
+/// A level of cache, summed over every instance of it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuCache {
+    pub level: u32,
+    /// Data, Instruction or Unified
+    pub kind: String,
+    /// Bytes, of all instances together
+    pub size: u64,
+    /// How many of it there are, like one per core for L1 or one shared by all cores for L3
+    pub instances: usize,
+}
+
+impl CpuCache {
+    /// Like L1d, L1i or L3
+    pub fn name(&self) -> String {
+        let suffix = match self.kind.as_str() {
+            "Data" => "d",
+            "Instruction" => "i",
+            _ => "",
+        };
+
+        format!("L{}{}", self.level, suffix)
+    }
+}
+
+/// What the CPU is, as opposed to what it's doing. Read once, since none of it changes while running
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CpuInfo {
+    /// Like x86_64 or aarch64
+    pub architecture: String,
+    /// Like GenuineIntel or AuthenticAMD
+    pub vendor: Option<String>,
+    pub family: Option<u32>,
+    pub model: Option<u32>,
+    pub stepping: Option<u32>,
+    pub microcode: Option<String>,
+    /// Smallest level first, with data before instruction caches
+    pub caches: Vec<CpuCache>,
+    /// MHz, the lowest any core can run at
+    pub min_frequency: Option<u64>,
+    /// MHz, the highest any core can boost to
+    pub max_frequency: Option<u64>,
+    /// MHz, the guaranteed frequency. Only some drivers report it
+    pub base_frequency: Option<u64>,
+    /// VT-x or AMD-V, if the CPU supports hardware virtualization
+    pub virtualization: Option<String>,
+    /// Who makes the hypervisor, if we're running in a virtual machine
+    pub hypervisor: Option<String>,
+    /// Known CPU vulnerabilities by name, and whether and how they're mitigated, as the kernel words it
+    pub vulnerabilities: Vec<(String, String)>,
+    #[serde(default)]
+    pub topology: CpuTopology,
+}
+
+impl CpuInfo {
+    /// proc_root and sys_root are where procfs and sysfs are mounted, normally /proc and /sys
+    pub fn read(proc_root: &Path, sys_root: &Path) -> Self {
+        let cpuinfo = fs::read_to_string(proc_root.join("cpuinfo")).unwrap_or_default();
+        let fields = first_processor(&cpuinfo);
+
+        let field = |name: &str| fields.get(name).map(|value| value.to_string());
+        let number = |name: &str| fields.get(name).and_then(|value| value.parse().ok());
+
+        let flags: HashSet<&str> = fields
+            .get("flags")
+            .map(|flags| flags.split_whitespace().collect())
+            .unwrap_or_default();
+
+        let virtualization = if flags.contains("vmx") {
+            Some(String::from("VT-x"))
+        } else if flags.contains("svm") {
+            Some(String::from("AMD-V"))
+        } else {
+            None
+        };
+
+        let cpus = sys_root.join("devices/system/cpu");
+        let frequencies = |file: &str| -> Vec<u64> {
+            logical_cores(&cpus)
+                .iter()
+                .filter_map(|core| read_number::<u64>(&core.join("cpufreq").join(file)))
+                // cpufreq reports kHz
+                .map(|khz| khz / 1000)
+                .collect()
+        };
+
+        Self {
+            architecture: env::consts::ARCH.to_string(),
+            vendor: field("vendor_id"),
+            family: number("cpu family"),
+            model: number("model"),
+            stepping: number("stepping"),
+            microcode: field("microcode"),
+            caches: read_caches(&cpus),
+            min_frequency: frequencies("cpuinfo_min_freq").into_iter().min(),
+            max_frequency: frequencies("cpuinfo_max_freq").into_iter().max(),
+            base_frequency: frequencies("base_frequency").into_iter().max(),
+            virtualization,
+            hypervisor: flags
+                .contains("hypervisor")
+                .then(|| read_hypervisor(sys_root)),
+            vulnerabilities: read_vulnerabilities(&cpus.join("vulnerabilities")),
+            topology: CpuTopology::read(sys_root),
+        }
+    }
+}
+
+/// The fields of the first processor in /proc/cpuinfo, which describe every other one on all but hybrid CPUs
+fn first_processor(cpuinfo: &str) -> HashMap<&str, &str> {
+    cpuinfo
+        .lines()
+        .take_while(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+
+            Some((key.trim(), value.trim()))
+        })
+        .collect()
+}
+
+/// The cpuN directories, in no particular order
+fn logical_cores(cpus: &Path) -> Vec<PathBuf> {
+    fs::read_dir(cpus)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name();
+            let number = name.to_str()?.strip_prefix("cpu")?;
+
+            number
+                .chars()
+                .all(|character| character.is_ascii_digit())
+                .then(|| entry.path())
+        })
+        .collect()
+}
+
+/// Caches shared by several logical cores are listed under each of them, so they're told apart by which cores share them
+fn read_caches(cpus: &Path) -> Vec<CpuCache> {
+    let mut instances = HashSet::new();
+    let mut caches: Vec<CpuCache> = Vec::new();
+
+    for core in logical_cores(cpus) {
+        let Ok(entries) = fs::read_dir(core.join("cache")) else {
+            continue;
+        };
+
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let index = entry.path();
+
+            let (Some(level), Some(kind), Some(size), Some(shared_with)) = (
+                read_number::<u32>(&index.join("level")),
+                read_trimmed(&index.join("type")),
+                read_trimmed(&index.join("size")).and_then(|size| parse_size(&size)),
+                read_trimmed(&index.join("shared_cpu_list")),
+            ) else {
+                continue;
+            };
+
+            if !instances.insert((level, kind.clone(), shared_with)) {
+                continue;
+            }
+
+            match caches
+                .iter_mut()
+                .find(|cache| cache.level == level && cache.kind == kind)
+            {
+                Some(cache) => {
+                    cache.size += size;
+                    cache.instances += 1;
+                }
+                None => caches.push(CpuCache {
+                    level,
+                    kind,
+                    size,
+                    instances: 1,
+                }),
+            }
+        }
+    }
+
+    caches.sort_by(|a, b| a.level.cmp(&b.level).then(a.kind.cmp(&b.kind)));
+    caches
+}
+
+/// Cache sizes look like 32K or 12M
+fn parse_size(size: &str) -> Option<u64> {
+    let (number, multiplier) = match size.chars().last()? {
+        'K' => (&size[..size.len() - 1], 1024),
+        'M' => (&size[..size.len() - 1], 1024 * 1024),
+        'G' => (&size[..size.len() - 1], 1024 * 1024 * 1024),
+        _ => (size, 1),
+    };
+
+    Some(number.parse::<u64>().ok()? * multiplier)
+}
+
+/// Xen says so under /sys/hypervisor. Others are told apart by the system vendor the firmware reports
+fn read_hypervisor(sys_root: &Path) -> String {
+    if let Some(kind) = read_trimmed(&sys_root.join("hypervisor/type")) {
+        return kind;
+    }
+
+    let vendor = read_trimmed(&sys_root.join("class/dmi/id/sys_vendor"));
+    let product = read_trimmed(&sys_root.join("class/dmi/id/product_name"));
+
+    match (vendor.as_deref(), product.as_deref()) {
+        (_, Some("VirtualBox")) | (Some("innotek GmbH"), _) => String::from("VirtualBox"),
+        (Some("Microsoft Corporation"), Some("Virtual Machine")) => String::from("Hyper-V"),
+        (Some("VMware, Inc."), _) => String::from("VMware"),
+        (Some("QEMU"), _) => String::from("KVM/QEMU"),
+        (Some(vendor), _) => vendor.to_string(),
+        (None, _) => String::from("Unknown"),
+    }
+}
+
+fn read_vulnerabilities(directory: &Path) -> Vec<(String, String)> {
+    let mut vulnerabilities: Vec<(String, String)> = fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+
+            Some((
+                entry.file_name().to_str()?.to_string(),
+                read_trimmed(&entry.path())?,
+            ))
+        })
+        .collect();
+
+    vulnerabilities.sort();
+    vulnerabilities
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::fixture;
+
+    use super::{parse_size, CpuCache, CpuInfo};
+
+    fn read(name: &str) -> CpuInfo {
+        CpuInfo::read(
+            &fixture(&format!("proc/{}", name)),
+            &fixture(&format!("sys/{}", name)),
+        )
+    }
+
+    #[test]
+    fn intel_identification_and_features() {
+        let info = read("intel");
+
+        assert_eq!(info.vendor.as_deref(), Some("GenuineIntel"));
+        assert_eq!(
+            (info.family, info.model, info.stepping),
+            (Some(6), Some(154), Some(3))
+        );
+        assert_eq!(info.microcode.as_deref(), Some("0x430"));
+        assert_eq!(info.virtualization.as_deref(), Some("VT-x"));
+        assert_eq!(info.hypervisor, None);
+
+        assert_eq!(info.min_frequency, Some(400));
+        assert_eq!(info.max_frequency, Some(4700));
+        assert_eq!(info.base_frequency, Some(2100));
+    }
+
+    #[test]
+    fn shared_caches_are_counted_once() {
+        let info = read("intel");
+
+        let cache = |name: &str| {
+            info.caches
+                .iter()
+                .find(|cache| cache.name() == name)
+                .cloned()
+        };
+
+        assert_eq!(
+            cache("L1d"),
+            Some(CpuCache {
+                level: 1,
+                kind: String::from("Data"),
+                size: 2 * 48 * 1024,
+                instances: 2,
+            })
+        );
+        assert_eq!(
+            cache("L3").map(|cache| (cache.size, cache.instances)),
+            Some((12 * 1024 * 1024, 1))
+        );
+
+        let names: Vec<String> = info.caches.iter().map(CpuCache::name).collect();
+        assert_eq!(names, vec!["L1d", "L1i", "L2", "L3"]);
+    }
+
+    #[test]
+    fn vulnerabilities_are_sorted_by_name() {
+        let info = read("intel");
+
+        assert_eq!(
+            info.vulnerabilities,
+            vec![
+                (String::from("meltdown"), String::from("Not affected")),
+                (
+                    String::from("spectre_v2"),
+                    String::from("Mitigation: Enhanced / Automatic IBRS")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn virtual_machines_name_their_hypervisor() {
+        let info = read("amd");
+
+        assert_eq!(info.vendor.as_deref(), Some("AuthenticAMD"));
+        assert_eq!(info.virtualization, None);
+        assert_eq!(info.hypervisor.as_deref(), Some("KVM/QEMU"));
+        assert!(info.caches.is_empty());
+        assert_eq!(info.max_frequency, None);
+    }
+
+    #[test]
+    fn cache_sizes() {
+        assert_eq!(parse_size("32K"), Some(32 * 1024));
+        assert_eq!(parse_size("12M"), Some(12 * 1024 * 1024));
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size(""), None);
+    }
+}
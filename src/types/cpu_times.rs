@@ -0,0 +1,287 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// What a logical core spends its time on, in the order /proc/stat lists them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuState {
+    User,
+    /// User mode at a lowered priority
+    Nice,
+    System,
+    Idle,
+    /// Idle while waiting for I/O to complete
+    Iowait,
+    Irq,
+    Softirq,
+    /// Taken by the hypervisor to run other virtual machines
+    Steal,
+}
+
+impl CpuState {
+    pub const ALL: [CpuState; 8] = [
+        CpuState::User,
+        CpuState::Nice,
+        CpuState::System,
+        CpuState::Idle,
+        CpuState::Iowait,
+        CpuState::Irq,
+        CpuState::Softirq,
+        CpuState::Steal,
+    ];
+
+    /// Every state but idle, which stack up to the usage
+    pub const BUSY: [CpuState; 7] = [
+        CpuState::User,
+        CpuState::Nice,
+        CpuState::System,
+        CpuState::Iowait,
+        CpuState::Irq,
+        CpuState::Softirq,
+        CpuState::Steal,
+    ];
+
+    /// As /proc/stat names it, for history and exports
+    pub fn name(&self) -> &'static str {
+        match self {
+            CpuState::User => "user",
+            CpuState::Nice => "nice",
+            CpuState::System => "system",
+            CpuState::Idle => "idle",
+            CpuState::Iowait => "iowait",
+            CpuState::Irq => "irq",
+            CpuState::Softirq => "softirq",
+            CpuState::Steal => "steal",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            CpuState::User => "User",
+            CpuState::Nice => "Nice",
+            CpuState::System => "System",
+            CpuState::Idle => "Idle",
+            CpuState::Iowait => "I/O wait",
+            CpuState::Irq => "IRQ",
+            CpuState::Softirq => "Soft IRQ",
+            CpuState::Steal => "Steal",
+        }
+    }
+}
+
+/// Time spent in each state, in clock ticks. Guest time is already counted as user and nice time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+}
+
+impl CpuTimes {
+    pub fn get(&self, state: CpuState) -> u64 {
+        match state {
+            CpuState::User => self.user,
+            CpuState::Nice => self.nice,
+            CpuState::System => self.system,
+            CpuState::Idle => self.idle,
+            CpuState::Iowait => self.iowait,
+            CpuState::Irq => self.irq,
+            CpuState::Softirq => self.softirq,
+            CpuState::Steal => self.steal,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        CpuState::ALL.iter().map(|state| self.get(*state)).sum()
+    }
+
+    /// Percent of the total time spent in the state. 0 if no time passed
+    pub fn percent(&self, state: CpuState) -> f32 {
+        match self.total() {
+            0 => 0.,
+            total => self.get(state) as f32 * 100. / total as f32,
+        }
+    }
+
+    /// The time spent since the previous times. Counters that went backwards, like after a core came back online,
+    /// count as no time
+    pub fn since(&self, previous: &Self) -> Self {
+        Self {
+            user: self.user.saturating_sub(previous.user),
+            nice: self.nice.saturating_sub(previous.nice),
+            system: self.system.saturating_sub(previous.system),
+            idle: self.idle.saturating_sub(previous.idle),
+            iowait: self.iowait.saturating_sub(previous.iowait),
+            irq: self.irq.saturating_sub(previous.irq),
+            softirq: self.softirq.saturating_sub(previous.softirq),
+            steal: self.steal.saturating_sub(previous.steal),
+        }
+    }
+
+    /// Parses the numbers after the name of a cpu line. Kernels older than 2.6.11 have no steal time
+    fn from_fields<'a>(fields: impl Iterator<Item = &'a str>) -> Option<Self> {
+        let values: Vec<u64> = fields
+            .take(8)
+            .map(|field| field.parse().ok())
+            .collect::<Option<_>>()?;
+
+        if values.len() < 4 {
+            return None;
+        }
+
+        let value = |index: usize| values.get(index).copied().unwrap_or_default();
+
+        Some(Self {
+            user: value(0),
+            nice: value(1),
+            system: value(2),
+            idle: value(3),
+            iowait: value(4),
+            irq: value(5),
+            softirq: value(6),
+            steal: value(7),
+        })
+    }
+}
+
+/// The cpu lines of /proc/stat
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CpuStat {
+    /// Of all logical cores together
+    pub total: CpuTimes,
+    /// One per logical core
+    pub logical_cores: Vec<CpuTimes>,
+}
+
+impl CpuStat {
+    /// proc_root is where procfs is mounted, normally /proc. Empty if /proc/stat can't be read
+    pub fn read(proc_root: &Path) -> Self {
+        fs::read_to_string(proc_root.join("stat"))
+            .map(|stat| Self::from_stat(&stat))
+            .unwrap_or_default()
+    }
+
+    /// Parses the contents of /proc/stat, lines like "cpu0 4705 356 584 3699176 23060 0 277 0 0 0"
+    pub fn from_stat(stat: &str) -> Self {
+        let mut cpu_stat = Self::default();
+
+        for line in stat.lines() {
+            let mut fields = line.split_whitespace();
+
+            let Some(name) = fields.next().and_then(|name| name.strip_prefix("cpu")) else {
+                continue;
+            };
+
+            let Some(times) = CpuTimes::from_fields(fields) else {
+                continue;
+            };
+
+            if name.is_empty() {
+                cpu_stat.total = times;
+            } else {
+                cpu_stat.logical_cores.push(times);
+            }
+        }
+
+        cpu_stat
+    }
+
+    /// The time spent since the previous stat, core by core
+    pub fn since(&self, previous: &Self) -> Self {
+        Self {
+            total: self.total.since(&previous.total),
+            logical_cores: self
+                .logical_cores
+                .iter()
+                .enumerate()
+                .map(|(index, times)| {
+                    times.since(previous.logical_cores.get(index).unwrap_or(times))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CpuStat, CpuState, CpuTimes};
+
+    const STAT: &str = "cpu  800 100 300 7000 500 20 30 250 0 0
+cpu0 400 100 100 3400 400 10 20 50 0 0
+cpu1 400 0 200 3600 100 10 10 200 0 0
+intr 1234 0 0
+ctxt 5678
+btime 1700000000
+";
+
+    #[test]
+    fn parses_total_and_logical_cores() {
+        let stat = CpuStat::from_stat(STAT);
+
+        assert_eq!(
+            stat.total,
+            CpuTimes {
+                user: 800,
+                nice: 100,
+                system: 300,
+                idle: 7000,
+                iowait: 500,
+                irq: 20,
+                softirq: 30,
+                steal: 250,
+            }
+        );
+        assert_eq!(stat.logical_cores.len(), 2);
+        assert_eq!(stat.logical_cores[1].steal, 200);
+    }
+
+    #[test]
+    fn old_kernels_without_steal() {
+        let stat = CpuStat::from_stat("cpu  10 0 5 85\ncpu0 10 0 5 85\n");
+
+        assert_eq!(stat.total.total(), 100);
+        assert_eq!(stat.total.steal, 0);
+        assert_eq!(stat.logical_cores.len(), 1);
+    }
+
+    #[test]
+    fn percents_of_the_time_since_the_previous_stat() {
+        let previous = CpuStat::from_stat(STAT);
+        let current = CpuStat::from_stat(
+            "cpu  900 100 350 7300 530 20 30 270 0 0
+cpu0 450 100 125 3545 430 10 20 50 0 0
+cpu1 450 0 225 3755 100 10 10 220 0 0
+",
+        );
+
+        let since = current.since(&previous);
+
+        assert_eq!(since.total.total(), 500);
+        assert_eq!(since.total.percent(CpuState::User), 20.);
+        assert_eq!(since.total.percent(CpuState::Idle), 60.);
+        assert_eq!(since.total.percent(CpuState::Iowait), 6.);
+        assert_eq!(since.total.percent(CpuState::Steal), 4.);
+
+        assert_eq!(since.logical_cores[0].percent(CpuState::Iowait), 12.);
+        assert_eq!(since.logical_cores[1].percent(CpuState::Steal), 8.);
+
+        let busy: f32 = CpuState::BUSY
+            .iter()
+            .map(|state| since.total.percent(*state))
+            .sum();
+        assert_eq!(busy, 40.);
+    }
+
+    #[test]
+    fn no_time_passed() {
+        let stat = CpuStat::from_stat(STAT);
+        let since = stat.since(&stat);
+
+        assert_eq!(since.total.percent(CpuState::User), 0.);
+    }
+}
@@ -0,0 +1,409 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{read_number, read_trimmed};
+
+/// The two kinds of cores of hybrid CPUs, like Intel's since Alder Lake or ARM's big.LITTLE
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum CoreKind {
+    Performance,
+    Efficiency,
+}
+
+impl CoreKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            CoreKind::Performance => "Performance cores",
+            CoreKind::Efficiency => "Efficiency cores",
+        }
+    }
+}
+
+/// Where a logical core sits in the CPU
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogicalCoreTopology {
+    /// The N of cpuN, which is also its index in the per logical core data
+    pub index: usize,
+    pub package: u32,
+    /// The physical core. Only unique within the package and cluster, some ARM CPUs number each cluster's from 0
+    pub core: u32,
+    /// Cores sharing a cache below the last level, like four E-cores sharing an L2. None on kernels before 5.16
+    pub cluster: Option<u32>,
+    /// None unless the CPU is hybrid
+    pub kind: Option<CoreKind>,
+    /// None on kernels built without NUMA
+    pub node: Option<u32>,
+}
+
+/// A physical core and the logical cores SMT runs on it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhysicalCore {
+    pub core: u32,
+    pub cluster: Option<u32>,
+    /// Indexes of the logical cores, in order
+    pub logical_cores: Vec<usize>,
+}
+
+/// Physical cores of the same package, NUMA node and kind, which are shown together
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoreGroup {
+    pub package: u32,
+    pub node: Option<u32>,
+    pub kind: Option<CoreKind>,
+    /// Ordered by their first logical core
+    pub physical_cores: Vec<PhysicalCore>,
+}
+
+/// How the logical cores are laid out over packages, physical cores, clusters and NUMA nodes, from sysfs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CpuTopology {
+    /// Ordered by index. Empty if sysfs can't be read
+    pub logical_cores: Vec<LogicalCoreTopology>,
+}
+
+impl CpuTopology {
+    /// sys_root is where sysfs is mounted, normally /sys
+    pub fn read(sys_root: &Path) -> Self {
+        let cpus = sys_root.join("devices/system/cpu");
+        let nodes = read_nodes(&sys_root.join("devices/system/node"));
+        let kinds = read_kinds(sys_root);
+
+        let mut logical_cores: Vec<LogicalCoreTopology> = fs::read_dir(&cpus)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let index: usize = entry
+                    .file_name()
+                    .to_str()?
+                    .strip_prefix("cpu")?
+                    .parse()
+                    .ok()?;
+
+                // Offline cores have no topology
+                let topology = entry.path().join("topology");
+
+                Some(LogicalCoreTopology {
+                    index,
+                    package: read_number(&topology.join("physical_package_id"))?,
+                    core: read_number(&topology.join("core_id"))?,
+                    cluster: read_number(&topology.join("cluster_id")),
+                    kind: kinds.get(&index).copied(),
+                    node: nodes.get(&index).copied(),
+                })
+            })
+            .collect();
+
+        logical_cores.sort_by_key(|logical_core| logical_core.index);
+
+        Self { logical_cores }
+    }
+
+    /// None if the topology couldn't be read
+    pub fn physical_core_count(&self) -> Option<u32> {
+        if self.logical_cores.is_empty() {
+            return None;
+        }
+
+        let mut physical_cores: Vec<(u32, Option<u32>, u32)> = self
+            .logical_cores
+            .iter()
+            .map(|logical_core| {
+                (
+                    logical_core.package,
+                    logical_core.cluster,
+                    logical_core.core,
+                )
+            })
+            .collect();
+
+        physical_cores.sort();
+        physical_cores.dedup();
+
+        Some(physical_cores.len() as u32)
+    }
+
+    pub fn package_count(&self) -> usize {
+        self.count_distinct(|logical_core| Some(logical_core.package))
+    }
+
+    /// 0 if the kernel doesn't report NUMA nodes
+    pub fn node_count(&self) -> usize {
+        self.count_distinct(|logical_core| logical_core.node)
+    }
+
+    fn count_distinct(&self, value: impl Fn(&LogicalCoreTopology) -> Option<u32>) -> usize {
+        let mut values: Vec<u32> = self.logical_cores.iter().filter_map(value).collect();
+
+        values.sort();
+        values.dedup();
+        values.len()
+    }
+
+    /// The logical cores grouped for showing them. Without a topology every logical core counts as its own physical core
+    pub fn groups(&self, logical_core_count: usize) -> Vec<CoreGroup> {
+        if self.logical_cores.is_empty() {
+            return vec![CoreGroup {
+                package: 0,
+                node: None,
+                kind: None,
+                physical_cores: (0..logical_core_count)
+                    .map(|index| PhysicalCore {
+                        core: index as u32,
+                        cluster: None,
+                        logical_cores: vec![index],
+                    })
+                    .collect(),
+            }];
+        }
+
+        let mut groups: Vec<CoreGroup> = Vec::new();
+
+        for logical_core in &self.logical_cores {
+            let group = match groups.iter().position(|group| {
+                (group.package, group.node, group.kind)
+                    == (logical_core.package, logical_core.node, logical_core.kind)
+            }) {
+                Some(position) => &mut groups[position],
+                None => {
+                    groups.push(CoreGroup {
+                        package: logical_core.package,
+                        node: logical_core.node,
+                        kind: logical_core.kind,
+                        physical_cores: Vec::new(),
+                    });
+
+                    groups.last_mut().expect("group was just pushed")
+                }
+            };
+
+            // Logical cores are in order, so physical cores end up ordered by their first one
+            match group.physical_cores.iter_mut().find(|physical_core| {
+                (physical_core.core, physical_core.cluster)
+                    == (logical_core.core, logical_core.cluster)
+            }) {
+                Some(physical_core) => physical_core.logical_cores.push(logical_core.index),
+                None => group.physical_cores.push(PhysicalCore {
+                    core: logical_core.core,
+                    cluster: logical_core.cluster,
+                    logical_cores: vec![logical_core.index],
+                }),
+            }
+        }
+
+        groups.sort_by_key(|group| (group.package, group.node, group.kind));
+        groups
+    }
+}
+
+/// The NUMA node of each logical core
+fn read_nodes(nodes: &Path) -> HashMap<usize, u32> {
+    fs::read_dir(nodes)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let node: u32 = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("node")?
+                .parse()
+                .ok()?;
+            let cpus = read_trimmed(&entry.path().join("cpulist"))?;
+
+            Some(
+                parse_cpu_list(&cpus)
+                    .into_iter()
+                    .map(move |cpu| (cpu, node)),
+            )
+        })
+        .flatten()
+        .collect()
+}
+
+/// Intel lists its P-cores and E-cores under separate PMUs. ARM reports a lower capacity for its efficiency cores.
+/// Empty if the CPU isn't hybrid
+fn read_kinds(sys_root: &Path) -> HashMap<usize, CoreKind> {
+    let devices = sys_root.join("devices");
+
+    if let (Some(performance), Some(efficiency)) = (
+        read_trimmed(&devices.join("cpu_core/cpus")),
+        read_trimmed(&devices.join("cpu_atom/cpus")),
+    ) {
+        return parse_cpu_list(&performance)
+            .into_iter()
+            .map(|cpu| (cpu, CoreKind::Performance))
+            .chain(
+                parse_cpu_list(&efficiency)
+                    .into_iter()
+                    .map(|cpu| (cpu, CoreKind::Efficiency)),
+            )
+            .collect();
+    }
+
+    let capacities: HashMap<usize, u32> = fs::read_dir(devices.join("system/cpu"))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let index = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("cpu")?
+                .parse()
+                .ok()?;
+
+            Some((index, read_number(&entry.path().join("cpu_capacity"))?))
+        })
+        .collect();
+
+    let Some(highest) = capacities.values().max().copied() else {
+        return HashMap::new();
+    };
+
+    if capacities.values().all(|capacity| *capacity == highest) {
+        return HashMap::new();
+    }
+
+    capacities
+        .into_iter()
+        .map(|(index, capacity)| {
+            let kind = if capacity == highest {
+                CoreKind::Performance
+            } else {
+                CoreKind::Efficiency
+            };
+
+            (index, kind)
+        })
+        .collect()
+}
+
+/// Lists like "0-3,8,10-11"
+fn parse_cpu_list(list: &str) -> Vec<usize> {
+    list.split(',')
+        .filter_map(|range| {
+            let range = range.trim();
+
+            let (start, end) = match range.split_once('-') {
+                Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+                None => {
+                    let cpu = range.parse().ok()?;
+                    (cpu, cpu)
+                }
+            };
+
+            Some(start..=end)
+        })
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::utils::fixture;
+
+    use super::{parse_cpu_list, CoreKind, CpuTopology};
+
+    fn sys_root(name: &str) -> PathBuf {
+        fixture(&format!("sys/{}", name))
+    }
+
+    #[test]
+    fn hybrid_cpu_groups_smt_siblings_and_kinds() {
+        let topology = CpuTopology::read(&sys_root("intel"));
+
+        assert_eq!(topology.physical_core_count(), Some(3));
+        assert_eq!(topology.package_count(), 1);
+        assert_eq!(topology.node_count(), 1);
+
+        let groups = topology.groups(4);
+        assert_eq!(groups.len(), 2);
+
+        assert_eq!(groups[0].kind, Some(CoreKind::Performance));
+        assert_eq!(groups[0].physical_cores.len(), 1);
+        assert_eq!(groups[0].physical_cores[0].logical_cores, vec![0, 1]);
+
+        assert_eq!(groups[1].kind, Some(CoreKind::Efficiency));
+        let efficiency: Vec<(Vec<usize>, Option<u32>)> = groups[1]
+            .physical_cores
+            .iter()
+            .map(|core| (core.logical_cores.clone(), core.cluster))
+            .collect();
+        assert_eq!(efficiency, vec![(vec![2], Some(8)), (vec![3], Some(8))]);
+    }
+
+    #[test]
+    fn numa_nodes_and_packages() {
+        let topology = CpuTopology::read(&sys_root("amd"));
+
+        // Both packages number their cores from 0
+        assert_eq!(topology.physical_core_count(), Some(2));
+        assert_eq!(topology.package_count(), 2);
+        assert_eq!(topology.node_count(), 2);
+
+        let groups: Vec<(u32, Option<u32>, Vec<usize>)> = topology
+            .groups(4)
+            .into_iter()
+            .map(|group| {
+                (
+                    group.package,
+                    group.node,
+                    group.physical_cores[0].logical_cores.clone(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            groups,
+            vec![(0, Some(0), vec![0, 2]), (1, Some(1), vec![1, 3])]
+        );
+    }
+
+    #[test]
+    fn core_ids_repeat_across_clusters() {
+        let topology = CpuTopology::read(&sys_root("arm"));
+
+        // Each cluster numbers its cores from 0
+        assert_eq!(topology.physical_core_count(), Some(4));
+
+        let groups = topology.groups(4);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].kind, None);
+
+        let physical_cores: Vec<(Vec<usize>, Option<u32>)> = groups[0]
+            .physical_cores
+            .iter()
+            .map(|core| (core.logical_cores.clone(), core.cluster))
+            .collect();
+        assert_eq!(
+            physical_cores,
+            vec![
+                (vec![0], Some(0)),
+                (vec![1], Some(0)),
+                (vec![2], Some(1)),
+                (vec![3], Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn without_sysfs_every_logical_core_stands_alone() {
+        let topology = CpuTopology::read(&sys_root("missing"));
+
+        assert_eq!(topology.physical_core_count(), None);
+
+        let groups = topology.groups(2);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].physical_cores[1].logical_cores, vec![1]);
+    }
+
+    #[test]
+    fn cpu_lists() {
+        assert_eq!(parse_cpu_list("0-3,8,10-11\n"), vec![0, 1, 2, 3, 8, 10, 11]);
+        assert_eq!(parse_cpu_list(""), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,298 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The parts of an XDG .desktop entry needed to recognise an application's processes
+#[derive(Debug, Clone)]
+pub struct DesktopEntry {
+    /// The path under applications/ without .desktop and with / turned into -, e.g. org.mozilla.firefox or
+    /// kde4-dolphin for kde4/dolphin.desktop
+    pub id: String,
+    pub name: String,
+    pub icon: Option<PathBuf>,
+    /// Base name of the executable the entry launches, e.g. firefox
+    pub executable: Option<String>,
+    pub startup_wm_class: Option<String>,
+    pub kind: ApplicationKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ApplicationKind {
+    #[default]
+    Native,
+    Flatpak,
+    Snap,
+}
+
+impl ApplicationKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ApplicationKind::Native => "Native",
+            ApplicationKind::Flatpak => "Flatpak",
+            ApplicationKind::Snap => "Snap",
+        }
+    }
+}
+
+/// Desktop entries with lookups by the ways a process can be matched to them
+#[derive(Debug, Default)]
+pub struct DesktopEntries {
+    pub entries: Vec<DesktopEntry>,
+    by_id: HashMap<String, usize>,
+    by_executable: HashMap<String, usize>,
+    by_wm_class: HashMap<String, usize>,
+}
+
+impl DesktopEntries {
+    /// Reads every desktop entry in the XDG data dirs, including Flatpak and Snap exports.
+    /// Entries earlier in the search path take priority, as per the spec
+    pub fn load() -> Self {
+        Self::load_from(&data_dirs())
+    }
+
+    /// Loads on the executor, to keep reading every entry and looking up icons off the UI thread
+    pub async fn load_in_background() -> Arc<Self> {
+        Arc::new(Self::load())
+    }
+
+    /// Reads the desktop entries in the applications directory of each data dir, in order of priority
+    pub(crate) fn load_from(data_dirs: &[PathBuf]) -> Self {
+        let mut desktop_entries = Self::default();
+
+        for data_dir in data_dirs {
+            let mut files = Vec::new();
+            find_desktop_files(&data_dir.join("applications"), "", &mut files);
+
+            for (id, path) in files {
+                if desktop_entries.by_id.contains_key(&id.to_lowercase()) {
+                    continue;
+                }
+
+                let Some(entry) = parse_desktop_entry(&path, id) else {
+                    continue;
+                };
+
+                desktop_entries.insert(entry);
+            }
+        }
+
+        desktop_entries
+    }
+
+    fn insert(&mut self, entry: DesktopEntry) {
+        let index = self.entries.len();
+
+        self.by_id.insert(entry.id.to_lowercase(), index);
+
+        if let Some(executable) = &entry.executable {
+            self.by_executable
+                .entry(executable.clone())
+                .or_insert(index);
+        }
+
+        if let Some(wm_class) = &entry.startup_wm_class {
+            self.by_wm_class
+                .entry(wm_class.to_lowercase())
+                .or_insert(index);
+        }
+
+        self.entries.push(entry);
+    }
+
+    pub fn find_by_id(&self, id: &str) -> Option<usize> {
+        self.by_id.get(&id.to_lowercase()).copied()
+    }
+
+    /// Finds the entry for an executable or process name, trying the Exec line, then the window class, then the id
+    pub fn find_by_executable(&self, executable: &str) -> Option<usize> {
+        let lowercase = executable.to_lowercase();
+
+        self.by_executable
+            .get(executable)
+            .or_else(|| self.by_wm_class.get(&lowercase))
+            .or_else(|| self.by_id.get(&lowercase))
+            .copied()
+    }
+
+    /// Finds the entry of a snap by its name. Snap entries are named <snap>_<app>.desktop
+    pub fn find_by_snap_name(&self, snap_name: &str) -> Option<usize> {
+        self.find_by_id(&format!("{}_{}", snap_name, snap_name))
+            .or_else(|| self.find_by_executable(snap_name))
+    }
+}
+
+/// $XDG_DATA_HOME followed by $XDG_DATA_DIRS, plus where Flatpak and Snap export their entries
+fn data_dirs() -> Vec<PathBuf> {
+    let home = env::var("HOME").map(PathBuf::from).ok();
+
+    let mut data_dirs = Vec::new();
+
+    match env::var("XDG_DATA_HOME") {
+        Ok(data_home) if !data_home.is_empty() => data_dirs.push(PathBuf::from(data_home)),
+        _ => {
+            if let Some(home) = &home {
+                data_dirs.push(home.join(".local/share"));
+            }
+        }
+    }
+
+    if let Some(home) = &home {
+        data_dirs.push(home.join(".local/share/flatpak/exports/share"));
+    }
+
+    data_dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+    data_dirs.push(PathBuf::from("/var/lib/snapd/desktop"));
+
+    let system_data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|data_dirs| !data_dirs.is_empty())
+        .unwrap_or(String::from("/usr/local/share:/usr/share"));
+
+    for data_dir in system_data_dirs.split(':') {
+        data_dirs.push(PathBuf::from(data_dir));
+    }
+
+    data_dirs
+}
+
+/// Collects the .desktop files in the directory and its subdirectories with their ids, which are prefixed with the
+/// subdirectories they are in
+fn find_desktop_files(directory: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let Some(name) = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+
+        if path.is_dir() {
+            find_desktop_files(&path, &format!("{}{}-", prefix, name), files);
+        } else if let Some(stem) = name.strip_suffix(".desktop") {
+            files.push((format!("{}{}", prefix, stem), path));
+        }
+    }
+}
+
+fn parse_desktop_entry(path: &Path, id: String) -> Option<DesktopEntry> {
+    let ini = ini::Ini::load_from_file_noescape(path).ok()?;
+    let section = ini.section(Some("Desktop Entry"))?;
+
+    if section
+        .get("Type")
+        .is_some_and(|kind| kind != "Application")
+    {
+        return None;
+    }
+
+    // Hidden entries are deleted ones, and NoDisplay ones are mostly helpers that would otherwise claim unrelated processes
+    if section.get("Hidden") == Some("true") || section.get("NoDisplay") == Some("true") {
+        return None;
+    }
+
+    let path_string = path.to_string_lossy();
+
+    let kind = if section.get("X-Flatpak").is_some() || path_string.contains("/flatpak/") {
+        ApplicationKind::Flatpak
+    } else if section.get("X-SnapInstanceName").is_some() || path_string.contains("/snapd/") {
+        ApplicationKind::Snap
+    } else {
+        ApplicationKind::Native
+    };
+
+    Some(DesktopEntry {
+        name: section.get("Name").unwrap_or(&id).to_string(),
+        icon: section.get("Icon").and_then(find_icon),
+        executable: section
+            .get("TryExec")
+            .or(section.get("Exec"))
+            .and_then(exec_base_name),
+        startup_wm_class: section.get("StartupWMClass").map(String::from),
+        kind,
+        id,
+    })
+}
+
+/// The base name of the program an Exec line runs, skipping env and its variable assignments
+fn exec_base_name(exec: &str) -> Option<String> {
+    let program = exec
+        .split_whitespace()
+        .find(|part| *part != "env" && !part.contains('='))?;
+
+    let program = program.trim_matches('"');
+
+    Path::new(program)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+/// Icon sizes to look for in the hicolor theme, in order of preference. Only PNGs are supported
+const ICON_SIZES: [&str; 6] = ["48x48", "64x64", "32x32", "128x128", "256x256", "24x24"];
+
+/// Resolves an Icon value to a PNG file, either directly or through the hicolor theme and pixmaps
+fn find_icon(icon: &str) -> Option<PathBuf> {
+    let path = Path::new(icon);
+
+    if path.is_absolute() {
+        return path.exists().then(|| path.to_path_buf());
+    }
+
+    for data_dir in data_dirs() {
+        for size in ICON_SIZES {
+            let icon_path = data_dir
+                .join("icons/hicolor")
+                .join(size)
+                .join("apps")
+                .join(format!("{}.png", icon));
+
+            if icon_path.exists() {
+                return Some(icon_path);
+            }
+        }
+
+        let pixmap_path = data_dir.join("pixmaps").join(format!("{}.png", icon));
+
+        if pixmap_path.exists() {
+            return Some(pixmap_path);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::fixture;
+
+    use super::DesktopEntries;
+
+    #[test]
+    fn overrides_and_subdirectories() {
+        let xdg = fixture("xdg");
+        let desktop_entries = DesktopEntries::load_from(&[xdg.join("user"), xdg.join("system")]);
+
+        assert_eq!(desktop_entries.entries.len(), 2);
+
+        let nautilus = desktop_entries
+            .find_by_id("org.gnome.Nautilus")
+            .expect("nautilus not found");
+        assert_eq!(desktop_entries.entries[nautilus].name, "Files (override)");
+
+        let dolphin = desktop_entries
+            .find_by_id("kde4-dolphin")
+            .expect("dolphin not found");
+        assert_eq!(desktop_entries.find_by_executable("dolphin"), Some(dolphin));
+    }
+}
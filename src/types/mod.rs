@@ -1 +1,12 @@
-pub mod resource_data;
\ No newline at end of file
+pub mod cpu_info;
+pub mod cpu_times;
+pub mod cpu_topology;
+pub mod desktop_entry;
+pub mod pressure;
+pub mod process_columns;
+pub mod process_filter;
+pub mod resource_data;
+pub mod snapshot;
+pub mod thermals;
+pub mod time_series;
+pub mod serialization;
@@ -0,0 +1,176 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// The average number of tasks running or waiting to run, from /proc/loadavg
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LoadAverage {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+    /// Tasks currently runnable
+    pub runnable: u32,
+    /// Tasks that exist
+    pub total: u32,
+}
+
+impl LoadAverage {
+    /// Parses the contents of /proc/loadavg, like "0.61 0.54 0.45 2/72 10660"
+    pub fn from_loadavg(loadavg: &str) -> Option<Self> {
+        let mut fields = loadavg.split_whitespace();
+
+        let one = fields.next()?.parse().ok()?;
+        let five = fields.next()?.parse().ok()?;
+        let fifteen = fields.next()?.parse().ok()?;
+        let (runnable, total) = fields.next()?.split_once('/')?;
+
+        Some(Self {
+            one,
+            five,
+            fifteen,
+            runnable: runnable.parse().ok()?,
+            total: total.parse().ok()?,
+        })
+    }
+}
+
+/// Percent of time tasks were stalled waiting for a resource, averaged over 10 seconds, 1 minute and 5 minutes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PressureAverages {
+    pub avg10: f32,
+    pub avg60: f32,
+    pub avg300: f32,
+    /// Microseconds stalled since boot
+    pub total: u64,
+}
+
+/// Pressure stall information of one resource
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pressure {
+    /// At least one task was stalled
+    pub some: PressureAverages,
+    /// Every non-idle task was stalled at once, so nothing got done. None for the CPU before Linux 5.13
+    pub full: Option<PressureAverages>,
+}
+
+impl Pressure {
+    /// Parses the contents of a /proc/pressure file, lines like "some avg10=2.19 avg60=3.68 avg300=3.10 total=338492764"
+    pub fn from_pressure(pressure: &str) -> Option<Self> {
+        let mut some = None;
+        let mut full = None;
+
+        for line in pressure.lines() {
+            let mut fields = line.split_whitespace();
+
+            let kind = fields.next();
+            let mut averages = PressureAverages::default();
+
+            for field in fields {
+                let Some((name, value)) = field.split_once('=') else {
+                    continue;
+                };
+
+                match name {
+                    "avg10" => averages.avg10 = value.parse().ok()?,
+                    "avg60" => averages.avg60 = value.parse().ok()?,
+                    "avg300" => averages.avg300 = value.parse().ok()?,
+                    "total" => averages.total = value.parse().ok()?,
+                    _ => {}
+                }
+            }
+
+            match kind {
+                Some("some") => some = Some(averages),
+                Some("full") => full = Some(averages),
+                _ => {}
+            }
+        }
+
+        Some(Self { some: some?, full })
+    }
+}
+
+/// How contended the system is. What can't be read is None, like pressure on kernels built without PSI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SystemPressure {
+    pub load: Option<LoadAverage>,
+    pub cpu: Option<Pressure>,
+    pub memory: Option<Pressure>,
+    pub io: Option<Pressure>,
+}
+
+impl SystemPressure {
+    /// proc_root is where procfs is mounted, normally /proc
+    pub fn read(proc_root: &Path) -> Self {
+        let read = |path: &str| fs::read_to_string(proc_root.join(path)).ok();
+
+        Self {
+            load: read("loadavg").and_then(|loadavg| LoadAverage::from_loadavg(&loadavg)),
+            cpu: read("pressure/cpu").and_then(|pressure| Pressure::from_pressure(&pressure)),
+            memory: read("pressure/memory").and_then(|pressure| Pressure::from_pressure(&pressure)),
+            io: read("pressure/io").and_then(|pressure| Pressure::from_pressure(&pressure)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{LoadAverage, Pressure, PressureAverages, SystemPressure};
+
+    #[test]
+    fn load_average() {
+        assert_eq!(
+            LoadAverage::from_loadavg("0.61 0.54 0.45 2/72 10660\n"),
+            Some(LoadAverage {
+                one: 0.61,
+                five: 0.54,
+                fifteen: 0.45,
+                runnable: 2,
+                total: 72,
+            })
+        );
+        assert_eq!(LoadAverage::from_loadavg(""), None);
+    }
+
+    #[test]
+    fn some_and_full() {
+        let pressure = Pressure::from_pressure(
+            "some avg10=2.20 avg60=0.65 avg300=0.27 total=27576429
+full avg10=1.63 avg60=0.43 avg300=0.19 total=21200951
+",
+        )
+        .expect("pressure not parsed");
+
+        assert_eq!(
+            pressure.some,
+            PressureAverages {
+                avg10: 2.20,
+                avg60: 0.65,
+                avg300: 0.27,
+                total: 27576429,
+            }
+        );
+        assert_eq!(pressure.full.map(|full| full.avg10), Some(1.63));
+    }
+
+    #[test]
+    fn cpu_before_full_was_reported() {
+        let pressure =
+            Pressure::from_pressure("some avg10=0.00 avg60=0.10 avg300=0.05 total=1234\n")
+                .expect("pressure not parsed");
+
+        assert_eq!(pressure.some.avg60, 0.10);
+        assert_eq!(pressure.full, None);
+        assert_eq!(Pressure::from_pressure(""), None);
+    }
+
+    #[test]
+    fn without_procfs() {
+        assert_eq!(
+            SystemPressure::read(Path::new("/nonexistent")),
+            SystemPressure::default()
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use std::{cmp::Ordering, fs};
+
+use ordered_float::OrderedFloat;
+
+use crate::{export::Value, resource_pages::resource_details::SortDirection, utils::config_dir};
+
+use super::resource_data::ProcessData;
+
+/// A column of the process table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProcessColumn {
+    Name,
+    Cpu,
+    Memory,
+    DiskRead,
+    DiskWritten,
+    TotalRead,
+    TotalWritten,
+    User,
+    State,
+    Threads,
+    Nice,
+    Affinity,
+    StartTime,
+    CpuTime,
+    Command,
+    ParentPid,
+    Swap,
+    FileDescriptors,
+}
+
+impl ProcessColumn {
+    /// Every column, in the order they are listed when choosing which to show
+    pub const ALL: [ProcessColumn; 18] = [
+        ProcessColumn::Name,
+        ProcessColumn::Cpu,
+        ProcessColumn::Memory,
+        ProcessColumn::DiskRead,
+        ProcessColumn::DiskWritten,
+        ProcessColumn::TotalRead,
+        ProcessColumn::TotalWritten,
+        ProcessColumn::User,
+        ProcessColumn::State,
+        ProcessColumn::Threads,
+        ProcessColumn::Nice,
+        ProcessColumn::Affinity,
+        ProcessColumn::StartTime,
+        ProcessColumn::CpuTime,
+        ProcessColumn::Command,
+        ProcessColumn::ParentPid,
+        ProcessColumn::Swap,
+        ProcessColumn::FileDescriptors,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ProcessColumn::Name => "Name",
+            ProcessColumn::Cpu => "CPU",
+            ProcessColumn::Memory => "Memory",
+            ProcessColumn::DiskRead => "Disk Read",
+            ProcessColumn::DiskWritten => "Disk Written",
+            ProcessColumn::TotalRead => "Total Read",
+            ProcessColumn::TotalWritten => "Total Written",
+            ProcessColumn::User => "User",
+            ProcessColumn::State => "State",
+            ProcessColumn::Threads => "Threads",
+            ProcessColumn::Nice => "Nice",
+            ProcessColumn::Affinity => "Affinity",
+            ProcessColumn::StartTime => "Started",
+            ProcessColumn::CpuTime => "CPU Time",
+            ProcessColumn::Command => "Command",
+            ProcessColumn::ParentPid => "PPID",
+            ProcessColumn::Swap => "Swap",
+            ProcessColumn::FileDescriptors => "FDs",
+        }
+    }
+
+    /// A stable name for the settings file
+    fn key(&self) -> &'static str {
+        match self {
+            ProcessColumn::Name => "name",
+            ProcessColumn::Cpu => "cpu",
+            ProcessColumn::Memory => "memory",
+            ProcessColumn::DiskRead => "disk_read",
+            ProcessColumn::DiskWritten => "disk_written",
+            ProcessColumn::TotalRead => "total_read",
+            ProcessColumn::TotalWritten => "total_written",
+            ProcessColumn::User => "user",
+            ProcessColumn::State => "state",
+            ProcessColumn::Threads => "threads",
+            ProcessColumn::Nice => "nice",
+            ProcessColumn::Affinity => "affinity",
+            ProcessColumn::StartTime => "start_time",
+            ProcessColumn::CpuTime => "cpu_time",
+            ProcessColumn::Command => "command",
+            ProcessColumn::ParentPid => "ppid",
+            ProcessColumn::Swap => "swap",
+            ProcessColumn::FileDescriptors => "fds",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|column| column.key() == key)
+    }
+
+    /// What the column's exported values are in
+    pub fn unit(&self) -> &'static str {
+        match self {
+            ProcessColumn::Cpu => "percent",
+            ProcessColumn::Memory
+            | ProcessColumn::TotalRead
+            | ProcessColumn::TotalWritten
+            | ProcessColumn::Swap => "bytes",
+            ProcessColumn::DiskRead | ProcessColumn::DiskWritten => "bytes/s",
+            ProcessColumn::StartTime => "unix seconds",
+            ProcessColumn::CpuTime => "seconds",
+            _ => "",
+        }
+    }
+
+    /// The process's value for the column as it's exported, unformatted
+    pub fn value(&self, process: &ProcessData) -> Value {
+        match self {
+            ProcessColumn::Name => Value::Text(process.name.clone()),
+            ProcessColumn::Cpu => Value::Number(process.cpu_usage as f64),
+            ProcessColumn::Memory => Value::Number(process.memory_usage as f64),
+            ProcessColumn::DiskRead => Value::Number(process.disk_read_rate),
+            ProcessColumn::DiskWritten => Value::Number(process.disk_written_rate),
+            ProcessColumn::TotalRead => process.io.map(|io| io.read_bytes as f64).into(),
+            ProcessColumn::TotalWritten => process.io.map(|io| io.write_bytes as f64).into(),
+            ProcessColumn::User => process.user.clone().into(),
+            ProcessColumn::State => Value::Text(process.state.clone()),
+            ProcessColumn::Threads => process.thread_count.map(f64::from).into(),
+            ProcessColumn::Nice => process.niceness.map(f64::from).into(),
+            ProcessColumn::Affinity => process
+                .affinity
+                .as_ref()
+                .map(|cores| {
+                    cores
+                        .iter()
+                        .map(|core| core.to_string())
+                        .collect::<Vec<String>>()
+                        .join(" ")
+                })
+                .into(),
+            ProcessColumn::StartTime => Value::Number(process.start_time as f64),
+            ProcessColumn::CpuTime => process.cpu_time.into(),
+            ProcessColumn::Command => Value::Text(process.command.clone()),
+            ProcessColumn::ParentPid => process.parent_pid.map(|pid| pid.as_u32() as f64).into(),
+            ProcessColumn::Swap => process.swap_usage.map(|bytes| bytes as f64).into(),
+            ProcessColumn::FileDescriptors => {
+                process.open_file_count.map(|count| count as f64).into()
+            }
+        }
+    }
+
+    /// Compares two processes by this column, in ascending order. Unknown values sort first
+    pub fn compare(&self, a: &ProcessData, b: &ProcessData) -> Ordering {
+        match self {
+            ProcessColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            ProcessColumn::Cpu => OrderedFloat(a.cpu_usage).cmp(&OrderedFloat(b.cpu_usage)),
+            ProcessColumn::Memory => a.memory_usage.cmp(&b.memory_usage),
+            ProcessColumn::DiskRead => {
+                OrderedFloat(a.disk_read_rate).cmp(&OrderedFloat(b.disk_read_rate))
+            }
+            ProcessColumn::DiskWritten => {
+                OrderedFloat(a.disk_written_rate).cmp(&OrderedFloat(b.disk_written_rate))
+            }
+            ProcessColumn::TotalRead => {
+                a.io.map(|io| io.read_bytes)
+                    .cmp(&b.io.map(|io| io.read_bytes))
+            }
+            ProcessColumn::TotalWritten => {
+                a.io.map(|io| io.write_bytes)
+                    .cmp(&b.io.map(|io| io.write_bytes))
+            }
+            ProcessColumn::User => a.user.cmp(&b.user),
+            ProcessColumn::State => a.state.cmp(&b.state),
+            ProcessColumn::Threads => a.thread_count.cmp(&b.thread_count),
+            ProcessColumn::Nice => a.niceness.cmp(&b.niceness),
+            ProcessColumn::Affinity => a
+                .affinity
+                .as_ref()
+                .map(|cores| cores.len())
+                .cmp(&b.affinity.as_ref().map(|cores| cores.len())),
+            ProcessColumn::StartTime => a.start_time.cmp(&b.start_time),
+            ProcessColumn::CpuTime => a
+                .cpu_time
+                .map(OrderedFloat)
+                .cmp(&b.cpu_time.map(OrderedFloat)),
+            ProcessColumn::Command => a.command.cmp(&b.command),
+            ProcessColumn::ParentPid => a.parent_pid.cmp(&b.parent_pid),
+            ProcessColumn::Swap => a.swap_usage.cmp(&b.swap_usage),
+            ProcessColumn::FileDescriptors => a.open_file_count.cmp(&b.open_file_count),
+        }
+    }
+}
+
+/// Which columns are shown, in what order, and which one the processes are sorted by.
+/// Saved to the config dir so it is kept across restarts
+#[derive(Debug, Clone)]
+pub struct ProcessColumnLayout {
+    /// The shown columns in display order. Always starts with the name
+    pub columns: Vec<ProcessColumn>,
+    pub sort_column: ProcessColumn,
+    pub sort_direction: SortDirection,
+}
+
+impl Default for ProcessColumnLayout {
+    fn default() -> Self {
+        Self {
+            columns: vec![
+                ProcessColumn::Name,
+                ProcessColumn::Cpu,
+                ProcessColumn::Memory,
+                ProcessColumn::DiskRead,
+                ProcessColumn::DiskWritten,
+                ProcessColumn::TotalRead,
+                ProcessColumn::TotalWritten,
+            ],
+            sort_column: ProcessColumn::Name,
+            sort_direction: SortDirection::default(),
+        }
+    }
+}
+
+const LAYOUT_FILE: &str = "process_columns.ini";
+
+impl ProcessColumnLayout {
+    /// Loads the saved layout, or the default one if there is none or it can't be read
+    pub fn load() -> Self {
+        let mut layout = Self::default();
+
+        let Some(path) = config_dir().map(|config_dir| config_dir.join(LAYOUT_FILE)) else {
+            return layout;
+        };
+
+        let Ok(ini) = ini::Ini::load_from_file(path) else {
+            return layout;
+        };
+
+        let Some(section) = ini.section(Some("processes")) else {
+            return layout;
+        };
+
+        if let Some(columns) = section.get("columns") {
+            layout.columns = vec![ProcessColumn::Name];
+
+            for column in columns
+                .split(',')
+                .filter_map(|key| ProcessColumn::from_key(key.trim()))
+            {
+                if !layout.columns.contains(&column) {
+                    layout.columns.push(column);
+                }
+            }
+        }
+
+        if let Some(sort_column) = section.get("sort_column").and_then(ProcessColumn::from_key) {
+            if layout.columns.contains(&sort_column) {
+                layout.sort_column = sort_column;
+            }
+        }
+
+        layout.sort_direction = match section.get("sort_direction") {
+            Some("ascending") => SortDirection::Ascending,
+            _ => SortDirection::Descending,
+        };
+
+        layout
+    }
+
+    /// Saves the layout, returning why it couldn't be if it fails
+    pub fn save(&self) -> Result<(), String> {
+        let config_dir = config_dir().ok_or(String::from("Couldn't find the config directory"))?;
+
+        fs::create_dir_all(&config_dir).map_err(|error| error.to_string())?;
+
+        let columns: Vec<&str> = self.columns.iter().map(|column| column.key()).collect();
+
+        let mut ini = ini::Ini::new();
+
+        ini.with_section(Some("processes"))
+            .set("columns", columns.join(","))
+            .set("sort_column", self.sort_column.key())
+            .set(
+                "sort_direction",
+                match self.sort_direction {
+                    SortDirection::Ascending => "ascending",
+                    SortDirection::Descending => "descending",
+                },
+            );
+
+        ini.write_to_file(config_dir.join(LAYOUT_FILE))
+            .map_err(|error| error.to_string())
+    }
+
+    pub fn is_shown(&self, column: ProcessColumn) -> bool {
+        self.columns.contains(&column)
+    }
+
+    /// Shows or hides the column. The name is always shown
+    pub fn set_shown(&mut self, column: ProcessColumn, shown: bool) {
+        if column == ProcessColumn::Name {
+            return;
+        }
+
+        if !shown {
+            self.columns.retain(|shown_column| *shown_column != column);
+
+            // Don't keep sorting by something that can't be seen
+            if self.sort_column == column {
+                self.sort_column = ProcessColumn::Name;
+            }
+
+            return;
+        }
+
+        if !self.is_shown(column) {
+            self.columns.push(column);
+        }
+    }
+
+    /// Moves a shown column left (negative offset) or right. The name always stays first
+    pub fn move_column(&mut self, column: ProcessColumn, offset: isize) {
+        let Some(index) = self
+            .columns
+            .iter()
+            .position(|shown_column| *shown_column == column)
+        else {
+            return;
+        };
+
+        let new_index = index.saturating_add_signed(offset);
+
+        if index == 0 || new_index == 0 || new_index >= self.columns.len() {
+            return;
+        }
+
+        let column = self.columns.remove(index);
+        self.columns.insert(new_index, column);
+    }
+}
@@ -0,0 +1,312 @@
+use super::resource_data::ProcessData;
+
+/// A parsed process filter query such as `firefox cpu>5 mem>200MB user:root`.
+/// Every term has to match for a process to be kept
+#[derive(Debug, Clone, Default)]
+pub struct ProcessFilter {
+    terms: Vec<FilterTerm>,
+}
+
+#[derive(Debug, Clone)]
+enum FilterTerm {
+    /// Case insensitive match against the name, PID, user and command line
+    Text(String),
+    Name(String),
+    User(String),
+    Command(String),
+    Pid(u32),
+    Compare(NumericField, Comparison, f64),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum NumericField {
+    /// Percent of a logical core
+    Cpu,
+    /// Bytes
+    Memory,
+    /// Bytes per second
+    DiskRead,
+    /// Bytes per second
+    DiskWritten,
+    Pid,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+}
+
+impl ProcessFilter {
+    /// Parses a whitespace separated query. Terms can be plain text, `key:value` where key is one of
+    /// name, user, cmd or pid, or a comparison like `cpu>5` on cpu, mem, read, written or pid. read and written are per second.
+    /// Byte values accept KB/MB/GB/TB (powers of 1000) and KiB/MiB/GiB/TiB or K/M/G/T (powers of 1024)
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let mut terms = Vec::new();
+
+        for token in query.split_whitespace() {
+            terms.push(parse_term(token)?);
+        }
+
+        Ok(Self { terms })
+    }
+
+    pub fn matches(&self, process: &ProcessData) -> bool {
+        self.terms.iter().all(|term| term_matches(term, process))
+    }
+}
+
+fn parse_term(token: &str) -> Result<FilterTerm, String> {
+    if let Some((key, value)) = token.split_once(':') {
+        let term = match key.to_lowercase().as_str() {
+            "name" => Some(FilterTerm::Name(value.to_lowercase())),
+            "user" => Some(FilterTerm::User(value.to_lowercase())),
+            "cmd" => Some(FilterTerm::Command(value.to_lowercase())),
+            "pid" => Some(FilterTerm::Pid(
+                value
+                    .parse()
+                    .map_err(|_| format!("Invalid PID \"{}\"", value))?,
+            )),
+            _ => None,
+        };
+
+        // Unknown keys are matched as text so things like URLs can still be searched for
+        if let Some(term) = term {
+            return Ok(term);
+        }
+    }
+
+    let Some(operator_start) = token.find(['<', '>', '=']) else {
+        return Ok(FilterTerm::Text(token.to_lowercase()));
+    };
+
+    let key = &token[..operator_start];
+
+    let field = match key.to_lowercase().as_str() {
+        "cpu" => NumericField::Cpu,
+        "mem" | "memory" => NumericField::Memory,
+        "read" => NumericField::DiskRead,
+        "write" | "written" => NumericField::DiskWritten,
+        "pid" => NumericField::Pid,
+        _ => return Ok(FilterTerm::Text(token.to_lowercase())),
+    };
+
+    let rest = &token[operator_start..];
+
+    let (comparison, value) = if let Some(value) = rest.strip_prefix(">=") {
+        (Comparison::GreaterOrEqual, value)
+    } else if let Some(value) = rest.strip_prefix("<=") {
+        (Comparison::LessOrEqual, value)
+    } else if let Some(value) = rest.strip_prefix('>') {
+        (Comparison::Greater, value)
+    } else if let Some(value) = rest.strip_prefix('<') {
+        (Comparison::Less, value)
+    } else if let Some(value) = rest.strip_prefix('=') {
+        (Comparison::Equal, value)
+    } else {
+        return Err(format!("Invalid comparison \"{}\"", token));
+    };
+
+    let value = match field {
+        NumericField::Memory | NumericField::DiskRead | NumericField::DiskWritten => {
+            parse_bytes(value)?
+        }
+        NumericField::Cpu => value
+            .trim_end_matches('%')
+            .parse()
+            .map_err(|_| format!("Invalid percentage \"{}\"", value))?,
+        NumericField::Pid => value
+            .parse()
+            .map_err(|_| format!("Invalid PID \"{}\"", value))?,
+    };
+
+    Ok(FilterTerm::Compare(field, comparison, value))
+}
+
+fn parse_bytes(value: &str) -> Result<f64, String> {
+    let unit_start = value
+        .find(|character: char| !(character.is_ascii_digit() || character == '.'))
+        .unwrap_or(value.len());
+
+    let (number, unit) = value.split_at(unit_start);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid byte amount \"{}\"", value))?;
+
+    let multiplier: f64 = match unit.to_lowercase().as_str() {
+        "" | "b" => 1.,
+        "kb" => 1000.,
+        "mb" => 1000_f64.powi(2),
+        "gb" => 1000_f64.powi(3),
+        "tb" => 1000_f64.powi(4),
+        "k" | "kib" => 1024.,
+        "m" | "mib" => 1024_f64.powi(2),
+        "g" | "gib" => 1024_f64.powi(3),
+        "t" | "tib" => 1024_f64.powi(4),
+        _ => return Err(format!("Unknown byte unit \"{}\"", unit)),
+    };
+
+    Ok(number * multiplier)
+}
+
+fn term_matches(term: &FilterTerm, process: &ProcessData) -> bool {
+    match term {
+        FilterTerm::Text(text) => {
+            process.name.to_lowercase().contains(text)
+                || process.pid.to_string() == *text
+                || process
+                    .user
+                    .as_ref()
+                    .is_some_and(|user| user.to_lowercase().contains(text))
+                || process.command.to_lowercase().contains(text)
+        }
+        FilterTerm::Name(name) => process.name.to_lowercase().contains(name),
+        FilterTerm::User(user) => process
+            .user
+            .as_ref()
+            .is_some_and(|process_user| process_user.to_lowercase() == *user),
+        FilterTerm::Command(command) => process.command.to_lowercase().contains(command),
+        FilterTerm::Pid(pid) => process.pid.as_u32() == *pid,
+        FilterTerm::Compare(field, comparison, value) => {
+            let actual = match field {
+                NumericField::Cpu => process.cpu_usage as f64,
+                NumericField::Memory => process.memory_usage as f64,
+                NumericField::DiskRead => process.disk_read_rate,
+                NumericField::DiskWritten => process.disk_written_rate,
+                NumericField::Pid => process.pid.as_u32() as f64,
+            };
+
+            match comparison {
+                Comparison::Greater => actual > *value,
+                Comparison::GreaterOrEqual => actual >= *value,
+                Comparison::Less => actual < *value,
+                Comparison::LessOrEqual => actual <= *value,
+                Comparison::Equal => actual == *value,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sysinfo::Pid;
+
+    use super::{parse_bytes, ProcessFilter};
+    use crate::types::resource_data::ProcessData;
+
+    fn firefox() -> ProcessData {
+        ProcessData {
+            name: String::from("firefox"),
+            pid: Pid::from_u32(1234),
+            parent_pid: None,
+            user: Some(String::from("Alice")),
+            command: String::from("/usr/lib/firefox/firefox --new-window"),
+            memory_usage: 300_000_000,
+            cpu_usage: 12.5,
+            disk_read_rate: 2048.,
+            disk_written_rate: 0.,
+            io: None,
+            state: String::from("Sleeping"),
+            start_time: 0,
+            niceness: None,
+            affinity: None,
+            thread_count: None,
+            swap_usage: None,
+            cpu_time: None,
+            open_file_count: None,
+        }
+    }
+
+    fn matches(query: &str) -> bool {
+        ProcessFilter::parse(query)
+            .expect("query should parse")
+            .matches(&firefox())
+    }
+
+    #[test]
+    fn comparisons() {
+        assert!(matches("cpu>5"));
+        assert!(matches("cpu>=12.5"));
+        assert!(matches("cpu<=12.5%"));
+        assert!(!matches("cpu<12.5"));
+        assert!(matches("pid=1234"));
+        assert!(!matches("pid>1234"));
+        assert!(matches("read>2KB"));
+        assert!(matches("read>=2K"));
+        assert!(!matches("read>2KiB"));
+        assert!(matches("written=0"));
+    }
+
+    #[test]
+    fn byte_suffixes() {
+        assert_eq!(parse_bytes("200"), Ok(200.));
+        assert_eq!(parse_bytes("200b"), Ok(200.));
+        assert_eq!(parse_bytes("1.5KB"), Ok(1500.));
+        assert_eq!(parse_bytes("2MB"), Ok(2_000_000.));
+        assert_eq!(parse_bytes("1GB"), Ok(1e9));
+        assert_eq!(parse_bytes("1k"), Ok(1024.));
+        assert_eq!(parse_bytes("1MiB"), Ok(1_048_576.));
+        assert_eq!(parse_bytes("1G"), Ok(1_073_741_824.));
+        assert_eq!(parse_bytes("1TiB"), Ok(1024_f64.powi(4)));
+
+        assert!(matches("mem>200MB"));
+        assert!(!matches("mem>300MB"));
+        assert!(!matches("mem>=300MiB"));
+    }
+
+    #[test]
+    fn keyed_terms() {
+        assert!(matches("user:alice"));
+        assert!(!matches("user:ali"));
+        assert!(matches("pid:1234"));
+        assert!(!matches("pid:123"));
+        assert!(matches("name:FIRE"));
+        assert!(matches("cmd:--new-window"));
+        assert!(matches("firefox cpu>5 user:alice"));
+        assert!(!matches("firefox cpu>50"));
+    }
+
+    #[test]
+    fn text_terms() {
+        assert!(matches("Firefox"));
+        assert!(matches("1234"));
+        assert!(matches("alice"));
+        assert!(!matches("chromium"));
+
+        // Unknown keys and fields are searched for as text
+        assert!(!matches("https://example.com"));
+        assert!(!matches("threads>5"));
+        assert!(ProcessFilter::parse("")
+            .expect("empty query")
+            .matches(&firefox()));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            ProcessFilter::parse("pid:abc").unwrap_err(),
+            "Invalid PID \"abc\""
+        );
+        assert_eq!(
+            ProcessFilter::parse("pid>abc").unwrap_err(),
+            "Invalid PID \"abc\""
+        );
+        assert_eq!(
+            ProcessFilter::parse("cpu>lots").unwrap_err(),
+            "Invalid percentage \"lots\""
+        );
+        assert_eq!(
+            ProcessFilter::parse("mem>5XB").unwrap_err(),
+            "Unknown byte unit \"XB\""
+        );
+        assert_eq!(
+            ProcessFilter::parse("mem>MB").unwrap_err(),
+            "Invalid byte amount \"MB\""
+        );
+        assert!(ProcessFilter::parse("firefox mem>").is_err());
+    }
+}
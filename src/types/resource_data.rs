@@ -1,19 +1,48 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use battery::units::{ElectricPotential, Energy, Power, ThermodynamicTemperature};
 use ordered_float::OrderedFloat;
-use sysinfo::{Disk, DiskKind, Pid, System};
+use serde::{Deserialize, Serialize};
+use sysinfo::{DiskKind, Pid, System, Users};
 
-use crate::{resource_pages::resource_details::SortDirection, App};
+use crate::resource_pages::resource_details::SortDirection;
+
+use super::{
+    cpu_times::CpuStat,
+    desktop_entry::{ApplicationKind, DesktopEntries},
+    pressure::{LoadAverage, Pressure},
+    process_columns::ProcessColumnLayout,
+    process_filter::ProcessFilter,
+    serialization::BatteryRecord,
+    snapshot::{CpuSample, DiskSample, MemorySample, ProcessSample, Snapshot},
+    thermals::CpuTemperatures,
+};
 
 #[derive(Debug)]
 pub struct ApplicationsData {
+    /// Loaded in the background the first time the applications page is shown
+    pub desktop_entries: Option<Arc<DesktopEntries>>,
+    /// Whether desktop entries are being loaded, so they're only loaded once
+    pub loading_desktop_entries: bool,
+    /// The desktop entry each process was matched to on its own, by PID and start time so reused PIDs are rematched,
+    /// and whether its cgroup was known. Processes sampled before cgroups were read are matched again once it is.
+    /// Kept outside of in_depth since reading the cgroup of every process each tick is wasteful
+    direct_matches: HashMap<Pid, (u64, bool, Option<usize>)>,
     pub in_depth: InDepthApplicationsData,
 }
 
 impl ApplicationsData {
     pub fn new() -> Self {
         Self {
+            desktop_entries: None,
+            loading_desktop_entries: false,
+            direct_matches: HashMap::new(),
             in_depth: InDepthApplicationsData::new(),
         }
     }
@@ -22,61 +51,223 @@ impl ApplicationsData {
         self.in_depth = InDepthApplicationsData::new();
     }
 
-    pub fn update_in_depth(&mut self, system_info: &mut System) {
-        let mut applications = HashMap::new();
+    /// Does nothing until the desktop entries are loaded
+    pub fn update_in_depth(&mut self, snapshot: &Snapshot) {
+        let Some(desktop_entries) = &self.desktop_entries else {
+            return;
+        };
+
+        let processes = &snapshot.processes;
+
+        self.direct_matches.retain(|pid, (start_time, _, _)| {
+            processes
+                .get(pid)
+                .is_some_and(|process| process.start_time == *start_time)
+        });
+
+        for (pid, process) in processes {
+            let has_cgroup = process.cgroup.is_some();
+
+            let matched = self
+                .direct_matches
+                .get(pid)
+                .is_some_and(|(start_time, had_cgroup, _)| {
+                    *start_time == process.start_time && (*had_cgroup || !has_cgroup)
+                });
+
+            if !matched {
+                self.direct_matches.insert(
+                    *pid,
+                    (
+                        process.start_time,
+                        has_cgroup,
+                        match_desktop_entry(desktop_entries, process),
+                    ),
+                );
+            }
+        }
+
+        // Processes that don't match anything themselves belong to the application of their nearest matched ancestor,
+        // so helpers and child processes are grouped with the app that started them
+        let mut resolved: HashMap<Pid, Option<usize>> = HashMap::new();
+
+        for pid in processes.keys() {
+            let mut chain = Vec::new();
+            let mut current = Some(*pid);
+
+            let entry_index = loop {
+                let Some(current_pid) = current else {
+                    break None;
+                };
+
+                if let Some(entry_index) = resolved.get(&current_pid) {
+                    break *entry_index;
+                }
+
+                if let Some((_, _, Some(entry_index))) = self.direct_matches.get(&current_pid) {
+                    break Some(*entry_index);
+                }
+
+                // Guard against loops in case a PID was reused by a child of its old child
+                if chain.contains(&current_pid) {
+                    break None;
+                }
+
+                chain.push(current_pid);
+                current = processes
+                    .get(&current_pid)
+                    .and_then(|process| process.parent_pid);
+            };
+
+            for chain_pid in chain {
+                resolved.insert(chain_pid, entry_index);
+            }
+
+            resolved.insert(*pid, entry_index);
+        }
+
+        let mut applications: HashMap<usize, ApplicationData> = HashMap::new();
+
+        for (pid, entry_index) in resolved {
+            let (Some(entry_index), Some(process)) = (entry_index, processes.get(&pid)) else {
+                continue;
+            };
+
+            let application = applications.entry(entry_index).or_insert_with(|| {
+                let entry = &desktop_entries.entries[entry_index];
 
-        for (pid, process) in system_info.processes() {
-            applications.insert(
-                *pid,
                 ApplicationData {
-                    name: process.name().to_string(),
-                    pid: *pid,
-                    parent_pid: process.parent(),
-                    memory_usage: process.memory(),
-                    cpu_usage: process.cpu_usage(),
-                },
-            );
+                    id: entry.id.clone(),
+                    name: entry.name.clone(),
+                    icon: entry.icon.clone(),
+                    kind: entry.kind,
+                    processes: Vec::new(),
+                    memory_usage: 0,
+                    cpu_usage: 0.,
+                    disk_read_rate: 0.,
+                    disk_written_rate: 0.,
+                }
+            });
+
+            application.processes.push((pid, process.start_time));
+            application.memory_usage += process.memory;
+            application.cpu_usage += process.cpu_usage;
+            application.disk_read_rate += snapshot.rate(process.disk_read);
+            application.disk_written_rate += snapshot.rate(process.disk_written);
         }
 
+        let mut applications: Vec<ApplicationData> = applications.into_values().collect();
+
+        applications.sort_by(|a, b| {
+            OrderedFloat(b.cpu_usage)
+                .cmp(&OrderedFloat(a.cpu_usage))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
         self.in_depth.applications = applications;
     }
 }
 
+/// Matches a process to a desktop entry by its systemd scope, which Flatpak, Snap and most desktops launch apps in,
+/// falling back to its executable and then its name
+fn match_desktop_entry(desktop_entries: &DesktopEntries, process: &ProcessSample) -> Option<usize> {
+    if let Some(cgroup) = &process.cgroup {
+        if let Some(entry_index) = cgroup
+            .split(['/', '\n'])
+            .find_map(|unit| match_scope(desktop_entries, unit))
+        {
+            return Some(entry_index);
+        }
+    }
+
+    if let Some(executable) = process.exe.as_ref().and_then(|exe| exe.file_name()) {
+        if let Some(entry_index) = desktop_entries.find_by_executable(&executable.to_string_lossy())
+        {
+            return Some(entry_index);
+        }
+    }
+
+    desktop_entries.find_by_executable(&process.name)
+}
+
+/// Matches systemd units like app-flatpak-org.mozilla.firefox-1234.scope, app-gnome-org.gnome.Nautilus-1234.scope
+/// and snap.firefox.firefox-1234.scope
+fn match_scope(desktop_entries: &DesktopEntries, unit: &str) -> Option<usize> {
+    let unit = unit.strip_suffix(".scope")?;
+
+    if let Some(snap) = unit.strip_prefix("snap.") {
+        let snap_name = snap.split('.').next()?;
+        return desktop_entries.find_by_snap_name(snap_name);
+    }
+
+    let (_launcher, application) = unit.strip_prefix("app-")?.split_once('-')?;
+    // Drop the random suffix that makes the scope unique
+    let (application_id, _) = application.rsplit_once('-')?;
+
+    desktop_entries.find_by_id(&application_id.replace("\\x2d", "-"))
+}
+
 #[derive(Debug)]
 pub struct InDepthApplicationsData {
-    pub applications: HashMap<Pid, ApplicationData>,
+    /// Sorted by CPU usage, highest first
+    pub applications: Vec<ApplicationData>,
 }
 
 impl InDepthApplicationsData {
     fn new() -> Self {
         Self {
-            applications: HashMap::new(),
+            applications: Vec::new(),
         }
     }
 }
 
+/// An application from a desktop entry, with the usage of all of its processes summed
 #[derive(Debug)]
 pub struct ApplicationData {
-    // Unsure about some of these properties
+    /// The desktop entry id
+    pub id: String,
     pub name: String,
-    pub pid: Pid,
-    pub parent_pid: Option<Pid>,
+    pub icon: Option<PathBuf>,
+    pub kind: ApplicationKind,
+    /// PIDs with the start times of their processes
+    pub processes: Vec<(Pid, u64)>,
     pub memory_usage: u64,
     pub cpu_usage: f32,
+    /// Bytes per second
+    pub disk_read_rate: f64,
+    /// Bytes per second
+    pub disk_written_rate: f64,
 }
 
 #[derive(Debug)]
 pub struct ProcessesData {
-    pub sort_index: u32,
-    pub sort_direction: SortDirection,
+    /// The shown columns and the sort order
+    pub layout: ProcessColumnLayout,
+    /// Whether to display processes as a tree grouped by their parents
+    pub show_tree: bool,
+    /// Processes whose children are shown in the tree view. Kept outside of in_depth so it survives ticks
+    pub expanded: HashSet<Pid>,
+    /// Processes selected for batch actions, by PID and start time so a reused PID isn't acted on. Kept until they
+    /// exit, even while filtered out
+    pub selected: HashMap<Pid, u64>,
+    /// The query as typed by the user
+    pub filter_query: String,
+    pub filter: ProcessFilter,
+    /// Why the query couldn't be parsed, if it couldn't
+    pub filter_error: Option<String>,
     pub in_depth: InDepthProcessesData,
 }
 
 impl ProcessesData {
     pub fn new() -> Self {
         Self {
-            sort_index: 0,
-            sort_direction: SortDirection::default(),
+            layout: ProcessColumnLayout::load(),
+            show_tree: false,
+            expanded: HashSet::new(),
+            selected: HashMap::new(),
+            filter_query: String::new(),
+            filter: ProcessFilter::default(),
+            filter_error: None,
             in_depth: InDepthProcessesData::new(),
         }
     }
@@ -85,87 +276,565 @@ impl ProcessesData {
         self.in_depth = InDepthProcessesData::new();
     }
 
-    pub fn update_in_depth(&mut self, system_info: &mut System) {
+    pub fn update_in_depth(&mut self, snapshot: &Snapshot) {
         let mut processes = Vec::new();
 
-        for (pid, process) in system_info.processes() {
-            let disk_usage = process.disk_usage();
+        self.selected.retain(|pid, start_time| {
+            snapshot
+                .processes
+                .get(pid)
+                .is_some_and(|process| process.start_time == *start_time)
+        });
 
-            processes.push(ProcessData {
-                name: process.name().to_string(),
+        for (pid, process) in &snapshot.processes {
+            let extras = process.extras.clone();
+
+            let process_data = ProcessData {
+                name: process.name.clone(),
                 pid: *pid,
-                parent_pid: process.parent(),
-                memory_usage: process.memory(),
-                cpu_usage: process.cpu_usage(),
-                disk_read: disk_usage.read_bytes,
-                disk_written: disk_usage.written_bytes,
-            });
+                parent_pid: process.parent_pid,
+                user: process.user.clone(),
+                command: process.command.join(" "),
+                memory_usage: process.memory,
+                cpu_usage: process.cpu_usage,
+                disk_read_rate: snapshot.rate(process.disk_read),
+                disk_written_rate: snapshot.rate(process.disk_written),
+                state: process.state.clone(),
+                start_time: process.start_time,
+                io: extras.io,
+                niceness: extras.niceness,
+                affinity: extras.affinity,
+                thread_count: extras.thread_count,
+                swap_usage: extras.swap_usage,
+                cpu_time: extras.cpu_time,
+                open_file_count: extras.open_file_count,
+            };
+
+            if !self.filter.matches(&process_data) {
+                continue;
+            }
+
+            processes.push(process_data);
         }
 
         self.in_depth.processes = processes;
 
-        self.sort_by_index();
+        self.sort();
+    }
+
+    /// Sorts the processes by the layout's sort column and direction
+    pub fn sort(&mut self) {
+        let sort_column = self.layout.sort_column;
+
+        self.in_depth
+            .processes
+            .sort_by(|a, b| sort_column.compare(a, b));
+
+        match self.layout.sort_direction {
+            SortDirection::Descending => self.in_depth.processes.reverse(),
+            SortDirection::Ascending => {}
+        };
+
+        self.build_tree();
     }
 
-    pub fn sort_by_index(&mut self) {
-        match self.sort_index {
-            0 => {
-                self.in_depth
-                    .processes
-                    .sort_by_key(|process| process.name.to_lowercase());
+    /// Groups the sorted processes by their parents, so siblings keep the current sort order, and sums the usage of each subtree
+    fn build_tree(&mut self) {
+        let in_depth = &mut self.in_depth;
+
+        let mut indexes_by_pid = HashMap::new();
+
+        for (index, process) in in_depth.processes.iter().enumerate() {
+            indexes_by_pid.insert(process.pid, index);
+        }
+
+        // Forget expanded processes that have since exited
+        self.expanded.retain(|pid| indexes_by_pid.contains_key(pid));
+
+        let mut roots = Vec::new();
+        let mut children: HashMap<Pid, Vec<usize>> = HashMap::new();
+
+        for (index, process) in in_depth.processes.iter().enumerate() {
+            match process.parent_pid {
+                Some(parent_pid) if indexes_by_pid.contains_key(&parent_pid) => {
+                    children.entry(parent_pid).or_default().push(index);
+                }
+                // The parent is unknown to us, so treat the process as a root
+                _ => roots.push(index),
             }
-            1 => {
-                self.in_depth
-                    .processes
-                    .sort_by_key(|process| OrderedFloat(process.cpu_usage));
+        }
+
+        let mut subtree_totals = HashMap::new();
+
+        for root in &roots {
+            sum_subtree(*root, &in_depth.processes, &children, &mut subtree_totals);
+        }
+
+        in_depth.tree_roots = roots;
+        in_depth.tree_children = children;
+        in_depth.subtree_totals = subtree_totals;
+    }
+
+    /// Parses and stores a new filter query. An invalid query is kept as typed but filters nothing until fixed
+    pub fn set_filter_query(&mut self, query: String) {
+        match ProcessFilter::parse(&query) {
+            Ok(filter) => {
+                self.filter = filter;
+                self.filter_error = None;
             }
-            2 => {
-                self.in_depth
-                    .processes
-                    .sort_by_key(|process| process.memory_usage);
+            Err(error) => {
+                self.filter = ProcessFilter::default();
+                self.filter_error = Some(error);
             }
-            3 => {
-                self.in_depth
-                    .processes
-                    .sort_by_key(|process| process.disk_read);
+        }
+
+        self.filter_query = query;
+    }
+
+    /// The rows to display, as (index into processes, depth in the tree), in display order
+    pub fn visible_rows(&self) -> Vec<(usize, u32)> {
+        if self.show_tree {
+            return self.visible_tree_rows();
+        }
+
+        (0..self.in_depth.processes.len())
+            .map(|index| (index, 0))
+            .collect()
+    }
+
+    /// The rows to display in the tree view, as (index into processes, depth), skipping children of collapsed processes
+    pub fn visible_tree_rows(&self) -> Vec<(usize, u32)> {
+        let mut rows = Vec::new();
+        // Reverse so the first root is popped first
+        let mut stack: Vec<(usize, u32)> = self
+            .in_depth
+            .tree_roots
+            .iter()
+            .rev()
+            .map(|index| (*index, 0))
+            .collect();
+
+        while let Some((index, depth)) = stack.pop() {
+            rows.push((index, depth));
+
+            let pid = self.in_depth.processes[index].pid;
+
+            if !self.expanded.contains(&pid) {
+                continue;
             }
-            4 => {
-                self.in_depth
-                    .processes
-                    .sort_by_key(|process| process.disk_written);
+
+            let Some(children) = self.in_depth.tree_children.get(&pid) else {
+                continue;
+            };
+
+            for child in children.iter().rev() {
+                stack.push((*child, depth + 1));
             }
-            _ => (), // No sorting
-        };
+        }
 
-        match self.sort_direction {
-            SortDirection::Descending => self.in_depth.processes.reverse(),
-            SortDirection::Ascending => {}
-        };
+        rows
     }
 }
 
+fn sum_subtree(
+    index: usize,
+    processes: &[ProcessData],
+    children: &HashMap<Pid, Vec<usize>>,
+    subtree_totals: &mut HashMap<Pid, ProcessTotals>,
+) -> ProcessTotals {
+    let process = &processes[index];
+
+    let mut totals = ProcessTotals {
+        cpu_usage: process.cpu_usage,
+        memory_usage: process.memory_usage,
+        disk_read_rate: process.disk_read_rate,
+        disk_written_rate: process.disk_written_rate,
+    };
+
+    if let Some(child_indexes) = children.get(&process.pid) {
+        for child_index in child_indexes {
+            let child_totals = sum_subtree(*child_index, processes, children, subtree_totals);
+
+            totals.cpu_usage += child_totals.cpu_usage;
+            totals.memory_usage += child_totals.memory_usage;
+            totals.disk_read_rate += child_totals.disk_read_rate;
+            totals.disk_written_rate += child_totals.disk_written_rate;
+        }
+    }
+
+    subtree_totals.insert(process.pid, totals);
+    totals
+}
+
 #[derive(Debug)]
 pub struct InDepthProcessesData {
     pub processes: Vec<ProcessData>,
+    /// Indexes into processes of the processes without a known parent
+    pub tree_roots: Vec<usize>,
+    /// Indexes into processes of the children of each process, in sorted order
+    pub tree_children: HashMap<Pid, Vec<usize>>,
+    /// Usage of each process summed with all of its descendants
+    pub subtree_totals: HashMap<Pid, ProcessTotals>,
 }
 
 impl InDepthProcessesData {
     fn new() -> Self {
         Self {
             processes: Vec::new(),
+            tree_roots: Vec::new(),
+            tree_children: HashMap::new(),
+            subtree_totals: HashMap::new(),
         }
     }
 }
 
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ProcessTotals {
+    pub cpu_usage: f32,
+    pub memory_usage: u64,
+    /// Bytes per second
+    pub disk_read_rate: f64,
+    /// Bytes per second
+    pub disk_written_rate: f64,
+}
+
 #[derive(Debug)]
 pub struct ProcessData {
     pub name: String,
     pub pid: Pid,
     pub parent_pid: Option<Pid>,
+    pub user: Option<String>,
+    /// The full command line, arguments separated by spaces
+    pub command: String,
     pub memory_usage: u64,
     pub cpu_usage: f32,
-    pub disk_read: u64,
-    pub disk_written: u64,
+    /// Bytes per second
+    pub disk_read_rate: f64,
+    /// Bytes per second
+    pub disk_written_rate: f64,
+    /// None if it couldn't be read, which requires owning the process
+    pub io: Option<ProcessIo>,
+    pub state: String,
+    /// Seconds since the epoch
+    pub start_time: u64,
+    /// The values below are only read while their column is shown, and are None otherwise or if they couldn't be read
+    pub niceness: Option<i32>,
+    /// The logical cores the process may run on
+    pub affinity: Option<Vec<usize>>,
+    pub thread_count: Option<u32>,
+    /// Bytes
+    pub swap_usage: Option<u64>,
+    /// Seconds spent running on the CPU, in user and kernel mode
+    pub cpu_time: Option<f64>,
+    pub open_file_count: Option<usize>,
+}
+
+/// Parses a field of /proc/<pid>/status, like "Threads:\t4" or "VmSwap:\t  1024 kB", ignoring units
+pub fn status_field<T: std::str::FromStr>(status: &str, name: &str) -> Option<T> {
+    status.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+
+        if key != name {
+            return None;
+        }
+
+        value.split_whitespace().next()?.parse().ok()
+    })
+}
+
+/// Reads the user and system time of the process from /proc/<pid>/stat. proc_root is where procfs is mounted
+pub fn read_cpu_time(proc_root: &Path, pid: Pid) -> Option<f64> {
+    let stat = fs::read_to_string(proc_root.join(pid.to_string()).join("stat")).ok()?;
+
+    // The name can contain spaces and parentheses, so only split what comes after it.
+    // The state is the first field after the name, and utime and stime are the 12th and 13th
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+
+    let user_ticks: u64 = fields.get(11)?.parse().ok()?;
+    let system_ticks: u64 = fields.get(12)?.parse().ok()?;
+
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+
+    if ticks_per_second <= 0 {
+        return None;
+    }
+
+    Some((user_ticks + system_ticks) as f64 / ticks_per_second as f64)
+}
+
+/// Cumulative storage I/O of a process from /proc/<pid>/io
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ProcessIo {
+    /// Bytes fetched from storage
+    pub read_bytes: u64,
+    /// Bytes sent to storage
+    pub write_bytes: u64,
+    /// Bytes that were written to the page cache but never reached storage, like a truncated file
+    pub cancelled_write_bytes: u64,
+}
+
+impl ProcessIo {
+    /// proc_root is where procfs is mounted, normally /proc
+    pub fn read(proc_root: &Path, pid: Pid) -> Option<Self> {
+        let io = fs::read_to_string(proc_root.join(pid.to_string()).join("io")).ok()?;
+
+        Self::from_io(&io)
+    }
+
+    /// Parses the contents of /proc/<pid>/io, lines like "read_bytes: 4096"
+    pub fn from_io(io: &str) -> Option<Self> {
+        let field = |name: &str| {
+            io.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+
+                if key != name {
+                    return None;
+                }
+
+                value.trim().parse::<u64>().ok()
+            })
+        };
+
+        Some(Self {
+            read_bytes: field("read_bytes")?,
+            write_bytes: field("write_bytes")?,
+            cancelled_write_bytes: field("cancelled_write_bytes")?,
+        })
+    }
+}
+
+/// Bytes /proc/diskstats counts in a sector, whatever the sector size of the device
+const DISKSTATS_SECTOR_SIZE: u64 = 512;
+
+/// Cumulative I/O of a block device from /proc/diskstats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiskIo {
+    pub read_bytes: u64,
+    pub written_bytes: u64,
+}
+
+impl DiskIo {
+    /// Every block device by name, like nvme0n1p2. proc_root is where procfs is mounted, normally /proc.
+    /// Empty if /proc/diskstats can't be read
+    pub fn read_all(proc_root: &Path) -> HashMap<String, Self> {
+        fs::read_to_string(proc_root.join("diskstats"))
+            .map(|diskstats| Self::from_diskstats(&diskstats))
+            .unwrap_or_default()
+    }
+
+    /// Parses the contents of /proc/diskstats, lines like
+    /// "259 2 nvme0n1p2 52911 11041 3606034 11830 70251 62425 5138552 48787 0 ..."
+    pub fn from_diskstats(diskstats: &str) -> HashMap<String, Self> {
+        diskstats
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+
+                // Skip the major and minor numbers. Sectors read is the 3rd field after the name, sectors written the 7th
+                let name = fields.get(2)?;
+                let sectors_read: u64 = fields.get(5)?.parse().ok()?;
+                let sectors_written: u64 = fields.get(9)?.parse().ok()?;
+
+                Some((
+                    name.to_string(),
+                    Self {
+                        read_bytes: sectors_read * DISKSTATS_SECTOR_SIZE,
+                        written_bytes: sectors_written * DISKSTATS_SECTOR_SIZE,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessDetailsData {
+    #[serde(with = "super::serialization::pid")]
+    pub pid: Pid,
+    /// Whether the process was still running as of the last update
+    pub exists: bool,
+    pub name: String,
+    pub command: Vec<String>,
+    pub exe: Option<PathBuf>,
+    pub cwd: Option<PathBuf>,
+    pub environment: Vec<String>,
+    pub user: Option<String>,
+    pub user_id: Option<u32>,
+    pub group: Option<String>,
+    pub group_id: Option<u32>,
+    /// Seconds since the unix epoch
+    pub start_time: u64,
+    /// Seconds the process has been running for
+    pub run_time: u64,
+    pub state: String,
+    pub thread_count: Option<u32>,
+    /// Open file descriptors and what they point to. None if we aren't allowed to read them
+    pub open_files: Option<Vec<(String, String)>>,
+    pub memory_maps: Option<MemoryMapsSummary>,
+    pub cgroup: Option<String>,
+    pub memory_usage: u64,
+    pub virtual_memory: u64,
+    pub cpu_usage: f32,
+    pub io: Option<ProcessIo>,
+}
+
+impl ProcessDetailsData {
+    pub fn new(pid: Pid) -> Self {
+        Self {
+            pid,
+            exists: false,
+            name: String::new(),
+            command: Vec::new(),
+            exe: None,
+            cwd: None,
+            environment: Vec::new(),
+            user: None,
+            user_id: None,
+            group: None,
+            group_id: None,
+            start_time: 0,
+            run_time: 0,
+            state: String::new(),
+            thread_count: None,
+            open_files: None,
+            memory_maps: None,
+            cgroup: None,
+            memory_usage: 0,
+            virtual_memory: 0,
+            cpu_usage: 0.,
+            io: None,
+        }
+    }
+
+    /// proc_root is where procfs is mounted, for what sysinfo doesn't read
+    pub fn update(
+        &mut self,
+        system_info: &System,
+        users_info: &Users,
+        group_names: &HashMap<u32, String>,
+        proc_root: &Path,
+    ) {
+        let Some(process) = system_info.process(self.pid) else {
+            self.exists = false;
+            return;
+        };
+
+        self.exists = true;
+        self.name = process.name().to_string();
+        self.command = process.cmd().to_vec();
+        self.exe = process.exe().map(|path| path.to_path_buf());
+        self.cwd = process.cwd().map(|path| path.to_path_buf());
+        self.environment = process.environ().to_vec();
+        self.user_id = process.user_id().map(|user_id| **user_id);
+        self.user = process
+            .user_id()
+            .and_then(|user_id| users_info.get_user_by_id(user_id))
+            .map(|user| user.name().to_string());
+        self.group_id = process.group_id().map(|group_id| *group_id);
+        self.group = self
+            .group_id
+            .and_then(|group_id| group_names.get(&group_id).cloned());
+        self.start_time = process.start_time();
+        self.run_time = process.run_time();
+        self.state = process.status().to_string();
+        self.memory_usage = process.memory();
+        self.virtual_memory = process.virtual_memory();
+        self.cpu_usage = process.cpu_usage();
+        self.io = ProcessIo::read(proc_root, self.pid);
+
+        // Things sysinfo doesn't provide are read from /proc directly
+
+        let proc_path = proc_root.join(self.pid.to_string());
+
+        self.thread_count = fs::read_to_string(proc_path.join("status"))
+            .ok()
+            .and_then(|status| status_field(&status, "Threads"));
+
+        self.open_files = fs::read_dir(proc_path.join("fd")).ok().map(|entries| {
+            let mut open_files: Vec<(String, String)> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| {
+                    let target = fs::read_link(entry.path())
+                        .map(|target| target.to_string_lossy().to_string())
+                        .unwrap_or_default();
+
+                    (entry.file_name().to_string_lossy().to_string(), target)
+                })
+                .collect();
+
+            open_files.sort_by_key(|(fd, _)| fd.parse::<u32>().unwrap_or(u32::MAX));
+            open_files
+        });
+
+        self.memory_maps = fs::read_to_string(proc_path.join("maps"))
+            .ok()
+            .map(|maps| MemoryMapsSummary::from_maps(&maps));
+
+        self.cgroup = fs::read_to_string(proc_path.join("cgroup"))
+            .ok()
+            .map(|cgroup| {
+                // Lines look like 0::/user.slice/user-1000.slice/session-2.scope. Only keep the paths
+                cgroup
+                    .lines()
+                    .filter_map(|line| line.splitn(3, ':').nth(2))
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            });
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct MemoryMapsSummary {
+    pub mappings: usize,
+    /// Bytes of address space mapped in total
+    pub total_size: u64,
+    /// Bytes of address space not backed by a file, like the heap and stacks
+    pub anonymous_size: u64,
+    /// Bytes of address space backed by files, like executables and shared libraries
+    pub file_backed_size: u64,
+    /// How many distinct files are mapped
+    pub mapped_files: usize,
+}
+
+impl MemoryMapsSummary {
+    /// Summarizes the contents of /proc/<pid>/maps
+    pub fn from_maps(maps: &str) -> Self {
+        let mut summary = Self::default();
+        let mut files = HashSet::new();
+
+        for line in maps.lines() {
+            // Lines look like: 7f2c1a000000-7f2c1a021000 rw-p 00000000 00:00 0    [heap]
+            let mut fields = line.split_whitespace();
+
+            let Some((start, end)) = fields.next().and_then(|range| range.split_once('-')) else {
+                continue;
+            };
+
+            let (Ok(start), Ok(end)) =
+                (u64::from_str_radix(start, 16), u64::from_str_radix(end, 16))
+            else {
+                continue;
+            };
+
+            let size = end.saturating_sub(start);
+            // Skip permissions, offset, device and inode to get to the path
+            let path = fields.nth(4);
+
+            summary.mappings += 1;
+            summary.total_size += size;
+
+            match path {
+                Some(path) if path.starts_with('/') => {
+                    summary.file_backed_size += size;
+                    files.insert(path);
+                }
+                _ => summary.anonymous_size += size,
+            }
+        }
+
+        summary.mapped_files = files.len();
+        summary
+    }
 }
 
 #[derive(Debug)]
@@ -174,29 +843,39 @@ pub struct CpuData {
     pub frequency: u64,
     pub logical_cores_usage_percents: Vec<f32>,
     pub logical_cores_frequencies: Vec<u64>,
+    /// Clock ticks spent in each state over the last refresh, in total and per logical core
+    pub times: CpuStat,
+    /// None if /proc/loadavg can't be read
+    pub load: Option<LoadAverage>,
+    pub pressure: Option<Pressure>,
+    pub temperatures: CpuTemperatures,
     pub show_logical_cores: bool,
 }
 
 impl CpuData {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             cpu_usage_percent: 0.0,
             frequency: 0,
             logical_cores_usage_percents: vec![],
             logical_cores_frequencies: vec![],
+            times: CpuStat::default(),
+            load: None,
+            pressure: None,
+            temperatures: CpuTemperatures::default(),
             show_logical_cores: false,
         }
     }
 
-    pub fn update(&mut self, cpu_info: &[sysinfo::Cpu], logical_core_count: u32) {
+    pub fn update(&mut self, cpu_info: &[CpuSample], logical_core_count: u32) {
         let mut total_used: f32 = 0.;
         let mut total_frequency: u64 = 0;
         let mut logical_cores_usage_percents: Vec<f32> = Vec::new();
         let mut logical_cores_frequencies: Vec<u64> = Vec::new();
 
         for (_, logical_core) in cpu_info.iter().enumerate() {
-            let cpu_usage = logical_core.cpu_usage();
-            let frequency = logical_core.frequency();
+            let cpu_usage = logical_core.usage;
+            let frequency = logical_core.frequency;
 
             logical_cores_usage_percents.push(cpu_usage);
 
@@ -215,12 +894,16 @@ impl CpuData {
 
 #[derive(Debug)]
 pub struct DiskData {
-    pub read: u64,
-    pub written: u64,
+    /// Bytes per second
+    pub read: f64,
+    /// Bytes per second
+    pub written: f64,
     pub kind: DiskKind,
     pub name: String,
     pub space_total: u64,
     pub space_used: u64,
+    /// I/O pressure of the whole system, not just this disk
+    pub pressure: Option<Pressure>,
     pub in_depth: Option<DiskDataInDepth>,
 }
 
@@ -228,11 +911,12 @@ impl DiskData {
     pub fn new() -> Self {
         Self {
             name: String::new(),
-            read: 0,
-            written: 0,
+            read: 0.,
+            written: 0.,
             space_total: 0,
             space_used: 0,
             kind: DiskKind::Unknown(0),
+            pressure: None,
             in_depth: Some(DiskDataInDepth::new()),
         }
     }
@@ -241,18 +925,27 @@ impl DiskData {
         self.in_depth = Some(DiskDataInDepth::new());
     }
 
-    pub fn update(&mut self, disk_name: &String, disk: &Disk) {
-        self.name = disk_name.clone();
-        self.space_total = disk.total_space();
-        self.space_used = self.space_total - disk.available_space();
-        self.read = 0;
-        self.written = 0;
-        self.kind = disk.kind();
+    /// The refresh interval is what the sample's read and written bytes were counted over
+    pub fn update(&mut self, disk: &DiskSample, refresh_interval: Duration) {
+        let refresh_seconds = refresh_interval.as_secs_f64();
+
+        self.name = disk.name.clone();
+        self.space_total = disk.total_space;
+        self.space_used = self.space_total - disk.available_space;
+        (self.read, self.written) = if refresh_seconds > 0. {
+            (
+                disk.read as f64 / refresh_seconds,
+                disk.written as f64 / refresh_seconds,
+            )
+        } else {
+            (0., 0.)
+        };
+        self.kind = disk.kind;
     }
 
-    pub fn update_in_depth(&mut self, disk_name: &String, disk: &Disk) {
+    pub fn update_in_depth(&mut self, disk: &DiskSample) {
         let in_depth = DiskDataInDepth {
-            is_removable: disk.is_removable(),
+            is_removable: disk.is_removable,
         };
 
         self.in_depth = Some(in_depth);
@@ -272,7 +965,8 @@ impl DiskDataInDepth {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(into = "BatteryRecord", from = "BatteryRecord")]
 pub struct BatteryData {
     pub vendor: String,
     pub model: String,
@@ -330,7 +1024,7 @@ pub struct MemoryData {
     pub swap_usage: u64,
     pub swap_total: u64,
     pub swap_usage_percent: f32,
-    pub in_depth: Option<InDepthMemoryData>,
+    pub pressure: Option<Pressure>,
 }
 
 impl MemoryData {
@@ -342,48 +1036,39 @@ impl MemoryData {
             swap_usage: 0,
             swap_total: 0,
             swap_usage_percent: 0.,
-            in_depth: Some(InDepthMemoryData::new()),
+            pressure: None,
         }
     }
 
-    pub fn update(&mut self, system_info: &System) {
+    pub fn update(&mut self, memory: &MemorySample) {
         // ram
 
-        self.ram_usage = system_info.used_memory();
-        self.ram_total = system_info.total_memory();
+        self.ram_usage = memory.ram_used;
+        self.ram_total = memory.ram_total;
 
         self.ram_usage_percent = self.ram_usage as f32 / self.ram_total as f32 * 100.;
 
         // swap
 
-        self.swap_usage = system_info.used_swap();
-        self.swap_total = system_info.total_swap();
+        self.swap_usage = memory.swap_used;
+        self.swap_total = memory.swap_total;
 
-        self.swap_usage_percent = self.swap_usage as f32 / self.swap_total as f32 * 100.;
+        // Without swap there's nothing to use
+        self.swap_usage_percent = if self.swap_total == 0 {
+            0.
+        } else {
+            self.swap_usage as f32 / self.swap_total as f32 * 100.
+        };
     }
 }
 
-#[derive(Debug)]
-pub struct InDepthMemoryData {
-    pub is_removable: bool,
-}
-
-impl InDepthMemoryData {
-    fn new() -> Self {
-        Self {
-            is_removable: false,
-        }
-    }
-}
 
 #[derive(Debug)]
 pub struct ResourceData {
     pub applications: ApplicationsData,
     pub processes: ProcessesData,
-    pub disks: HashMap<String, DiskData>,
-    pub battery: BatteryData,
-    pub cpu: CpuData,
-    pub memory: MemoryData,
+    /// Details of the process whose page is open, if any
+    pub process_details: Option<ProcessDetailsData>,
 }
 
 impl ResourceData {
@@ -391,10 +1076,301 @@ impl ResourceData {
         Self {
             applications: ApplicationsData::new(),
             processes: ProcessesData::new(),
-            disks: HashMap::new(),
-            battery: BatteryData::new(),
-            cpu: CpuData::new(),
-            memory: MemoryData::new(),
+            process_details: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc, time::Duration};
+
+    use sysinfo::Pid;
+
+    use crate::{
+        collector::{Collector, CollectorRequest},
+        resource_pages::resource_details::SortDirection,
+        system_source::FixtureSource,
+        types::{
+            desktop_entry::DesktopEntries,
+            process_columns::{ProcessColumn, ProcessColumnLayout},
+            snapshot::{ProcessExtras, ProcessSample, Snapshot},
+        },
+        utils::fixture,
+    };
+
+    use super::{ApplicationsData, CpuData, DiskData, DiskIo, MemoryData, ProcessesData};
+
+    /// The snapshots of a fixture, one per frame
+    fn snapshots(fixture: &str, count: usize) -> Vec<Snapshot> {
+        let mut collector = Collector::with_source(
+            CollectorRequest::new(Duration::from_secs(1)),
+            Box::new(FixtureSource::load(fixture)),
+        );
+
+        (0..count).map(|_| collector.sample()).collect()
+    }
+
+    /// A snapshot of a single process that only its scope could be matched by
+    fn scoped_process_snapshot(cgroup: Option<&str>) -> Snapshot {
+        let process = ProcessSample {
+            name: String::from("gjs"),
+            parent_pid: None,
+            user: None,
+            command: Vec::new(),
+            exe: None,
+            memory: 0,
+            cpu_usage: 0.,
+            disk_read: 0,
+            disk_written: 0,
+            state: String::from("Sleeping"),
+            start_time: 100,
+            cgroup: cgroup.map(String::from),
+            extras: ProcessExtras::default(),
+        };
+
+        Snapshot {
+            refresh_interval: Duration::from_secs(1),
+            processes: HashMap::from([(Pid::from_u32(10), process)]),
+            ..Snapshot::default()
+        }
+    }
+
+    #[test]
+    fn rates_are_zero_without_a_refresh_interval() {
+        let mut snapshot = scoped_process_snapshot(None);
+        snapshot.refresh_interval = Duration::ZERO;
+
+        for process in snapshot.processes.values_mut() {
+            process.disk_read = 4096;
         }
+
+        let mut processes = processes_data(ProcessColumn::DiskRead, SortDirection::Descending);
+        processes.update_in_depth(&snapshot);
+
+        assert_eq!(processes.in_depth.processes[0].disk_read_rate, 0.);
+    }
+
+    #[test]
+    fn processes_are_matched_again_once_their_cgroup_is_read() {
+        let xdg = fixture("xdg");
+        let mut applications = ApplicationsData::new();
+
+        // Nothing is matched until the desktop entries are loaded
+        applications.update_in_depth(&scoped_process_snapshot(None));
+        assert!(applications.direct_matches.is_empty());
+
+        applications.desktop_entries = Some(Arc::new(DesktopEntries::load_from(&[
+            xdg.join("user"),
+            xdg.join("system"),
+        ])));
+
+        // Sampled before the page asked for cgroups
+        applications.update_in_depth(&scoped_process_snapshot(None));
+        assert!(applications.in_depth.applications.is_empty());
+
+        applications.update_in_depth(&scoped_process_snapshot(Some(
+            "0::/user.slice/app.slice/app-gnome-org.gnome.Nautilus-1234.scope",
+        )));
+
+        let names: Vec<&str> = applications
+            .in_depth
+            .applications
+            .iter()
+            .map(|application| application.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Files (override)"]);
+    }
+
+    /// Processes data that doesn't depend on the layout saved in the config dir
+    fn processes_data(sort_column: ProcessColumn, sort_direction: SortDirection) -> ProcessesData {
+        let mut processes = ProcessesData::new();
+        processes.layout = ProcessColumnLayout::default();
+        processes.layout.sort_column = sort_column;
+        processes.layout.sort_direction = sort_direction;
+        processes
+    }
+
+    fn names(processes: &ProcessesData) -> Vec<&str> {
+        processes
+            .in_depth
+            .processes
+            .iter()
+            .map(|process| process.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn cpu_update_averages_logical_cores() {
+        let snapshots = snapshots("desktop", 2);
+        let mut cpu = CpuData::new();
+
+        cpu.update(&snapshots[0].cpus, 4);
+
+        assert_eq!(cpu.cpu_usage_percent, 25.);
+        assert_eq!(cpu.frequency, 2500);
+        assert_eq!(cpu.logical_cores_usage_percents, vec![10., 20., 30., 40.]);
+        assert_eq!(cpu.logical_cores_frequencies, vec![1000, 2000, 3000, 4000]);
+
+        cpu.update(&snapshots[1].cpus, 4);
+
+        assert_eq!(cpu.cpu_usage_percent, 50.);
+        assert_eq!(cpu.frequency, 2400);
+    }
+
+    #[test]
+    fn memory_update_computes_percents() {
+        let snapshots = snapshots("desktop", 2);
+        let mut memory = MemoryData::new();
+
+        memory.update(&snapshots[1].memory);
+
+        assert_eq!(memory.ram_usage, 8 * 1024 * 1024 * 1024);
+        assert_eq!(memory.ram_usage_percent, 50.);
+        assert_eq!(memory.swap_usage_percent, 25.);
+    }
+
+    #[test]
+    fn memory_update_without_swap() {
+        let snapshots = snapshots("desktop", 1);
+        let mut memory = MemoryData::new();
+
+        memory.update(&snapshots[0].memory);
+
+        assert_eq!(memory.ram_usage_percent, 25.);
+        assert_eq!(memory.swap_total, 0);
+        assert_eq!(memory.swap_usage_percent, 0.);
+    }
+
+    #[test]
+    fn disk_update_computes_rates() {
+        let snapshots = snapshots("desktop", 1);
+        let mut disk = DiskData::new();
+
+        disk.update(&snapshots[0].disks[0], Duration::from_secs(2));
+
+        assert_eq!(disk.name, "/dev/nvme0n1p2");
+        assert_eq!(disk.space_used, 300_000_000_000);
+        assert_eq!(disk.read, 512. * 1024.);
+        assert_eq!(disk.written, 256. * 1024.);
+
+        // Nothing was counted yet on the first refresh
+        disk.update(&snapshots[0].disks[0], Duration::ZERO);
+
+        assert_eq!(disk.read, 0.);
+    }
+
+    #[test]
+    fn diskstats_are_parsed_in_bytes() {
+        let diskstats = "\
+ 259       0 nvme0n1 60473 15117 5148762 19126 104318 85468 8290664 75373 0 49612 102131 0 0 0 0 4893 7631
+ 259       2 nvme0n1p2 52911 11041 3606034 11830 70251 62425 5138552 48787 0 39864 60617 0 0 0 0 0 0
+ 253       0 dm-0 1 2
+";
+
+        let disks = DiskIo::from_diskstats(diskstats);
+
+        assert_eq!(disks.len(), 2);
+        assert_eq!(
+            disks["nvme0n1p2"],
+            DiskIo {
+                read_bytes: 3606034 * 512,
+                written_bytes: 5138552 * 512,
+            }
+        );
+    }
+
+    #[test]
+    fn diskstats_are_read_from_the_proc_root() {
+        let proc_root = fixture("proc");
+
+        let disks = DiskIo::read_all(&proc_root.join("amd"));
+
+        assert_eq!(disks.len(), 2);
+        assert_eq!(disks["nvme0n1p1"].read_bytes, 8192 * 512);
+        assert!(DiskIo::read_all(&proc_root.join("missing")).is_empty());
+    }
+
+    #[test]
+    fn processes_sort_by_column_and_direction() {
+        let snapshots = snapshots("desktop", 1);
+
+        let mut processes = processes_data(ProcessColumn::Cpu, SortDirection::Descending);
+        processes.update_in_depth(&snapshots[0]);
+
+        assert_eq!(
+            names(&processes),
+            vec!["Isolated Web Co", "Firefox", "bash", "systemd"]
+        );
+
+        processes.layout.sort_column = ProcessColumn::Name;
+        processes.layout.sort_direction = SortDirection::Ascending;
+        processes.sort();
+
+        assert_eq!(
+            names(&processes),
+            vec!["bash", "Firefox", "Isolated Web Co", "systemd"]
+        );
+
+        processes.layout.sort_column = ProcessColumn::Memory;
+        processes.sort();
+
+        assert_eq!(
+            names(&processes),
+            vec!["bash", "systemd", "Isolated Web Co", "Firefox"]
+        );
+    }
+
+    #[test]
+    fn processes_tree_keeps_sort_order_and_sums_subtrees() {
+        let snapshots = snapshots("desktop", 1);
+
+        let mut processes = processes_data(ProcessColumn::Cpu, SortDirection::Descending);
+        processes.show_tree = true;
+        processes.update_in_depth(&snapshots[0]);
+
+        // Only the root shows until it's expanded
+        assert_eq!(processes.visible_rows().len(), 1);
+
+        processes.expanded.insert(Pid::from_u32(1));
+        processes.expanded.insert(Pid::from_u32(200));
+
+        let rows: Vec<(&str, u32)> = processes
+            .visible_rows()
+            .into_iter()
+            .map(|(index, depth)| (processes.in_depth.processes[index].name.as_str(), depth))
+            .collect();
+
+        assert_eq!(
+            rows,
+            vec![
+                ("systemd", 0),
+                ("Firefox", 1),
+                ("Isolated Web Co", 2),
+                ("bash", 1)
+            ]
+        );
+
+        let firefox = processes.in_depth.subtree_totals[&Pid::from_u32(200)];
+        assert_eq!(firefox.cpu_usage, 42.5);
+        assert_eq!(firefox.memory_usage, 1_100_000_000);
+
+        let systemd = processes.in_depth.subtree_totals[&Pid::from_u32(1)];
+        assert_eq!(systemd.cpu_usage, 43.);
+    }
+
+    #[test]
+    fn processes_filter_and_rates() {
+        let snapshots = snapshots("desktop", 1);
+
+        let mut processes = processes_data(ProcessColumn::Name, SortDirection::Ascending);
+        processes.set_filter_query(String::from("name:firefox"));
+        processes.update_in_depth(&snapshots[0]);
+
+        assert_eq!(names(&processes), vec!["Firefox"]);
+
+        let firefox = &processes.in_depth.processes[0];
+        let refresh_seconds = snapshots[0].refresh_interval.as_secs_f64();
+        assert_eq!(firefox.disk_read_rate, 4096. / refresh_seconds);
     }
 }
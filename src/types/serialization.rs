@@ -0,0 +1,209 @@
+//! Serde support for the sysinfo and battery types kept in snapshots, which don't implement it themselves
+
+use battery::units::{
+    electric_potential::volt, energy::joule, power::watt, ratio::ratio,
+    thermodynamic_temperature::kelvin, time::second, ElectricPotential, Energy, Power, Ratio,
+    ThermodynamicTemperature, Time,
+};
+use serde::{Deserialize, Serialize};
+
+use super::resource_data::BatteryData;
+
+/// A PID as its number
+pub mod pid {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use sysinfo::Pid;
+
+    pub fn serialize<S: Serializer>(pid: &Pid, serializer: S) -> Result<S::Ok, S::Error> {
+        pid.as_u32().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pid, D::Error> {
+        Ok(Pid::from_u32(u32::deserialize(deserializer)?))
+    }
+}
+
+pub mod optional_pid {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use sysinfo::Pid;
+
+    pub fn serialize<S: Serializer>(pid: &Option<Pid>, serializer: S) -> Result<S::Ok, S::Error> {
+        pid.map(|pid| pid.as_u32()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Pid>, D::Error> {
+        Ok(Option::<u32>::deserialize(deserializer)?.map(Pid::from_u32))
+    }
+}
+
+/// A map keyed by PID, as a map keyed by PID numbers
+pub mod pid_map {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use sysinfo::Pid;
+
+    pub fn serialize<S: Serializer, T: Serialize>(
+        map: &HashMap<Pid, T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(map.iter().map(|(pid, value)| (pid.as_u32(), value)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<Pid, T>, D::Error> {
+        Ok(HashMap::<u32, T>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(pid, value)| (Pid::from_u32(pid), value))
+            .collect())
+    }
+}
+
+pub mod disk_kind {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use sysinfo::DiskKind;
+
+    pub fn serialize<S: Serializer>(kind: &DiskKind, serializer: S) -> Result<S::Ok, S::Error> {
+        match kind {
+            DiskKind::HDD => "hdd",
+            DiskKind::SSD => "ssd",
+            DiskKind::Unknown(_) => "unknown",
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DiskKind, D::Error> {
+        Ok(match String::deserialize(deserializer)?.as_str() {
+            "hdd" => DiskKind::HDD,
+            "ssd" => DiskKind::SSD,
+            _ => DiskKind::Unknown(-1),
+        })
+    }
+}
+
+/// Battery data with its quantities in SI base units
+#[derive(Serialize, Deserialize)]
+pub struct BatteryRecord {
+    vendor: String,
+    model: String,
+    cycles: Option<u32>,
+    /// Kelvin
+    temperature: Option<f32>,
+    /// Watts
+    energy_rate: f32,
+    /// Joules
+    designed_capacity: f32,
+    /// Joules
+    current_capacity: f32,
+    /// Joules
+    energy: f32,
+    /// Volts
+    voltage: f32,
+    state: String,
+    /// Seconds
+    time_to_behaviour: Option<f32>,
+    state_of_health: f32,
+    state_of_charge: f32,
+    technology: String,
+}
+
+impl From<BatteryData> for BatteryRecord {
+    fn from(battery: BatteryData) -> Self {
+        Self {
+            vendor: battery.vendor,
+            model: battery.model,
+            cycles: battery.cycles,
+            temperature: battery.temperature.map(|temperature| temperature.value),
+            energy_rate: battery.energy_rate.value,
+            designed_capacity: battery.designed_capacity.value,
+            current_capacity: battery.current_capacity.value,
+            energy: battery.energy.value,
+            voltage: battery.voltage.value,
+            state: battery_state_key(battery.state).to_string(),
+            time_to_behaviour: battery.time_to_behaviour.map(|time| time.value),
+            state_of_health: battery.state_of_health.value,
+            state_of_charge: battery.state_of_charge.value,
+            technology: battery_technology_key(battery.technology).to_string(),
+        }
+    }
+}
+
+impl From<BatteryRecord> for BatteryData {
+    fn from(record: BatteryRecord) -> Self {
+        Self {
+            vendor: record.vendor,
+            model: record.model,
+            cycles: record.cycles,
+            temperature: record
+                .temperature
+                .map(ThermodynamicTemperature::new::<kelvin>),
+            energy_rate: Power::new::<watt>(record.energy_rate),
+            designed_capacity: Energy::new::<joule>(record.designed_capacity),
+            current_capacity: Energy::new::<joule>(record.current_capacity),
+            energy: Energy::new::<joule>(record.energy),
+            voltage: ElectricPotential::new::<volt>(record.voltage),
+            state: battery_state_from_key(&record.state),
+            time_to_behaviour: record.time_to_behaviour.map(Time::new::<second>),
+            state_of_health: Ratio::new::<ratio>(record.state_of_health),
+            state_of_charge: Ratio::new::<ratio>(record.state_of_charge),
+            technology: battery_technology_from_key(&record.technology),
+        }
+    }
+}
+
+fn battery_state_key(state: battery::State) -> &'static str {
+    match state {
+        battery::State::Charging => "charging",
+        battery::State::Discharging => "discharging",
+        battery::State::Empty => "empty",
+        battery::State::Full => "full",
+        _ => "unknown",
+    }
+}
+
+fn battery_state_from_key(key: &str) -> battery::State {
+    match key {
+        "charging" => battery::State::Charging,
+        "discharging" => battery::State::Discharging,
+        "empty" => battery::State::Empty,
+        "full" => battery::State::Full,
+        _ => battery::State::Unknown,
+    }
+}
+
+const BATTERY_TECHNOLOGIES: [(battery::Technology, &str); 8] = [
+    (battery::Technology::LithiumIon, "lithium-ion"),
+    (battery::Technology::LeadAcid, "lead-acid"),
+    (battery::Technology::LithiumPolymer, "lithium-polymer"),
+    (
+        battery::Technology::NickelMetalHydride,
+        "nickel-metal-hydride",
+    ),
+    (battery::Technology::NickelCadmium, "nickel-cadmium"),
+    (battery::Technology::NickelZinc, "nickel-zinc"),
+    (
+        battery::Technology::LithiumIronPhosphate,
+        "lithium-iron-phosphate",
+    ),
+    (
+        battery::Technology::RechargeableAlkalineManganese,
+        "rechargeable-alkaline-manganese",
+    ),
+];
+
+fn battery_technology_key(technology: battery::Technology) -> &'static str {
+    BATTERY_TECHNOLOGIES
+        .iter()
+        .find(|(known, _)| *known == technology)
+        .map_or("unknown", |(_, key)| key)
+}
+
+fn battery_technology_from_key(key: &str) -> battery::Technology {
+    BATTERY_TECHNOLOGIES
+        .iter()
+        .find(|(_, known)| *known == key)
+        .map_or(battery::Technology::Unknown, |(technology, _)| *technology)
+}
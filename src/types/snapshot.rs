@@ -0,0 +1,150 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{DiskKind, Pid};
+
+use super::{
+    cpu_times::CpuStat,
+    pressure::SystemPressure,
+    resource_data::{BatteryData, ProcessDetailsData, ProcessIo},
+    serialization,
+    thermals::CpuTemperatures,
+};
+
+/// Everything sampled from the system in one refresh. Built by the collector off the UI thread and never changed
+/// afterwards, so the UI only has to apply it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// When the refresh finished
+    pub timestamp: SystemTime,
+    /// The measured time since the previous refresh, which per refresh deltas like disk usage were taken over
+    pub refresh_interval: Duration,
+    /// One per logical core
+    pub cpus: Vec<CpuSample>,
+    /// Clock ticks spent in each state since the previous refresh
+    #[serde(default)]
+    pub cpu_times: CpuStat,
+    #[serde(default)]
+    pub cpu_temperatures: CpuTemperatures,
+    pub memory: MemorySample,
+    #[serde(default)]
+    pub pressure: SystemPressure,
+    pub disks: Vec<DiskSample>,
+    pub batteries: Vec<BatteryData>,
+    #[serde(with = "serialization::pid_map")]
+    pub processes: HashMap<Pid, ProcessSample>,
+    /// Only collected while a process details page is open, for the process it shows
+    pub process_details: Option<ProcessDetailsData>,
+}
+
+impl Snapshot {
+    /// Bytes per second of a count taken over the refresh interval, or 0 if there was none, like in a default snapshot
+    pub fn rate(&self, bytes: u64) -> f64 {
+        let refresh_seconds = self.refresh_interval.as_secs_f64();
+
+        if refresh_seconds > 0. {
+            bytes as f64 / refresh_seconds
+        } else {
+            0.
+        }
+    }
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        Self {
+            timestamp: SystemTime::UNIX_EPOCH,
+            refresh_interval: Duration::ZERO,
+            cpus: Vec::new(),
+            cpu_times: CpuStat::default(),
+            cpu_temperatures: CpuTemperatures::default(),
+            memory: MemorySample::default(),
+            pressure: SystemPressure::default(),
+            disks: Vec::new(),
+            batteries: Vec::new(),
+            processes: HashMap::new(),
+            process_details: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct CpuSample {
+    /// Percent
+    pub usage: f32,
+    /// MHz
+    pub frequency: u64,
+}
+
+/// Bytes
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct MemorySample {
+    pub ram_used: u64,
+    pub ram_total: u64,
+    pub swap_used: u64,
+    pub swap_total: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskSample {
+    pub name: String,
+    /// The same device can be mounted more than once, like btrfs subvolumes
+    #[serde(default)]
+    pub mount_point: PathBuf,
+    #[serde(with = "serialization::disk_kind")]
+    pub kind: DiskKind,
+    /// Bytes
+    pub total_space: u64,
+    /// Bytes
+    pub available_space: u64,
+    pub is_removable: bool,
+    /// Bytes read from the disk's block device since the previous refresh
+    #[serde(default)]
+    pub read: u64,
+    /// Bytes written to the disk's block device since the previous refresh
+    #[serde(default)]
+    pub written: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessSample {
+    pub name: String,
+    #[serde(with = "serialization::optional_pid")]
+    pub parent_pid: Option<Pid>,
+    pub user: Option<String>,
+    pub command: Vec<String>,
+    pub exe: Option<PathBuf>,
+    /// Bytes
+    pub memory: u64,
+    pub cpu_usage: f32,
+    /// Bytes read since the previous refresh
+    pub disk_read: u64,
+    /// Bytes written since the previous refresh
+    pub disk_written: u64,
+    pub state: String,
+    /// Seconds since the epoch
+    pub start_time: u64,
+    /// Only read while the applications page is shown
+    pub cgroup: Option<String>,
+    pub extras: ProcessExtras,
+}
+
+/// Values read from /proc on top of the regular refresh. They are only read while a column needs them,
+/// and are None otherwise or if they couldn't be read
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProcessExtras {
+    pub io: Option<ProcessIo>,
+    pub niceness: Option<i32>,
+    /// The logical cores the process may run on
+    pub affinity: Option<Vec<usize>>,
+    pub thread_count: Option<u32>,
+    /// Bytes
+    pub swap_usage: Option<u64>,
+    /// Seconds spent running on the CPU, in user and kernel mode
+    pub cpu_time: Option<f64>,
+    pub open_file_count: Option<usize>,
+}
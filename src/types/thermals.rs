@@ -0,0 +1,287 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{read_number, read_trimmed};
+
+/// hwmon drivers that report CPU temperatures
+const CPU_HWMON_NAMES: [&str; 3] = ["coretemp", "k10temp", "zenpower"];
+
+/// A temperature sensor reading, in degrees Celsius
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Temperature {
+    /// As the driver names the sensor, like "Package id 0" or "Tccd1"
+    pub label: String,
+    pub celsius: f32,
+    /// Hotter than the hardware is meant to run at
+    pub high: Option<f32>,
+    /// Hot enough for the hardware to throttle hard or shut down
+    pub critical: Option<f32>,
+}
+
+impl Temperature {
+    /// The high and critical temperatures that are known, for charts
+    pub fn thresholds(&self) -> Vec<f64> {
+        self.high
+            .into_iter()
+            .chain(self.critical)
+            .map(f64::from)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CpuTemperatures {
+    /// One per package on Intel, the whole die on AMD
+    pub packages: Vec<Temperature>,
+    /// Per core on Intel, per CCD on AMD. Empty if the sensors don't go further than the package
+    pub cores: Vec<Temperature>,
+}
+
+impl CpuTemperatures {
+    /// Reads hwmon, or the thermal zones if no hwmon driver reports the CPU. sys_root is where sysfs is mounted,
+    /// normally /sys
+    pub fn read(sys_root: &Path) -> Self {
+        let temperatures = Self::read_hwmon(sys_root);
+
+        if !temperatures.packages.is_empty() {
+            return temperatures;
+        }
+
+        Self::read_thermal_zones(sys_root)
+    }
+
+    /// The hottest package
+    pub fn package(&self) -> Option<&Temperature> {
+        self.packages
+            .iter()
+            .max_by(|a, b| a.celsius.total_cmp(&b.celsius))
+    }
+
+    fn read_hwmon(sys_root: &Path) -> Self {
+        let mut temperatures = Self::default();
+
+        for hwmon in sorted_entries(&sys_root.join("class/hwmon"), "hwmon") {
+            let Some(name) = read_trimmed(&hwmon.join("name")) else {
+                continue;
+            };
+
+            if !CPU_HWMON_NAMES.contains(&name.as_str()) {
+                continue;
+            }
+
+            let sensors = hwmon_sensors(&hwmon);
+
+            if name == "coretemp" {
+                for sensor in sensors {
+                    if sensor.label.starts_with("Package") {
+                        temperatures.packages.push(sensor);
+                    } else {
+                        temperatures.cores.push(sensor);
+                    }
+                }
+
+                continue;
+            }
+
+            // Tctl is offset on some AMD CPUs to make fans spin up sooner, so Tdie is the actual temperature if there is one
+            let die = sensors
+                .iter()
+                .find(|sensor| sensor.label == "Tdie")
+                .or_else(|| sensors.iter().find(|sensor| sensor.label == "Tctl"));
+
+            if let Some(die) = die {
+                temperatures.packages.push(die.clone());
+            }
+
+            temperatures.cores.extend(
+                sensors
+                    .into_iter()
+                    .filter(|sensor| sensor.label.starts_with("Tccd")),
+            );
+        }
+
+        temperatures
+    }
+
+    /// ARM boards and VMs without a hwmon driver often still have a CPU thermal zone
+    fn read_thermal_zones(sys_root: &Path) -> Self {
+        let mut temperatures = Self::default();
+
+        for zone in sorted_entries(&sys_root.join("class/thermal"), "thermal_zone") {
+            let Some(kind) = read_trimmed(&zone.join("type")) else {
+                continue;
+            };
+
+            if kind != "x86_pkg_temp" && !kind.contains("cpu") && !kind.contains("soc") {
+                continue;
+            }
+
+            let Some(celsius) = read_millidegrees(&zone.join("temp")) else {
+                continue;
+            };
+
+            let trip_point = |trip_type: &str| {
+                (0..)
+                    .map_while(|index| {
+                        let kind = read_trimmed(&zone.join(format!("trip_point_{}_type", index)))?;
+                        Some((index, kind))
+                    })
+                    .find(|(_, kind)| kind == trip_type)
+                    .and_then(|(index, _)| {
+                        read_millidegrees(&zone.join(format!("trip_point_{}_temp", index)))
+                    })
+            };
+
+            temperatures.packages.push(Temperature {
+                label: kind.clone(),
+                celsius,
+                // Passive is where the kernel starts throttling, for zones without a hot trip point
+                high: trip_point("hot").or_else(|| trip_point("passive")),
+                critical: trip_point("critical"),
+            });
+        }
+
+        temperatures
+    }
+}
+
+/// The temperature sensors of a hwmon device, in the order the driver numbers them
+fn hwmon_sensors(hwmon: &Path) -> Vec<Temperature> {
+    let mut indexes: Vec<u32> = fs::read_dir(hwmon)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.ok()?.file_name();
+
+            file_name
+                .to_str()?
+                .strip_prefix("temp")?
+                .strip_suffix("_input")?
+                .parse()
+                .ok()
+        })
+        .collect();
+
+    indexes.sort();
+
+    indexes
+        .into_iter()
+        .filter_map(|index| {
+            let file = |suffix: &str| hwmon.join(format!("temp{}_{}", index, suffix));
+
+            Some(Temperature {
+                label: read_trimmed(&file("label")).unwrap_or_else(|| format!("temp{}", index)),
+                celsius: read_millidegrees(&file("input"))?,
+                high: read_millidegrees(&file("max")),
+                critical: read_millidegrees(&file("crit")),
+            })
+        })
+        .collect()
+}
+
+/// The entries of a directory starting with the prefix, ordered by the number after it so hwmon10 comes after hwmon9
+fn sorted_entries(directory: &Path, prefix: &str) -> Vec<PathBuf> {
+    let mut entries: Vec<(u32, PathBuf)> = fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let number = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix(prefix)?
+                .parse()
+                .ok()?;
+
+            Some((number, entry.path()))
+        })
+        .collect();
+
+    entries.sort_by_key(|(number, _)| *number);
+
+    entries.into_iter().map(|(_, path)| path).collect()
+}
+
+/// sysfs reports temperatures in thousandths of a degree
+fn read_millidegrees(path: &Path) -> Option<f32> {
+    let millidegrees: i64 = read_number(path)?;
+
+    Some(millidegrees as f32 / 1000.)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::utils::fixture;
+
+    use super::{CpuTemperatures, Temperature};
+
+    fn sys_root(name: &str) -> PathBuf {
+        fixture(&format!("sys/{}", name))
+    }
+
+    #[test]
+    fn intel_packages_and_cores() {
+        let temperatures = CpuTemperatures::read(&sys_root("intel"));
+
+        assert_eq!(
+            temperatures.packages,
+            vec![Temperature {
+                label: String::from("Package id 0"),
+                celsius: 54.,
+                high: Some(80.),
+                critical: Some(100.),
+            }]
+        );
+
+        let cores: Vec<(&str, f32)> = temperatures
+            .cores
+            .iter()
+            .map(|core| (core.label.as_str(), core.celsius))
+            .collect();
+
+        assert_eq!(cores, vec![("Core 0", 51.), ("Core 4", 49.5)]);
+    }
+
+    #[test]
+    fn amd_prefers_tdie_and_reads_ccds() {
+        let temperatures = CpuTemperatures::read(&sys_root("amd"));
+
+        let package = temperatures.package().expect("no package temperature");
+        assert_eq!(package.label, "Tdie");
+        assert_eq!(package.celsius, 62.25);
+        assert_eq!(package.critical, None);
+
+        assert_eq!(temperatures.cores.len(), 2);
+        assert_eq!(temperatures.cores[1].label, "Tccd2");
+    }
+
+    #[test]
+    fn thermal_zones_without_hwmon() {
+        let temperatures = CpuTemperatures::read(&sys_root("arm"));
+
+        assert_eq!(
+            temperatures.packages,
+            vec![Temperature {
+                label: String::from("cpu-thermal"),
+                celsius: 47.236,
+                high: Some(80.),
+                critical: Some(90.),
+            }]
+        );
+        assert!(temperatures.cores.is_empty());
+    }
+
+    #[test]
+    fn no_sensors() {
+        let temperatures = CpuTemperatures::read(&sys_root("missing"));
+
+        assert_eq!(temperatures, CpuTemperatures::default());
+        assert!(temperatures.package().is_none());
+    }
+}
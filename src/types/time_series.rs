@@ -0,0 +1,177 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, RwLock, RwLockReadGuard},
+    time::{Duration, SystemTime},
+};
+
+/// What the values of a series measure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Percent,
+    Bytes,
+    BytesPerSecond,
+    Celsius,
+    /// Like the load average, which counts runnable tasks
+    Tasks,
+}
+
+impl Unit {
+    /// As written next to exported values
+    pub fn name(&self) -> &'static str {
+        match self {
+            Unit::Percent => "percent",
+            Unit::Bytes => "bytes",
+            Unit::BytesPerSecond => "bytes/s",
+            Unit::Celsius => "celsius",
+            Unit::Tasks => "tasks",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub timestamp: SystemTime,
+    pub value: f64,
+}
+
+impl Sample {
+    /// How long before the given time the sample was taken, as a negative number of seconds for chart x axes
+    pub fn seconds_before(&self, time: SystemTime) -> f64 {
+        -time
+            .duration_since(self.timestamp)
+            .unwrap_or_default()
+            .as_secs_f64()
+    }
+}
+
+/// Timestamped values of one metric, dropping samples older than the retention as new ones are pushed.
+/// Clones share the same samples, so a chart can hold one and always draw the latest data without copying it
+#[derive(Debug, Clone)]
+pub struct TimeSeries {
+    unit: Unit,
+    retention: Duration,
+    samples: Arc<RwLock<VecDeque<Sample>>>,
+}
+
+impl TimeSeries {
+    pub fn new(unit: Unit, retention: Duration) -> Self {
+        Self {
+            unit,
+            retention,
+            samples: Arc::new(RwLock::new(VecDeque::new())),
+        }
+    }
+
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    pub fn retention(&self) -> Duration {
+        self.retention
+    }
+
+    /// Adds a sample and drops the ones that have fallen out of the retention
+    pub fn push(&mut self, timestamp: SystemTime, value: f64) {
+        let mut samples = self.samples.write().expect("time series lock poisoned");
+
+        while let Some(oldest) = samples.front() {
+            // A clock that went backwards doesn't age samples
+            let age = timestamp
+                .duration_since(oldest.timestamp)
+                .unwrap_or_default();
+
+            if age <= self.retention {
+                break;
+            }
+
+            samples.pop_front();
+        }
+
+        samples.push_back(Sample { timestamp, value });
+    }
+
+    /// Oldest first
+    pub fn samples(&self) -> RwLockReadGuard<'_, VecDeque<Sample>> {
+        self.samples.read().expect("time series lock poisoned")
+    }
+
+    pub fn latest(&self) -> Option<Sample> {
+        self.samples().back().copied()
+    }
+
+    /// The largest value, or 0 if there are no samples
+    pub fn max_value(&self) -> f64 {
+        self.samples()
+            .iter()
+            .map(|sample| sample.value)
+            .fold(0., f64::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::{TimeSeries, Unit};
+
+    fn at(seconds: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    fn values(series: &TimeSeries) -> Vec<f64> {
+        series.samples().iter().map(|sample| sample.value).collect()
+    }
+
+    #[test]
+    fn drops_samples_older_than_the_retention() {
+        let mut series = TimeSeries::new(Unit::Percent, Duration::from_secs(10));
+
+        for second in 0..=10 {
+            series.push(at(second), second as f64);
+        }
+
+        // Exactly as old as the retention is still kept
+        assert_eq!(series.samples().len(), 11);
+
+        series.push(at(12), 12.);
+
+        assert_eq!(values(&series).first(), Some(&2.));
+        assert_eq!(series.latest().map(|sample| sample.value), Some(12.));
+    }
+
+    #[test]
+    fn a_gap_drops_everything_before_it() {
+        let mut series = TimeSeries::new(Unit::Bytes, Duration::from_secs(10));
+
+        series.push(at(0), 1.);
+        series.push(at(5), 2.);
+        series.push(at(100), 3.);
+
+        assert_eq!(values(&series), vec![3.]);
+    }
+
+    #[test]
+    fn a_clock_going_backwards_keeps_samples() {
+        let mut series = TimeSeries::new(Unit::Percent, Duration::from_secs(10));
+
+        series.push(at(100), 1.);
+        series.push(at(50), 2.);
+
+        assert_eq!(values(&series), vec![1., 2.]);
+    }
+
+    #[test]
+    fn clones_share_samples() {
+        let mut series = TimeSeries::new(Unit::Percent, Duration::from_secs(10));
+        let chart = series.clone();
+
+        series.push(at(0), 4.);
+        series.push(at(1), 2.);
+
+        assert_eq!(chart.max_value(), 4.);
+        assert_eq!(
+            TimeSeries::new(Unit::Percent, Duration::ZERO).max_value(),
+            0.
+        );
+    }
+}
@@ -1,8 +1,19 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
 use crate::{
     constants::{BYTE_UNITS_BINARY, BYTE_UNITS_DECIMAL, FREQUENCY_UNITS},
     preferences::{ByteBase, Preferences},
 };
 
+/// Formats bytes per second, e.g. 1.5 MB/s
+pub fn format_rate(preferences: &Preferences, bytes_per_second: f64) -> String {
+    format!("{}/s", format_bytes(preferences, bytes_per_second as f32))
+}
+
 pub fn format_bytes(preferences: &Preferences, mut bytes: f32) -> String {
     let (units, factor) = match preferences.byte_base {
         ByteBase::Decimal => {
@@ -83,4 +94,185 @@ pub fn format_hz(preferences: &Preferences, mut hz: f32) -> String {
         i += 1;
     }
     format!("{:.2} {}", hz, units[i])
-}
\ No newline at end of file
+}
+
+/// Formats degrees Celsius, e.g. 54.5℃
+pub fn format_celsius(celsius: f32) -> String {
+    format!("{:.1}℃", celsius)
+}
+
+/// Formats seconds since the unix epoch as a UTC date and time, e.g. 2024-05-01 13:45:10 UTC
+pub fn format_unix_time(seconds: u64) -> String {
+    let days = (seconds / 86400) as i64;
+    let seconds_of_day = seconds % 86400;
+
+    // Convert days since the epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60
+    )
+}
+
+/// Formats a duration in seconds as the largest relevant units, e.g. 2d 4h 10m or 5m 3s
+pub fn format_duration(seconds: u64) -> String {
+    let days = seconds / 86400;
+    let hours = seconds / 3600 % 24;
+    let minutes = seconds / 60 % 60;
+
+    if days > 0 {
+        return format!("{}d {}h {}m", days, hours, minutes);
+    }
+
+    if hours > 0 {
+        return format!("{}h {}m", hours, minutes);
+    }
+
+    format!("{}m {}s", minutes, seconds % 60)
+}
+
+/// Where our settings are stored, $XDG_CONFIG_HOME/c-tasks or ~/.config/c-tasks
+pub fn config_dir() -> Option<PathBuf> {
+    let config_home = match env::var("XDG_CONFIG_HOME") {
+        Ok(config_home) if !config_home.is_empty() => PathBuf::from(config_home),
+        _ => PathBuf::from(env::var("HOME").ok()?).join(".config"),
+    };
+
+    Some(config_home.join("c-tasks"))
+}
+
+/// Where our data, like history, is stored, $XDG_DATA_HOME/c-tasks or ~/.local/share/c-tasks
+pub fn data_dir() -> Option<PathBuf> {
+    let data_home = match env::var("XDG_DATA_HOME") {
+        Ok(data_home) if !data_home.is_empty() => PathBuf::from(data_home),
+        _ => PathBuf::from(env::var("HOME").ok()?).join(".local/share"),
+    };
+
+    Some(data_home.join("c-tasks"))
+}
+
+/// Reads a small file like the ones in sysfs, without the trailing newline
+pub fn read_trimmed(path: &Path) -> Option<String> {
+    Some(fs::read_to_string(path).ok()?.trim().to_string())
+}
+
+/// Reads a file holding a single number, like most of sysfs
+pub fn read_number<T: FromStr>(path: &Path) -> Option<T> {
+    read_trimmed(path)?.parse().ok()
+}
+
+/// A path under tests/fixtures
+#[cfg(test)]
+pub fn fixture(path: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(path)
+}
+
+/// Quotes a field if it has characters CSV gives a meaning to, like the commas a process name can have
+pub fn csv_field(field: &str) -> String {
+    if !field.contains([',', '"', '\n', '\r']) {
+        return field.to_string();
+    }
+
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::preferences::{ByteBase, Preferences};
+
+    use super::*;
+
+    fn preferences(byte_base: ByteBase) -> Preferences {
+        let mut preferences = Preferences::default();
+        preferences.byte_base = byte_base;
+        preferences
+    }
+
+    #[test]
+    fn bytes_in_binary_and_decimal() {
+        let binary = preferences(ByteBase::Binary);
+        let decimal = preferences(ByteBase::Decimal);
+
+        assert_eq!(format_bytes(&binary, 0.), "0.00 B");
+        assert_eq!(format_bytes(&binary, 1023.), "1023.00 B");
+        assert_eq!(format_bytes(&binary, 1536.), "1.50 KiB");
+        assert_eq!(format_bytes(&binary, 4. * 1024. * 1024. * 1024.), "4.00 GiB");
+
+        assert_eq!(format_bytes(&decimal, 1500.), "1.50 KB");
+        assert_eq!(format_bytes(&decimal, 2_000_000.), "2.00 MB");
+    }
+
+    #[test]
+    fn rates() {
+        let binary = preferences(ByteBase::Binary);
+
+        assert_eq!(format_rate(&binary, 512.), "512.00 B/s");
+        assert_eq!(format_rate(&binary, 3. * 1024. * 1024.), "3.00 MiB/s");
+    }
+
+    #[test]
+    fn bytes_lists_share_the_smallest_unit() {
+        let binary = preferences(ByteBase::Binary);
+
+        let (bytes, unit) = round_bytes_list(&binary, vec![2048., 1024. * 1024. * 3.]);
+
+        assert_eq!(unit, "KiB");
+        assert_eq!(bytes, vec!["2.00 KiB", "3072.00 KiB"]);
+    }
+
+    #[test]
+    fn frequencies() {
+        let preferences = Preferences::default();
+
+        assert_eq!(format_hz(&preferences, 800.), "800.00 MHz");
+        assert_eq!(format_hz(&preferences, 3400.), "3.40 GHz");
+    }
+
+    #[test]
+    fn temperatures() {
+        assert_eq!(format_celsius(54.), "54.0℃");
+        assert_eq!(format_celsius(47.236), "47.2℃");
+    }
+
+    #[test]
+    fn unix_times() {
+        assert_eq!(format_unix_time(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_unix_time(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_unix_time(1_714_571_110), "2024-05-01 13:45:10 UTC");
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(0), "0m 0s");
+        assert_eq!(format_duration(303), "5m 3s");
+        assert_eq!(format_duration(3 * 3600 + 120), "3h 2m");
+        assert_eq!(format_duration(2 * 86400 + 4 * 3600 + 600), "2d 4h 10m");
+    }
+
+    #[test]
+    fn csv_fields() {
+        assert_eq!(csv_field("firefox"), "firefox");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}